@@ -0,0 +1,22 @@
+//! The purpose of `encrypt-credentials` is to bring the credentials in the database under the
+//! current master key.
+//! Credentials stored in plain text are encrypted and the data keys of credentials encrypted with
+//! a previous master key are rewrapped.
+//!
+//! To rotate the master key, configure the new key as `encryption.master_key` and move the old
+//! one to `encryption.previous_master_keys`, then run this job. Once it finished, the old key can
+//! be removed from the configuration.
+
+use config::Config;
+use database::{Database, Keyring};
+
+#[tokio::main]
+async fn main() {
+    let config = Config::load();
+    let encryption = &config.encryption;
+    let keyring = Keyring::new(&encryption.master_key, &encryption.previous_master_keys);
+    let db = Database::connect_and_init(config.postgres_url.as_str(), keyring).await;
+
+    let rewrapped = db.conn().await.rewrap_credentials().await;
+    println!("Encrypted or rewrapped {} credentials", rewrapped);
+}
@@ -1,30 +1,35 @@
 //! The purpose of `sync` is to synchronize the local database with external sources.
-//! In particular, it will synchronize all installation repositories from GitHub.
+//! In particular, it will synchronize all installations of the GitHub App and their repositories.
 //! This is useful when the local database is out of date because of a reinstallation or an outage.
 
 use uuid::Uuid;
 
 use config::Config;
-use database::{Database, NewInstallation, NewRepository, NewUser, UserRole};
+use database::{Database, Keyring, NewInstallation, NewRepository, NewUser, UserRole};
 use github::{AccountType, GitHub, User};
 
-async fn synchronize_installations(config: Config, db: Database, github: GitHub) {
-    let installation_id = config.github_app_installation_id;
+async fn synchronize_installations(db: Database, github: GitHub) {
     let app_jwt = github.github_app_jwt();
-    let github_installations = github.installations(&app_jwt).await;
-    let github_installation = github_installations
-        .into_iter()
-        .find(|installation| installation.id == installation_id)
-        .expect("GitHub App installation not found");
+    let github_installations =
+        github.installations(&app_jwt).await.expect("Failed to fetch app installations");
 
-    synchronize_installation(github_installation, db.clone(), github.clone()).await;
+    for github_installation in github_installations {
+        let account = github_installation.account.login.clone();
+        println!("Synchronizing installation on {}", account);
+        // Other installations are still synchronized if one fails
+        if let Err(err) =
+            synchronize_installation(github_installation, db.clone(), github.clone()).await
+        {
+            eprintln!("Failed to synchronize installation on {}: {}", account, err);
+        }
+    }
 }
 
 async fn synchronize_installation(
     github_installation: github::Installation,
     db: Database,
     github: GitHub,
-) {
+) -> github::Result<()> {
     let mut conn = db.conn().await;
 
     let installation = conn
@@ -35,21 +40,19 @@ async fn synchronize_installation(
         .await;
     let members = if github_installation.account.r#type == AccountType::Organization {
         let org = github_installation.account.login;
-        let app_jwt = github.github_app_jwt();
-        let access_token = github.installation_access_token(&app_jwt).await;
-        let github = github.with_access(&access_token.token);
-        let members = github.organization_members(&org).await;
-        let mut members_with_role = vec![];
-        for member in members {
-            let membership = github.organization_membership(&org, &member.login).await;
-            let role = match membership.role {
-                github::Role::Admin => UserRole::Admin,
-                github::Role::Member => UserRole::Member,
-                github::Role::Unknown => UserRole::Member,
-            };
-            members_with_role.push((member, role));
-        }
-        members_with_role
+        let github = github.with_installation_access(github_installation.id).await?;
+        let members = github.organization_members(&org).await?;
+        members
+            .into_iter()
+            .map(|member| {
+                let role = match member.role {
+                    github::Role::Admin => UserRole::Admin,
+                    github::Role::Member => UserRole::Member,
+                    github::Role::Unknown => UserRole::Member,
+                };
+                (member.user, role)
+            })
+            .collect()
     } else {
         vec![(
             User {
@@ -70,23 +73,22 @@ async fn synchronize_installation(
             github_access_token_expires_at: None,
         };
         let user = conn.update_or_add_user(new_user).await;
-        conn.add_installation_user(installation.id, user.id, role).await;
+        conn.update_or_add_installation_user(installation.id, user.id, role).await;
     }
 
     drop(conn);
 
-    synchronize_installation_repositories(installation.id, db, github).await;
+    synchronize_installation_repositories(installation.id, github_installation.id, db, github).await
 }
 
 async fn synchronize_installation_repositories(
     installation_id: Uuid,
+    github_installation_id: i64,
     db: Database,
     github: GitHub,
-) {
-    let jwt = github.github_app_jwt();
-    let access_token = github.installation_access_token(&jwt).await;
-    let github = github.with_access(&access_token.token);
-    let repos = github.installation_repositories().await;
+) -> github::Result<()> {
+    let github = github.with_installation_access(github_installation_id).await?;
+    let repos = github.installation_repositories().await?;
     let mut conn = db.conn().await;
     let new_repositories = repos
         .into_iter()
@@ -97,13 +99,16 @@ async fn synchronize_installation_repositories(
         })
         .collect();
     conn.update_or_add_installation_repositories(installation_id, new_repositories).await;
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() {
     let config = Config::load();
-    let db = Database::connect_and_init(config.postgres_url.as_str()).await;
+    let encryption = &config.encryption;
+    let keyring = Keyring::new(&encryption.master_key, &encryption.previous_master_keys);
+    let db = Database::connect_and_init(config.postgres_url.as_str(), keyring).await;
     let github = GitHub::new(config.clone());
 
-    synchronize_installations(config, db, github).await;
+    synchronize_installations(db, github).await;
 }
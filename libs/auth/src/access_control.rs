@@ -1,3 +1,5 @@
+use std::fmt;
+
 use uuid::Uuid;
 
 use database::{Database, InstallationRepository, User};
@@ -7,33 +9,71 @@ pub struct CreateTask {
     pub inst_repo: InstallationRepository,
 }
 
-/// Check that the user can create a task for the given repository.
+/// Why a user may not create a task for a repository
+#[derive(Debug, PartialEq, Eq)]
+pub enum CreateTaskDenied {
+    /// The app is not installed on the repository
+    RepositoryNotInstalled,
+    RepositoryNotActivated,
+    /// The user never signed in to the service
+    UnknownUser,
+    UserInactive,
+    NotMember,
+}
+
+impl fmt::Display for CreateTaskDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            CreateTaskDenied::RepositoryNotInstalled => {
+                "the app is not installed on this repository"
+            }
+            CreateTaskDenied::RepositoryNotActivated => "this repository is not activated",
+            CreateTaskDenied::UnknownUser => "you have not signed in yet",
+            CreateTaskDenied::UserInactive => "your account is deactivated",
+            CreateTaskDenied::NotMember => "you are not a member of this repository",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Check that the user can create a task for the given repository in the installation.
 pub async fn github_user_can_create_task(
     db: &Database,
+    github_installation_id: i64,
     github_user_id: &str,
     github_repo_id: &str,
-) -> Option<CreateTask> {
+) -> Result<CreateTask, CreateTaskDenied> {
     let mut conn = db.conn().await;
 
-    // User active: the user must be active
-    let user = conn.get_user_by_github_id(github_user_id).await.unwrap();
+    // Repository active: the repository must be installed and active
+    let inst_repo = conn
+        .installation_repository_by_github_id(github_installation_id, github_repo_id)
+        .await
+        .ok_or(CreateTaskDenied::RepositoryNotInstalled)?;
+    if !inst_repo.active {
+        return Err(CreateTaskDenied::RepositoryNotActivated);
+    }
+
+    // User active: the user must be known and active
+    let user =
+        conn.get_user_by_github_id(github_user_id).await.ok_or(CreateTaskDenied::UnknownUser)?;
     if !user.active {
-        return None;
+        return Err(CreateTaskDenied::UserInactive);
     }
 
     // Membership: the user must have repository membership via either:
     //   - `installation_users`
     //   - `installation_repository_users`
     let inst_repo = conn
-        .installation_repository_for_authorized_github_user(github_user_id, github_repo_id)
-        .await?;
-
-    // Repository active: the repository must be active
-    if !inst_repo.active {
-        return None;
-    }
-
-    Some(CreateTask { user, inst_repo })
+        .installation_repository_for_authorized_github_user(
+            inst_repo.installation_id,
+            github_user_id,
+            github_repo_id,
+        )
+        .await
+        .ok_or(CreateTaskDenied::NotMember)?;
+
+    Ok(CreateTask { user, inst_repo })
 }
 
 /// Check that the user is active.
@@ -85,3 +125,21 @@ pub async fn user_can_admin_repo(db: &Database, user_id: Uuid, repo_id: Uuid) ->
 
     true
 }
+
+/// Check that the user can administer the installation.
+pub async fn user_can_admin_installation(db: &Database, user_id: Uuid, inst_id: Uuid) -> bool {
+    let mut conn = db.conn().await;
+
+    // User active: the user must be active
+    let user = conn.get_user(&user_id).await;
+    if !user.active {
+        return false;
+    }
+
+    // Membership: the user must have `Admin` membership via `installation_users`
+    if !conn.user_is_admin_of_installation(user_id, inst_id).await {
+        return false;
+    }
+
+    true
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use pkcs8::der::asn1::OctetString;
 use pkcs8::der::Decode;
 use pkcs8::{ObjectIdentifier, PrivateKeyInfo, SubjectPublicKeyInfo};
@@ -29,18 +32,35 @@ pub struct Config {
     pub github_app_webhook_secret: String,
     /// Contents of the GitHub app private key
     pub github_app_private_key: String,
-    /// ID of the GitHub app installation on which the GitHub app is installed
-    /// Currently, only a single installation is supported
-    pub github_app_installation_id: i64,
-    /// ID of the GitHub organization on which the GitHub app is installed
-    pub github_app_organization_id: String,
-    /// Name of the GitHub organization on which the GitHub app is installed
-    pub github_app_organization_name: String,
     /// The handle of the GitHub bot including the leading '@', e.g. "@handle"
     pub github_bot_handle: String,
     /// The User-Agent string to use when making requests to the GitHub API
     pub github_api_user_agent: String,
+    /// Base URL of the GitHub web interface, defaults to https://github.com
+    ///
+    /// For GitHub Enterprise Server this is the instance, e.g. https://github.example.com
+    pub github_web_url: Option<Url>,
+    /// Base URL of the GitHub REST API, defaults to https://api.github.com
+    ///
+    /// For GitHub Enterprise Server this is e.g. https://github.example.com/api/v3
+    pub github_api_url: Option<Url>,
+    /// URL of the GitHub GraphQL API, defaults to `graphql` on the REST API URL
+    ///
+    /// For GitHub Enterprise Server this is e.g. https://github.example.com/api/graphql
+    pub github_graphql_url: Option<Url>,
+    /// Base URL of the GitHub OAuth endpoints, defaults to the web URL
+    pub github_oauth_url: Option<Url>,
+    /// Base URL of git smart HTTP remotes, defaults to the web URL
+    pub github_git_url: Option<Url>,
+    /// URL of the chat completions endpoint the LLM proxy forwards to, defaults to OpenRouter
+    pub llm_chat_completions_url: Option<Url>,
+    /// Limits on what agents may spend on LLM requests
+    pub llm_budget: LlmBudget,
+    /// OpenAI-compatible endpoints installations and repositories can choose instead of OpenRouter
+    pub llm_providers: Vec<LlmProvider>,
     pub postgres_url: Url,
+    /// Master keys the credentials stored in the database are encrypted with
+    pub encryption: Encryption,
     pub web_base_url: Url,
     // Default agent configuration
     pub default_agent_container_registry_host: String,
@@ -62,25 +82,65 @@ pub struct Config {
     pub s3_access_key: String,
     pub s3_secret_key: String,
     pub s3_prefix: String,
+    /// Address the bucket in the path instead of the host name, e.g. for MinIO
+    pub s3_force_path_style: bool,
     /// Directory of static files to serve
     pub static_dir: PathBuf,
 }
 
 impl Config {
-    /// Load the config from `config.toml`
+    /// Load the config from `config.toml`, or the file in `MINION_CONFIG_FILE`
     pub fn load() -> Self {
         let env_vars: ConfigEnvVars =
             envy::prefixed("MINION_").from_env::<ConfigEnvVars>().unwrap();
         let config_file_path = env_vars.config_file.unwrap_or("config.toml".into());
 
+        let mut config = Self::load_from(&config_file_path);
+        if let Some(web_base_url) = env_vars.web_base_url {
+            config.web_base_url = web_base_url;
+        }
+        config
+    }
+
+    /// Load the config from the file, without overrides from the environment
+    pub fn load_from(config_file_path: &Path) -> Self {
         let text = fs::read_to_string(config_file_path).unwrap();
         let file: ConfigFile = toml::from_str(&text).unwrap();
 
         let github_app_private_key = fs::read_to_string(file.github_app_private_key).unwrap();
 
+        let llm_budget = file.llm_budget.unwrap_or_default();
+        let llm_providers = file.llm_providers.unwrap_or_default();
+        for (i, provider) in llm_providers.iter().enumerate() {
+            assert!(
+                llm_providers[..i].iter().all(|other| other.name != provider.name),
+                "Duplicate LLM provider name: {}",
+                provider.name
+            );
+            if matches!(provider.auth, LlmProviderAuth::Bearer | LlmProviderAuth::Header(_)) {
+                assert!(
+                    provider.api_key.is_some(),
+                    "LLM provider {} needs an api_key",
+                    provider.name
+                );
+            }
+            // Only OpenRouter reports the cost of requests, which budgets are enforced on
+            if llm_budget.is_limited()
+                && !matches!(provider.auth, LlmProviderAuth::OpenRouterUserKey)
+            {
+                assert!(
+                    provider.token_prices.is_some(),
+                    "LLM provider {} needs token_prices to enforce the LLM budget",
+                    provider.name
+                );
+            }
+        }
+
         let (jwt_public_key, jwt_expanded_private_key) =
             load_jwt_keys(&file.jwt_private_key, &file.jwt_public_key);
 
+        let encryption = load_encryption(&file.encryption);
+
         Config {
             service_name: file.service_name,
             host: file.host,
@@ -96,13 +156,19 @@ impl Config {
             github_app_client_secret: file.github_app_client_secret,
             github_app_webhook_secret: file.github_app_webhook_secret,
             github_app_private_key,
-            github_app_installation_id: file.github_app_installation_id,
-            github_app_organization_id: file.github_app_organization_id,
-            github_app_organization_name: file.github_app_organization_name,
             github_bot_handle: file.github_bot_handle,
             github_api_user_agent: file.github_api_user_agent,
+            github_web_url: file.github_web_url,
+            github_api_url: file.github_api_url,
+            github_graphql_url: file.github_graphql_url,
+            github_oauth_url: file.github_oauth_url,
+            github_git_url: file.github_git_url,
+            llm_chat_completions_url: file.llm_chat_completions_url,
+            llm_budget,
+            llm_providers,
             postgres_url: file.postgres_url,
-            web_base_url: env_vars.web_base_url.unwrap_or(file.web_base_url),
+            encryption,
+            web_base_url: file.web_base_url,
             default_agent_container_registry_host: file.default_agent_container_registry_host,
             default_agent_container_registry_username: file
                 .default_agent_container_registry_username,
@@ -120,9 +186,39 @@ impl Config {
             s3_access_key: file.s3_access_key,
             s3_secret_key: file.s3_secret_key,
             s3_prefix: file.s3_prefix,
+            s3_force_path_style: file.s3_force_path_style.unwrap_or_default(),
             static_dir: file.static_dir,
         }
     }
+
+    /// The LLM provider with the given name
+    pub fn llm_provider(&self, name: &str) -> Option<&LlmProvider> {
+        self.llm_providers.iter().find(|provider| provider.name == name)
+    }
+}
+
+fn load_encryption(file: &EncryptionFile) -> Encryption {
+    let master_key = match (&file.master_key, &file.master_key_file) {
+        (Some(master_key), None) => master_key.clone(),
+        (None, Some(path)) => fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("Failed to read master key file: {}", path.display())),
+        _ => panic!("Configure either encryption.master_key or encryption.master_key_file"),
+    };
+    Encryption {
+        master_key: decode_master_key(&master_key),
+        previous_master_keys: file
+            .previous_master_keys
+            .iter()
+            .map(|key| decode_master_key(key))
+            .collect(),
+    }
+}
+
+fn decode_master_key(master_key: &str) -> [u8; 32] {
+    let master_key = BASE64.decode(master_key.trim()).expect("Master key is not base64");
+    master_key.try_into().unwrap_or_else(|key: Vec<u8>| {
+        panic!("Invalid master key length: expected 32 bytes, got {} bytes", key.len())
+    })
 }
 
 fn load_jwt_keys(private_key_path: &Path, public_key_path: &Path) -> (Vec<u8>, Vec<u8>) {
@@ -231,18 +327,35 @@ struct ConfigFile {
     pub github_app_webhook_secret: String,
     /// Path to the GitHub app private key
     pub github_app_private_key: String,
-    /// ID of the GitHub app installation on which the GitHub app is installed
-    /// Currently, only a single installation is supported
-    pub github_app_installation_id: i64,
-    /// ID of the GitHub organization on which the GitHub app is installed
-    pub github_app_organization_id: String,
-    /// Name of the GitHub organization on which the GitHub app is installed
-    pub github_app_organization_name: String,
     /// The handle of the GitHub bot including the leading '@', e.g. "@handle"
     pub github_bot_handle: String,
     /// The User-Agent string to use when making requests to the GitHub API
     pub github_api_user_agent: String,
+    /// Base URL of the GitHub web interface, defaults to https://github.com
+    ///
+    /// For GitHub Enterprise Server this is the instance, e.g. https://github.example.com
+    pub github_web_url: Option<Url>,
+    /// Base URL of the GitHub REST API, defaults to https://api.github.com
+    ///
+    /// For GitHub Enterprise Server this is e.g. https://github.example.com/api/v3
+    pub github_api_url: Option<Url>,
+    /// URL of the GitHub GraphQL API, defaults to `graphql` on the REST API URL
+    ///
+    /// For GitHub Enterprise Server this is e.g. https://github.example.com/api/graphql
+    pub github_graphql_url: Option<Url>,
+    /// Base URL of the GitHub OAuth endpoints, defaults to the web URL
+    pub github_oauth_url: Option<Url>,
+    /// Base URL of git smart HTTP remotes, defaults to the web URL
+    pub github_git_url: Option<Url>,
+    /// URL of the chat completions endpoint the LLM proxy forwards to, defaults to OpenRouter
+    pub llm_chat_completions_url: Option<Url>,
+    /// Limits on what agents may spend on LLM requests, unlimited if missing
+    pub llm_budget: Option<LlmBudget>,
+    /// OpenAI-compatible endpoints installations and repositories can choose instead of OpenRouter
+    pub llm_providers: Option<Vec<LlmProvider>>,
     pub postgres_url: Url,
+    /// Master keys the credentials stored in the database are encrypted with
+    pub encryption: EncryptionFile,
     pub web_base_url: Url,
     // Default agent configuration
     pub default_agent_container_registry_host: String,
@@ -264,10 +377,96 @@ struct ConfigFile {
     pub s3_access_key: String,
     pub s3_secret_key: String,
     pub s3_prefix: String,
+    /// Address the bucket in the path instead of the host name, e.g. for MinIO
+    pub s3_force_path_style: Option<bool>,
     /// Directory of static files to serve
     pub static_dir: PathBuf,
 }
 
+/// Master keys of the envelope encryption of the credentials stored in the database
+#[derive(Clone)]
+pub struct Encryption {
+    /// The key new values are encrypted with
+    pub master_key: [u8; 32],
+    /// Keys that were rotated out, values still encrypted with them can be read until rewrapped
+    pub previous_master_keys: Vec<[u8; 32]>,
+}
+
+/// The encryption section of the configuration file, keys are base64 of 32 random bytes
+#[derive(Clone, Deserialize)]
+struct EncryptionFile {
+    pub master_key: Option<String>,
+    /// Path to a file holding the master key, instead of `master_key`
+    pub master_key_file: Option<PathBuf>,
+    #[serde(default)]
+    pub previous_master_keys: Vec<String>,
+}
+
+/// Limits on LLM spending in USD, requests of a task are rejected once a limit is reached
+#[derive(Clone, Deserialize, Default)]
+pub struct LlmBudget {
+    /// The most a single task may spend
+    pub task: Option<f64>,
+    /// The most the tasks a user created may spend per UTC day
+    pub user_daily: Option<f64>,
+    /// The most the tasks on a repository may spend per UTC day
+    pub repository_daily: Option<f64>,
+}
+
+impl LlmBudget {
+    /// Whether any limit is set
+    pub fn is_limited(&self) -> bool {
+        self.task.is_some() || self.user_daily.is_some() || self.repository_daily.is_some()
+    }
+}
+
+/// An OpenAI-compatible chat completions endpoint, e.g. a vLLM server or an internal gateway
+#[derive(Clone, Deserialize)]
+pub struct LlmProvider {
+    /// The name installations and repositories choose the provider by
+    pub name: String,
+    /// URL of the chat completions endpoint, including any query the provider needs
+    pub chat_completions_url: Url,
+    /// How the proxy authenticates against the provider
+    pub auth: LlmProviderAuth,
+    /// Key sent as bearer token with `Bearer` auth or in the header with `Header` auth
+    pub api_key: Option<String>,
+    /// Model names agents ask for, mapped to the names the provider knows the models by
+    #[serde(default)]
+    pub models: HashMap<String, String>,
+    /// GitHub ids of the installations that may use the provider, all installations if unset
+    pub installations: Option<Vec<i64>>,
+    /// Prices the cost of requests is computed from when the provider doesn't report it
+    pub token_prices: Option<TokenPrices>,
+}
+
+/// Prices of tokens in USD per million tokens
+#[derive(Clone, Deserialize)]
+pub struct TokenPrices {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl LlmProvider {
+    /// Whether the installation with the given GitHub id may use the provider
+    pub fn allows_installation(&self, github_id: i64) -> bool {
+        self.installations.as_ref().map_or(true, |installations| installations.contains(&github_id))
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub enum LlmProviderAuth {
+    /// The OpenRouter key of the installation or of the user who created the task, whichever the
+    /// installation prefers if both have one
+    OpenRouterUserKey,
+    /// The `api_key` of the provider as bearer token
+    Bearer,
+    /// The `api_key` of the provider in the header with the given name, e.g. `api-key` for Azure
+    Header(String),
+    /// No credentials, e.g. for a server only reachable from the service
+    None,
+}
+
 #[derive(Clone, Deserialize)]
 pub enum AccessControl {
     /// Only users with allowed email addresses can sign in
@@ -0,0 +1 @@
+alter table tasks drop column replay_of_task_id;
@@ -0,0 +1,3 @@
+-- A replay task is served the recorded LLM responses of an earlier task
+alter table tasks
+add column replay_of_task_id uuid references tasks (id) on delete set null;
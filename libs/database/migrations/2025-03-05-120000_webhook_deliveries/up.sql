@@ -0,0 +1,21 @@
+create type webhook_delivery_status as enum (
+    'processing',
+    'processed',
+    'failed'
+);
+
+-- Deliveries of GitHub webhooks, used to skip redeliveries that were already processed
+create table webhook_deliveries (
+    id uuid primary key default uuidv7(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    -- Value of the X-GitHub-Delivery header
+    github_delivery_id text not null unique,
+    event text not null,
+    action text,
+    installation_github_id bigint,
+    status webhook_delivery_status not null default 'processing',
+    error text
+);
+
+select diesel_manage_updated_at('webhook_deliveries');
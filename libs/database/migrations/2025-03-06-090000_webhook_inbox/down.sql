@@ -0,0 +1,25 @@
+drop index webhook_deliveries_status_next_attempt_at_idx;
+
+alter table webhook_deliveries
+    drop column payload,
+    drop column attempts,
+    drop column next_attempt_at;
+
+-- Enum values can't be dropped, so the type is recreated without them
+delete from webhook_deliveries where status in ('queued', 'dead');
+
+alter type webhook_delivery_status rename to webhook_delivery_status_old;
+
+create type webhook_delivery_status as enum (
+    'processing',
+    'processed',
+    'failed'
+);
+
+alter table webhook_deliveries
+    alter column status drop default,
+    alter column status type webhook_delivery_status
+    using status::text::webhook_delivery_status,
+    alter column status set default 'processing';
+
+drop type webhook_delivery_status_old;
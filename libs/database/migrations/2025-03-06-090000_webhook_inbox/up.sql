@@ -0,0 +1,12 @@
+alter type webhook_delivery_status add value 'queued';
+alter type webhook_delivery_status add value 'dead';
+
+-- Deliveries are stored with their payload and processed by a background worker
+alter table webhook_deliveries
+    add column payload jsonb,
+    add column attempts integer not null default 0,
+    add column next_attempt_at timestamptz not null default now();
+
+create index webhook_deliveries_status_next_attempt_at_idx on webhook_deliveries (
+    status, next_attempt_at
+);
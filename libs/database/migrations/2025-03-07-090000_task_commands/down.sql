@@ -0,0 +1,21 @@
+alter table tasks
+    drop column base_ref,
+    drop column model;
+
+-- Enum values can't be dropped, so the type is recreated without them
+update tasks set status = 'failed' where status = 'cancelled';
+
+alter type task_status rename to task_status_old;
+
+create type task_status as enum (
+    'queued',
+    'running',
+    'completed',
+    'failed'
+);
+
+alter table tasks
+    alter column status type task_status
+    using status::text::task_status;
+
+drop type task_status_old;
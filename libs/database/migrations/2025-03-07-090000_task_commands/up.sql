@@ -0,0 +1,6 @@
+alter type task_status add value 'cancelled';
+
+-- Options given with the command that created the task
+alter table tasks
+    add column base_ref text,
+    add column model text;
@@ -0,0 +1 @@
+alter table installations_repositories drop column trigger_label;
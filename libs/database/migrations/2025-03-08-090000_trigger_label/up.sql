@@ -0,0 +1,3 @@
+-- Labeling an issue with the trigger label queues a task, no label disables the trigger
+alter table installations_repositories
+add column trigger_label text;
@@ -0,0 +1,5 @@
+drop table task_review_comments;
+
+alter table tasks
+    drop column github_pull_request_id,
+    drop column follow_up_of_task_id;
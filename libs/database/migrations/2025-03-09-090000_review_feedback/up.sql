@@ -0,0 +1,22 @@
+-- Follow-up tasks continue the branch and pull request of an earlier task
+alter table tasks
+    add column github_pull_request_id text,
+    add column follow_up_of_task_id uuid references tasks (id) on delete set null;
+
+-- Review comments that a follow-up task addresses
+create table task_review_comments (
+    id uuid primary key default uuidv7(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    task_id uuid references tasks (id) on delete cascade not null,
+    github_comment_id text not null,
+    author_login text not null,
+    body text not null,
+    -- Unset for comments on the pull request as a whole
+    path text,
+    line integer,
+    diff_hunk text,
+    unique (task_id, github_comment_id)
+);
+
+select diesel_manage_updated_at('task_review_comments');
@@ -0,0 +1 @@
+alter table tasks drop column github_status_comment_id;
@@ -0,0 +1,2 @@
+-- The comment that reports the status of the task, updated in place
+alter table tasks add column github_status_comment_id text;
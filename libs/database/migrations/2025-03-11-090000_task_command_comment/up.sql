@@ -0,0 +1,2 @@
+-- The comment that asked for the task with a command, passed on to the agent
+alter table tasks add column github_command_comment text;
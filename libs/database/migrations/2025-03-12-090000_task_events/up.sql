@@ -0,0 +1,17 @@
+-- Progress the agent reports while it works on a task
+create table task_events (
+    id uuid primary key default uuidv7(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    task_id uuid references tasks (id) on delete cascade not null,
+    step text not null,
+    message text,
+    -- Between 0 and 100, unset if the agent can't tell
+    percent smallint check (percent between 0 and 100),
+    -- Milestones are mirrored into the status comment on GitHub
+    milestone boolean not null default false
+);
+
+create index task_events_task_id_idx on task_events (task_id);
+
+select diesel_manage_updated_at('task_events');
@@ -0,0 +1,14 @@
+-- Files the agent uploaded for a task, the contents are in object storage
+create table task_artifacts (
+    id uuid primary key default uuidv7(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    task_id uuid references tasks (id) on delete cascade not null,
+    name text not null,
+    content_type text not null,
+    -- In bytes
+    size bigint not null,
+    unique (task_id, name)
+);
+
+select diesel_manage_updated_at('task_artifacts');
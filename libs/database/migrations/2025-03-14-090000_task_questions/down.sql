@@ -0,0 +1,20 @@
+drop table task_questions;
+
+-- Enum values can't be dropped, so the type is recreated without them
+update tasks set status = 'failed' where status = 'waiting_for_input';
+
+alter type task_status rename to task_status_old;
+
+create type task_status as enum (
+    'queued',
+    'running',
+    'completed',
+    'failed',
+    'cancelled'
+);
+
+alter table tasks
+    alter column status type task_status
+    using status::text::task_status;
+
+drop type task_status_old;
@@ -0,0 +1,17 @@
+-- Tasks whose agent asked a question on the issue wait for the answer without a virtual machine
+alter type task_status add value 'waiting_for_input';
+
+create table task_questions (
+    id uuid primary key default uuidv7(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    task_id uuid references tasks (id) on delete cascade not null,
+    question text not null,
+    -- The comment on the issue that asks the question
+    github_comment_id text not null,
+    -- Unset until the issue author replies
+    answer text,
+    answer_author_login text
+);
+
+select diesel_manage_updated_at('task_questions');
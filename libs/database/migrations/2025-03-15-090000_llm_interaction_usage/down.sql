@@ -0,0 +1,4 @@
+alter table llm_interactions
+    drop column prompt_tokens,
+    drop column completion_tokens,
+    drop column cost;
@@ -0,0 +1,5 @@
+-- Token usage and cost the LLM provider reported for an interaction, cost in USD
+alter table llm_interactions
+    add column prompt_tokens integer,
+    add column completion_tokens integer,
+    add column cost double precision;
@@ -0,0 +1,2 @@
+alter table installations_repositories drop column llm_provider;
+alter table installations drop column llm_provider;
@@ -0,0 +1,3 @@
+-- Name of the configured LLM provider the tasks use, the one of the repository takes precedence
+alter table installations add column llm_provider text;
+alter table installations_repositories add column llm_provider text;
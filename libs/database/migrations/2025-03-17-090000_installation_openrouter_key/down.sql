@@ -0,0 +1,8 @@
+alter table llm_interactions drop column key_source;
+
+alter table installations
+    drop column openrouter_key,
+    drop column llm_key_precedence;
+
+drop type llm_key_source;
+drop type llm_key_precedence;
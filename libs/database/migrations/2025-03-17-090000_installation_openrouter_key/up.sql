@@ -0,0 +1,18 @@
+-- Which OpenRouter key pays for the tasks of an installation when both are set
+create type llm_key_precedence as enum (
+    'installation_first',
+    'user_first'
+);
+
+-- Where the key an LLM request was authenticated with came from
+create type llm_key_source as enum (
+    'installation',
+    'user',
+    'provider'
+);
+
+alter table installations
+    add column openrouter_key text,
+    add column llm_key_precedence llm_key_precedence not null default 'installation_first';
+
+alter table llm_interactions add column key_source llm_key_source;
@@ -0,0 +1 @@
+alter table agent_configs drop column installation_id;
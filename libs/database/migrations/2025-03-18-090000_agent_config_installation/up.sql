@@ -0,0 +1,3 @@
+-- Agent configs hold registry credentials, so only the installation owning one may use it
+alter table agent_configs
+    add column installation_id uuid references installations (id) on delete cascade;
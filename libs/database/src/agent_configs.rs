@@ -1,4 +1,4 @@
-use diesel::{ExpressionMethods, QueryDsl};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
@@ -10,7 +10,7 @@ impl Conn<'_> {
     /// Add a new `AgentConfig` to the database.
     pub async fn add_agent_config(&mut self, new_agent_config: NewAgentConfig) -> AgentConfig {
         diesel::insert_into(agent_configs)
-            .values(new_agent_config)
+            .values(new_agent_config.encrypted())
             .get_result(&mut self.conn)
             .await
             .unwrap()
@@ -18,6 +18,7 @@ impl Conn<'_> {
 
     /// Update an existing `AgentConfig` in the database.
     pub async fn update_agent_config(&mut self, update: UpdateAgentConfig) -> AgentConfig {
+        let update = update.encrypted();
         diesel::update(&update).set(&update).get_result(&mut self.conn).await.unwrap()
     }
 
@@ -26,6 +27,16 @@ impl Conn<'_> {
         agent_configs.filter(id.eq(agent_config_id)).get_result(&mut self.conn).await.unwrap()
     }
 
+    /// Retrieve an `AgentConfig` by its ID, if it exists.
+    pub async fn find_agent_config(&mut self, agent_config_id: &Uuid) -> Option<AgentConfig> {
+        agent_configs
+            .filter(id.eq(agent_config_id))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
     /// Delete an `AgentConfig` by its ID.
     pub async fn delete_agent_config(&mut self, agent_config_id: &Uuid) -> usize {
         diesel::delete(agent_configs.filter(id.eq(agent_config_id)))
@@ -8,6 +8,8 @@ use diesel_async::SimpleAsyncConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use url::Url;
 
+use crate::Keyring;
+
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
 #[derive(Clone)]
@@ -20,7 +22,9 @@ impl Database {
     ///
     /// This function only establishes a connection to the database and doesn't run any migrations.
     /// It requires the database to already exist and be up-to-date.
-    pub async fn connect(url: &str) -> Self {
+    /// The keyring encrypts and decrypts the credentials stored in the database.
+    pub async fn connect(url: &str, keyring: Keyring) -> Self {
+        keyring.install();
         let mgr = AsyncDieselConnectionManager::<AsyncPgConnection>::new(url);
         let pool = Pool::builder().build(mgr).await.unwrap();
         Self { pool }
@@ -29,7 +33,9 @@ impl Database {
     /// Connect and initialize the database
     ///
     /// This function creates the database if it doesn't exist and runs all pending migrations.
-    pub async fn connect_and_init(url: &str) -> Self {
+    /// The keyring encrypts and decrypts the credentials stored in the database.
+    pub async fn connect_and_init(url: &str, keyring: Keyring) -> Self {
+        keyring.install();
         log::info!("Connecting to database at {}", url);
         let db_name = extract_db_name(url);
         let postgres_connection_string = replace_db_name(url, "postgres");
@@ -0,0 +1,55 @@
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::encryption::keyring;
+use crate::schema::{agent_configs, installations, users};
+
+/// Rewrap the values of a credential column under the current master key
+///
+/// A value is only replaced if it didn't change in the meantime. Evaluates to the number of
+/// rewrapped values.
+macro_rules! rewrap_column {
+    ($conn:expr, $table:ident, $column:ident) => {{
+        let rows: Vec<(Uuid, Option<String>)> = $table::table
+            .select(($table::id, $table::$column))
+            .filter($table::$column.is_not_null())
+            .load(&mut $conn.conn)
+            .await
+            .expect(concat!("Error loading ", stringify!($table), ".", stringify!($column)));
+
+        let mut rewrapped = 0;
+        for (id, value) in rows {
+            let Some(value) = value else { continue };
+            let new_value = keyring().rewrap(&value).unwrap_or_else(|err| {
+                panic!(
+                    "Failed to rewrap {}.{} of {}: {}",
+                    stringify!($table),
+                    stringify!($column),
+                    id,
+                    err
+                )
+            });
+            let Some(new_value) = new_value else { continue };
+            rewrapped += diesel::update($table::table.find(id).filter($table::$column.eq(&value)))
+                .set($table::$column.eq(new_value))
+                .execute(&mut $conn.conn)
+                .await
+                .expect(concat!("Error updating ", stringify!($table), ".", stringify!($column)));
+        }
+        rewrapped
+    }};
+}
+
+impl Conn<'_> {
+    /// Encrypt the credentials stored in plain text and rewrap the ones encrypted under an earlier
+    /// master key, returns the number of values written
+    pub async fn rewrap_credentials(&mut self) -> usize {
+        rewrap_column!(self, users, github_access_token)
+            + rewrap_column!(self, users, openrouter_key)
+            + rewrap_column!(self, users, openrouter_code_verifier)
+            + rewrap_column!(self, installations, openrouter_key)
+            + rewrap_column!(self, agent_configs, container_registry_password)
+    }
+}
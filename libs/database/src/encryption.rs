@@ -0,0 +1,189 @@
+//! Envelope encryption of the credentials stored in the database
+//!
+//! Every value is encrypted with its own random data key, which is stored next to the value
+//! wrapped with the master key. Rotating the master key only rewraps the data keys.
+//!
+//! Values are stored as `enc:v1:<master key id>:<wrapped data key>:<ciphertext>` with base64
+//! parts. Values without the prefix were stored before encryption and are read as they are.
+
+use std::sync::OnceLock;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use diesel::deserialize::{self, Queryable};
+use diesel::pg::Pg;
+use diesel::sql_types::{Nullable, Text};
+use sha2::{Digest, Sha256};
+
+mod tests;
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+static KEYRING: OnceLock<Keyring> = OnceLock::new();
+
+/// The master key credentials are encrypted with and earlier ones they may still be encrypted with
+pub struct Keyring {
+    current: MasterKey,
+    previous: Vec<MasterKey>,
+}
+
+struct MasterKey {
+    /// Names the key in stored values without revealing it
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl MasterKey {
+    fn new(key: &[u8; 32]) -> Self {
+        let digest = Sha256::digest(key);
+        let id = digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+        MasterKey { id, cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)) }
+    }
+}
+
+/// The parts of an encrypted value
+struct Envelope<'a> {
+    key_id: &'a str,
+    wrapped_key: Vec<u8>,
+    ciphertext: &'a str,
+}
+
+impl<'a> Envelope<'a> {
+    /// `None` if the value isn't encrypted
+    fn parse(value: &'a str) -> Result<Option<Self>, String> {
+        let Some(envelope) = value.strip_prefix(PREFIX) else {
+            return Ok(None);
+        };
+        let mut parts = envelope.splitn(3, ':');
+        let (Some(key_id), Some(wrapped_key), Some(ciphertext)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err("Malformed encrypted value".to_owned());
+        };
+        let wrapped_key =
+            BASE64.decode(wrapped_key).map_err(|_| "Malformed wrapped data key".to_owned())?;
+        Ok(Some(Envelope { key_id, wrapped_key, ciphertext }))
+    }
+}
+
+impl Keyring {
+    pub fn new(master_key: &[u8; 32], previous_master_keys: &[[u8; 32]]) -> Self {
+        Keyring {
+            current: MasterKey::new(master_key),
+            previous: previous_master_keys.iter().map(MasterKey::new).collect(),
+        }
+    }
+
+    /// Use the keyring for the credentials of all connections of the process
+    ///
+    /// Panics if a keyring with other master keys is in use already, credentials would be
+    /// encrypted with the wrong key otherwise.
+    pub(crate) fn install(self) {
+        let key_ids = self.key_ids();
+        let installed = KEYRING.get_or_init(|| self);
+        assert!(
+            installed.key_ids() == key_ids,
+            "A keyring with other master keys is already in use by this process"
+        );
+    }
+
+    /// Ids of the current and the previous master keys
+    fn key_ids(&self) -> Vec<String> {
+        let keys = std::iter::once(&self.current).chain(&self.previous);
+        keys.map(|key| key.id.clone()).collect()
+    }
+
+    pub(crate) fn encrypt(&self, plaintext: &str) -> String {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let ciphertext = seal(&Aes256Gcm::new(&data_key), plaintext.as_bytes());
+        self.envelope(&data_key, &BASE64.encode(ciphertext))
+    }
+
+    pub(crate) fn decrypt(&self, value: &str) -> Result<String, String> {
+        let Some(envelope) = Envelope::parse(value)? else {
+            return Ok(value.to_owned());
+        };
+        let data_key = self.unwrap_data_key(&envelope)?;
+        let ciphertext =
+            BASE64.decode(envelope.ciphertext).map_err(|_| "Malformed ciphertext".to_owned())?;
+        let plaintext = open(&Aes256Gcm::new(&data_key), &ciphertext)?;
+        String::from_utf8(plaintext).map_err(|_| "Decrypted value isn't UTF-8".to_owned())
+    }
+
+    /// The value encrypted under the current master key, `None` if it already is
+    ///
+    /// Plain text values are encrypted, values under an earlier master key get their data key
+    /// rewrapped.
+    pub(crate) fn rewrap(&self, value: &str) -> Result<Option<String>, String> {
+        let Some(envelope) = Envelope::parse(value)? else {
+            return Ok(Some(self.encrypt(value)));
+        };
+        if envelope.key_id == self.current.id {
+            return Ok(None);
+        }
+        let data_key = self.unwrap_data_key(&envelope)?;
+        Ok(Some(self.envelope(&data_key, envelope.ciphertext)))
+    }
+
+    fn envelope(&self, data_key: &Key<Aes256Gcm>, ciphertext: &str) -> String {
+        let wrapped_key = BASE64.encode(seal(&self.current.cipher, data_key.as_slice()));
+        format!("{PREFIX}{}:{}:{}", self.current.id, wrapped_key, ciphertext)
+    }
+
+    fn unwrap_data_key(&self, envelope: &Envelope) -> Result<Key<Aes256Gcm>, String> {
+        let master_key = (std::iter::once(&self.current).chain(&self.previous))
+            .find(|master_key| master_key.id == envelope.key_id)
+            .ok_or_else(|| format!("Unknown master key {}", envelope.key_id))?;
+        let data_key = open(&master_key.cipher, &envelope.wrapped_key)?;
+        if data_key.len() != 32 {
+            return Err("Invalid data key length".to_owned());
+        }
+        Ok(*Key::<Aes256Gcm>::from_slice(&data_key))
+    }
+}
+
+/// Encrypt with a random nonce that is prepended to the ciphertext
+fn seal(cipher: &Aes256Gcm, plaintext: &[u8]) -> Vec<u8> {
+    let nonce = Aes256Gcm::generate_nonce(OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext).expect("Encryption failed");
+    [nonce.as_slice(), ciphertext.as_slice()].concat()
+}
+
+fn open(cipher: &Aes256Gcm, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Encrypted value too short".to_owned());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    (cipher.decrypt(Nonce::from_slice(nonce), ciphertext))
+        .map_err(|_| "Decryption failed, the value or its key was tampered with".to_owned())
+}
+
+pub(crate) fn keyring() -> &'static Keyring {
+    KEYRING.get().expect("No keyring installed, connect to the database first")
+}
+
+/// Encrypt a credential before it is stored
+pub(crate) fn encrypt(value: Option<String>) -> Option<String> {
+    value.map(|value| keyring().encrypt(&value))
+}
+
+/// A credential column that is decrypted when it is loaded
+pub struct Decrypted(Option<String>);
+
+impl Queryable<Nullable<Text>, Pg> for Decrypted {
+    type Row = Option<String>;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        let value = row.map(|value| keyring().decrypt(&value)).transpose()?;
+        Ok(Decrypted(value))
+    }
+}
+
+impl From<Decrypted> for Option<String> {
+    fn from(value: Decrypted) -> Self {
+        value.0
+    }
+}
@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use base64::Engine;
+
+use super::{Keyring, BASE64};
+
+const OLD_KEY: [u8; 32] = [1; 32];
+const NEW_KEY: [u8; 32] = [2; 32];
+
+#[test]
+fn test_encrypt_and_decrypt() {
+    let keyring = Keyring::new(&NEW_KEY, &[]);
+
+    let encrypted = keyring.encrypt("sk-or-secret");
+
+    assert!(encrypted.starts_with("enc:v1:"), "Encrypted value {encrypted:?}");
+    assert!(!encrypted.contains("sk-or-secret"));
+    assert_eq!(keyring.decrypt(&encrypted).unwrap(), "sk-or-secret");
+    // Every value has its own data key and nonces
+    assert_ne!(keyring.encrypt("sk-or-secret"), encrypted);
+}
+
+#[test]
+fn test_plain_text_is_read_as_it_is() {
+    let keyring = Keyring::new(&NEW_KEY, &[]);
+
+    assert_eq!(keyring.decrypt("sk-or-secret").unwrap(), "sk-or-secret");
+}
+
+#[test]
+fn test_decrypt_with_unknown_key() {
+    let encrypted = Keyring::new(&OLD_KEY, &[]).encrypt("sk-or-secret");
+
+    assert!(Keyring::new(&NEW_KEY, &[]).decrypt(&encrypted).is_err());
+}
+
+#[test]
+fn test_decrypt_tampered_value() {
+    let keyring = Keyring::new(&NEW_KEY, &[]);
+    let encrypted = keyring.encrypt("sk-or-secret");
+    let (envelope, ciphertext) = encrypted.rsplit_once(':').unwrap();
+    let mut ciphertext = BASE64.decode(ciphertext).unwrap();
+    *ciphertext.last_mut().unwrap() ^= 1;
+    let tampered = format!("{envelope}:{}", BASE64.encode(ciphertext));
+
+    assert!(keyring.decrypt(&tampered).is_err());
+}
+
+#[test]
+fn test_rewrap_rotates_the_master_key() {
+    let encrypted = Keyring::new(&OLD_KEY, &[]).encrypt("sk-or-secret");
+    let keyring = Keyring::new(&NEW_KEY, &[OLD_KEY]);
+
+    let rewrapped = keyring.rewrap(&encrypted).unwrap().unwrap();
+
+    // Only the data key is rewrapped, the ciphertext stays
+    assert_eq!(rewrapped.rsplit_once(':').unwrap().1, encrypted.rsplit_once(':').unwrap().1);
+    assert_eq!(Keyring::new(&NEW_KEY, &[]).decrypt(&rewrapped).unwrap(), "sk-or-secret");
+    assert_eq!(keyring.rewrap(&rewrapped).unwrap(), None);
+}
+
+#[test]
+fn test_rewrap_encrypts_plain_text() {
+    let keyring = Keyring::new(&NEW_KEY, &[]);
+
+    let encrypted = keyring.rewrap("sk-or-secret").unwrap().unwrap();
+
+    assert_eq!(keyring.decrypt(&encrypted).unwrap(), "sk-or-secret");
+}
+
+#[test]
+#[should_panic(expected = "other master keys")]
+fn test_install_rejects_other_keyring() {
+    Keyring::new(&NEW_KEY, &[OLD_KEY]).install();
+    // The same keys may be installed again, e.g. by a second connection
+    Keyring::new(&NEW_KEY, &[OLD_KEY]).install();
+
+    Keyring::new(&OLD_KEY, &[]).install();
+}
@@ -99,8 +99,8 @@ impl Conn<'_> {
         combined_query.load(&mut self.conn).await.expect("Error loading authorized users")
     }
 
-    /// Retrieves the `InstallationRepository` for a given `(github_user_id, github_repo_id)`
-    /// if the user is authorized.
+    /// Retrieves the `InstallationRepository` for a given `(github_user_id, github_repo_id)` in
+    /// the installation if the user is authorized.
     ///
     /// The user is authorized if they appear in:
     ///   - `installation_users` for the same installation
@@ -109,6 +109,7 @@ impl Conn<'_> {
     /// We implement that via two queries that we `UNION`.
     pub async fn installation_repository_for_authorized_github_user(
         &mut self,
+        inst_id: Uuid,
         github_user_id: &str,
         github_repo_id: &str,
     ) -> Option<InstallationRepository> {
@@ -123,6 +124,7 @@ impl Conn<'_> {
             .filter(
                 repo_dsl::github_id.eq(github_repo_id).and(user_dsl::github_id.eq(github_user_id)),
             )
+            .filter(ir::installation_id.eq(inst_id))
             .select(InstallationRepository::as_select());
 
         // Query #2: user is in `installation_repository_users` for the same `(installation, repo)`
@@ -137,6 +139,7 @@ impl Conn<'_> {
             .filter(
                 repo_dsl::github_id.eq(github_repo_id).and(user_dsl::github_id.eq(github_user_id)),
             )
+            .filter(ir::installation_id.eq(inst_id))
             .select(InstallationRepository::as_select());
 
         // Combine both queries with `UNION`, which is inherently distinct in SQL
@@ -1,24 +1,41 @@
 use diesel::expression::SelectableHelper;
-use diesel::{ExpressionMethods, QueryDsl};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
 use crate::conn::Conn;
 use crate::models::installation_users::NewInstallationUser;
 use crate::schema::installation_users::dsl;
-use crate::schema::users;
-use crate::{User, UserRole};
+use crate::schema::{installations, users};
+use crate::{Installation, User, UserRole};
 
 impl Conn<'_> {
-    pub async fn add_installation_user(&mut self, inst_id: Uuid, user_id: Uuid, role: UserRole) {
+    /// Add the user to the installation, or update the role of a user that already belongs to it
+    pub async fn update_or_add_installation_user(
+        &mut self,
+        inst_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+    ) {
         let new_record = NewInstallationUser { installation_id: inst_id, user_id, role };
 
         diesel::insert_into(dsl::installation_users)
             .values(new_record)
-            .on_conflict_do_nothing()
+            .on_conflict((dsl::installation_id, dsl::user_id))
+            .do_update()
+            .set(dsl::role.eq(role))
             .execute(&mut self.conn)
             .await
-            .expect("Error inserting installation_repository_users");
+            .expect("Error inserting installation_users");
+    }
+
+    pub async fn remove_installation_user(&mut self, inst_id: Uuid, user_id: Uuid) {
+        diesel::delete(dsl::installation_users)
+            .filter(dsl::installation_id.eq(inst_id))
+            .filter(dsl::user_id.eq(user_id))
+            .execute(&mut self.conn)
+            .await
+            .expect("Error deleting installation_users");
     }
 
     /// A list of all users assocated with the installation.
@@ -31,4 +48,27 @@ impl Conn<'_> {
             .await
             .expect("Error loading users for installation")
     }
+
+    pub async fn user_is_admin_of_installation(&mut self, user_id: Uuid, inst_id: Uuid) -> bool {
+        let role = dsl::installation_users
+            .find((inst_id, user_id))
+            .select(dsl::role)
+            .first::<UserRole>(&mut self.conn)
+            .await
+            .optional()
+            .expect("Error loading installation user role");
+        matches!(role, Some(UserRole::Admin))
+    }
+
+    /// The installations the user is an admin of
+    pub async fn installations_of_admin(&mut self, user_id: Uuid) -> Vec<Installation> {
+        dsl::installation_users
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::role.eq(UserRole::Admin))
+            .inner_join(installations::table)
+            .select(installations::all_columns)
+            .load(&mut self.conn)
+            .await
+            .expect("Error loading installations of admin")
+    }
 }
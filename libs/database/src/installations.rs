@@ -3,7 +3,9 @@ use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
 use crate::conn::Conn;
-use crate::models::installations::{Installation, NewInstallation, UpdateInstallationByGitHubId};
+use crate::models::installations::{
+    Installation, NewInstallation, UpdateInstallationByGitHubId, UpdateInstallationById,
+};
 use crate::schema::installations::dsl::*;
 
 impl Conn<'_> {
@@ -33,6 +35,11 @@ impl Conn<'_> {
         installations.load(&mut self.conn).await.unwrap()
     }
 
+    pub async fn update_installation(&mut self, update: UpdateInstallationById) -> Installation {
+        let update = update.encrypted();
+        diesel::update(&update).set(&update).get_result(&mut self.conn).await.unwrap()
+    }
+
     pub async fn update_installation_by_github_id(
         &mut self,
         update: UpdateInstallationByGitHubId,
@@ -1,5 +1,7 @@
 use diesel::expression::SelectableHelper;
-use diesel::{BoolExpressionMethods, CombineDsl, ExpressionMethods, JoinOnDsl, QueryDsl};
+use diesel::{
+    BoolExpressionMethods, CombineDsl, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl,
+};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
@@ -7,7 +9,7 @@ use crate::conn::Conn;
 use crate::models::installations_repositories::NewInstallationRepository;
 use crate::models::repositories::{NewRepository, Repository};
 use crate::schema::{
-    installation_repository_users as iru, installation_users as iu,
+    installation_repository_users as iru, installation_users as iu, installations,
     installations_repositories as ir, repositories,
 };
 use crate::{InstallationRepository, UpdateInstallationRepository, UserRole};
@@ -45,6 +47,15 @@ impl Conn<'_> {
         diesel::update(&update).set(&update).get_result(&mut self.conn).await.unwrap()
     }
 
+    /// Deactivate the repository in all installations
+    pub async fn deactivate_installation_repositories(&mut self, repo_id: Uuid) {
+        diesel::update(ir::table.filter(ir::repository_id.eq(repo_id)))
+            .set(ir::active.eq(false))
+            .execute(&mut self.conn)
+            .await
+            .unwrap();
+    }
+
     pub async fn delete_installation_repositories_by_github_ids(
         &mut self,
         inst_id: Uuid,
@@ -106,6 +117,38 @@ impl Conn<'_> {
             .unwrap()
     }
 
+    /// The repository in the installation, both by their GitHub ids
+    pub async fn installation_repository_by_github_id(
+        &mut self,
+        github_installation_id: i64,
+        github_repo_id: &str,
+    ) -> Option<InstallationRepository> {
+        ir::table
+            .inner_join(repositories::dsl::repositories)
+            .inner_join(installations::table)
+            .filter(repositories::dsl::github_id.eq(github_repo_id))
+            .filter(installations::github_id.eq(github_installation_id))
+            .select(InstallationRepository::as_select())
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Name of the LLM provider set for the repository in the installation, else for the
+    /// installation
+    pub async fn llm_provider_name(&mut self, inst_id: Uuid, repo_id: Uuid) -> Option<String> {
+        let (repo_provider, inst_provider) = ir::table
+            .find((inst_id, repo_id))
+            .inner_join(installations::table)
+            .select((ir::llm_provider, installations::llm_provider))
+            .first::<(Option<String>, Option<String>)>(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()?;
+        repo_provider.or(inst_provider)
+    }
+
     /// Returns all `(Repository, InstallationRepository)` pairs to which `user_id`
     /// has access. A user has access if:
     /// - They appear in `installation_users` for that installation (all repos),
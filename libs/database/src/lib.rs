@@ -1,5 +1,7 @@
 mod agent_configs;
 mod conn;
+mod credentials;
+mod encryption;
 mod installation_repository_users;
 mod installation_users;
 mod installations;
@@ -8,18 +10,30 @@ mod llm_interactions;
 mod models;
 mod repositories;
 mod schema;
+mod task_artifacts;
 mod task_compute_usage;
+mod task_events;
+mod task_questions;
+mod task_review_comments;
 mod tasks;
 mod types;
 mod users;
+mod webhook_deliveries;
 
 pub use conn::*;
+pub use encryption::Keyring;
+pub use models::agent_configs::*;
 pub use models::installations::*;
 pub use models::installations_repositories::*;
 pub use models::llm_interactions::*;
 pub use models::repositories::*;
+pub use models::task_artifacts::*;
+pub use models::task_events::*;
+pub use models::task_questions::*;
+pub use models::task_review_comments::*;
 pub use models::tasks::*;
 pub use models::users::*;
+pub use models::webhook_deliveries::*;
 pub use types::*;
 
 pub trait Update {
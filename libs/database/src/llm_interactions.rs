@@ -1,10 +1,15 @@
+use chrono::{DateTime, Utc};
+use diesel::dsl::sum;
 use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
 use crate::conn::Conn;
-use crate::models::llm_interactions::{LLMInteraction, NewLLMInteraction};
+use crate::models::llm_interactions::{
+    LLMInteraction, LLMInteractionResponse, LLMUsage, NewLLMInteraction,
+};
 use crate::schema::llm_interactions::dsl::*;
+use crate::schema::tasks;
 
 impl Conn<'_> {
     pub async fn add_llm_interaction(
@@ -18,6 +23,18 @@ impl Conn<'_> {
             .expect("Error inserting new interaction")
     }
 
+    pub async fn set_llm_interaction_response(
+        &mut self,
+        interaction_id: Uuid,
+        the_response: LLMInteractionResponse,
+    ) {
+        diesel::update(llm_interactions.find(interaction_id))
+            .set(the_response)
+            .execute(&mut self.conn)
+            .await
+            .expect("Error updating interaction response");
+    }
+
     pub async fn llm_interactions(&mut self, the_task_id: &Uuid) -> Vec<LLMInteraction> {
         llm_interactions
             .filter(task_id.eq(the_task_id))
@@ -47,4 +64,50 @@ impl Conn<'_> {
             .await
             .expect("Error loading interactions")
     }
+
+    /// Tokens and cost of all interactions of a task
+    pub async fn task_llm_usage(&mut self, the_task_id: &Uuid) -> LLMUsage {
+        let (prompt, completion, the_cost) = llm_interactions
+            .filter(task_id.eq(the_task_id))
+            .select((sum(prompt_tokens), sum(completion_tokens), sum(cost)))
+            .get_result::<(Option<i64>, Option<i64>, Option<f64>)>(&mut self.conn)
+            .await
+            .expect("Error summing interaction usage");
+
+        LLMUsage {
+            prompt_tokens: prompt.unwrap_or_default(),
+            completion_tokens: completion.unwrap_or_default(),
+            cost: the_cost.unwrap_or_default(),
+        }
+    }
+
+    /// Cost of the interactions of all tasks the user created since `since`, in USD
+    pub async fn user_llm_cost_since(&mut self, user_id: &Uuid, since: DateTime<Utc>) -> f64 {
+        llm_interactions
+            .inner_join(tasks::table)
+            .filter(tasks::created_by_id.eq(user_id))
+            .filter(created_at.ge(since))
+            .select(sum(cost))
+            .get_result::<Option<f64>>(&mut self.conn)
+            .await
+            .expect("Error summing interaction cost")
+            .unwrap_or_default()
+    }
+
+    /// Cost of the interactions of all tasks on the repository since `since`, in USD
+    pub async fn repository_llm_cost_since(
+        &mut self,
+        repository_id: &Uuid,
+        since: DateTime<Utc>,
+    ) -> f64 {
+        llm_interactions
+            .inner_join(tasks::table)
+            .filter(tasks::repository_id.eq(repository_id))
+            .filter(created_at.ge(since))
+            .select(sum(cost))
+            .get_result::<Option<f64>>(&mut self.conn)
+            .await
+            .expect("Error summing interaction cost")
+            .unwrap_or_default()
+    }
 }
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
 use uuid::Uuid;
 
+use crate::encryption::{encrypt, Decrypted};
 use crate::schema::agent_configs;
 use crate::Update;
 
@@ -13,8 +14,11 @@ pub struct AgentConfig {
     pub updated_at: DateTime<Utc>,
     pub container_registry_host: String,
     pub container_registry_username: Option<String>,
+    #[diesel(deserialize_as = Decrypted)]
     pub container_registry_password: Option<String>,
     pub container_image: String,
+    /// The installation whose tasks may use the config
+    pub installation_id: Option<Uuid>,
 }
 
 impl Update for AgentConfig {
@@ -33,6 +37,7 @@ pub struct UpdateAgentConfig {
     pub container_registry_username: Option<Option<String>>,
     pub container_registry_password: Option<Option<String>>,
     pub container_image: Option<String>,
+    pub installation_id: Option<Option<Uuid>>,
 }
 
 impl UpdateAgentConfig {
@@ -60,6 +65,16 @@ impl UpdateAgentConfig {
         self.container_image = Some(image);
         self
     }
+
+    pub fn installation_id(mut self, installation_id: Option<Uuid>) -> Self {
+        self.installation_id = Some(installation_id);
+        self
+    }
+
+    pub(crate) fn encrypted(mut self) -> Self {
+        self.container_registry_password = self.container_registry_password.map(encrypt);
+        self
+    }
 }
 
 #[derive(Insertable)]
@@ -69,21 +84,29 @@ pub struct NewAgentConfig {
     pub container_registry_username: Option<String>,
     pub container_registry_password: Option<String>,
     pub container_image: String,
+    pub installation_id: Option<Uuid>,
 }
 
 impl NewAgentConfig {
+    pub(crate) fn encrypted(mut self) -> Self {
+        self.container_registry_password = encrypt(self.container_registry_password);
+        self
+    }
+
     pub fn into_update(self, id: Uuid) -> UpdateAgentConfig {
         let NewAgentConfig {
             container_registry_host,
             container_registry_username,
             container_registry_password,
             container_image,
+            installation_id,
         } = self;
 
         let mut update_agent_config = UpdateAgentConfig::default()
             .id(id)
             .container_registry_host(container_registry_host)
-            .container_image(container_image);
+            .container_image(container_image)
+            .installation_id(installation_id);
 
         update_agent_config =
             update_agent_config.container_registry_username(container_registry_username);
@@ -2,8 +2,9 @@ use chrono::{DateTime, Utc};
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
 use uuid::Uuid;
 
+use crate::encryption::{encrypt, Decrypted};
 use crate::schema::installations;
-use crate::Update;
+use crate::{LlmKeyPrecedence, Update};
 
 #[derive(Queryable, Identifiable)]
 #[diesel(table_name = installations)]
@@ -15,6 +16,12 @@ pub struct Installation {
     pub created_by_github_id: Option<String>,
     pub suspended_at: Option<DateTime<Utc>>,
     pub suspended_by_github_id: Option<String>,
+    /// Name of the configured LLM provider the tasks use unless their repository sets one
+    pub llm_provider: Option<String>,
+    /// OpenRouter key that pays for the tasks of the installation
+    #[diesel(deserialize_as = Decrypted)]
+    pub openrouter_key: Option<String>,
+    pub llm_key_precedence: LlmKeyPrecedence,
 }
 
 impl Update for Installation {
@@ -32,6 +39,9 @@ pub struct UpdateInstallationById {
     created_by_github_id: Option<Option<String>>,
     suspended_at: Option<Option<DateTime<Utc>>>,
     suspended_by_github_id: Option<Option<String>>,
+    llm_provider: Option<Option<String>>,
+    openrouter_key: Option<Option<String>>,
+    llm_key_precedence: Option<LlmKeyPrecedence>,
 }
 
 impl UpdateInstallationById {
@@ -49,6 +59,26 @@ impl UpdateInstallationById {
         self.suspended_by_github_id = Some(suspended_by_github_id);
         self
     }
+
+    pub fn llm_provider(mut self, llm_provider: Option<String>) -> Self {
+        self.llm_provider = Some(llm_provider);
+        self
+    }
+
+    pub fn openrouter_key(mut self, openrouter_key: Option<String>) -> Self {
+        self.openrouter_key = Some(openrouter_key);
+        self
+    }
+
+    pub fn llm_key_precedence(mut self, llm_key_precedence: LlmKeyPrecedence) -> Self {
+        self.llm_key_precedence = Some(llm_key_precedence);
+        self
+    }
+
+    pub(crate) fn encrypted(mut self) -> Self {
+        self.openrouter_key = self.openrouter_key.map(encrypt);
+        self
+    }
 }
 
 #[derive(Default, AsChangeset)]
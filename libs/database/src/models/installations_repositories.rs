@@ -17,6 +17,10 @@ pub struct InstallationRepository {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub active: bool,
+    /// Label that queues a task when it is added to an issue
+    pub trigger_label: Option<String>,
+    /// Name of the configured LLM provider the tasks use
+    pub llm_provider: Option<String>,
 }
 
 impl Update for InstallationRepository {
@@ -38,6 +42,8 @@ pub struct UpdateInstallationRepository {
     pub installation_id: Uuid,
     pub repository_id: Uuid,
     pub active: Option<bool>,
+    pub trigger_label: Option<Option<String>>,
+    pub llm_provider: Option<Option<String>>,
 }
 
 impl UpdateInstallationRepository {
@@ -45,6 +51,16 @@ impl UpdateInstallationRepository {
         self.active = Some(active);
         self
     }
+
+    pub fn trigger_label(mut self, trigger_label: Option<String>) -> Self {
+        self.trigger_label = Some(trigger_label);
+        self
+    }
+
+    pub fn llm_provider(mut self, llm_provider: Option<String>) -> Self {
+        self.llm_provider = Some(llm_provider);
+        self
+    }
 }
 
 #[derive(Insertable)]
@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
-use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
 use serde_json::Value;
 use uuid::Uuid;
 
 use crate::schema::llm_interactions;
+use crate::LlmKeySource;
 
 #[derive(Queryable, Identifiable, Selectable)]
 #[diesel(belongs_to(Task))]
@@ -15,6 +16,11 @@ pub struct LLMInteraction {
     pub task_id: Uuid,
     pub request: Option<Value>,
     pub response: Option<Value>,
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+    /// In USD
+    pub cost: Option<f64>,
+    pub key_source: Option<LlmKeySource>,
 }
 
 #[derive(Insertable)]
@@ -23,4 +29,28 @@ pub struct NewLLMInteraction {
     pub task_id: Uuid,
     pub request: Option<Value>,
     pub response: Option<Value>,
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+    /// In USD
+    pub cost: Option<f64>,
+    pub key_source: Option<LlmKeySource>,
+}
+
+/// The response to an interaction that is only known after it was stored, e.g. a streamed one
+#[derive(AsChangeset)]
+#[diesel(table_name = llm_interactions)]
+pub struct LLMInteractionResponse {
+    pub response: Option<Value>,
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+    pub cost: Option<f64>,
+}
+
+/// Tokens and cost of the LLM interactions of a task added up
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LLMUsage {
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    /// In USD
+    pub cost: f64,
 }
@@ -5,6 +5,11 @@ pub mod installations;
 pub mod installations_repositories;
 pub mod llm_interactions;
 pub mod repositories;
+pub mod task_artifacts;
 pub mod task_compute_usage;
+pub mod task_events;
+pub mod task_questions;
+pub mod task_review_comments;
 pub mod tasks;
 pub mod users;
+pub mod webhook_deliveries;
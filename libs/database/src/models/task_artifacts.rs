@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use uuid::Uuid;
+
+use crate::schema::task_artifacts;
+
+/// A file the agent uploaded for a task, the contents are in object storage
+#[derive(Debug, Queryable, Identifiable, Selectable)]
+#[diesel(belongs_to(Task))]
+#[diesel(table_name = task_artifacts)]
+pub struct TaskArtifact {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub task_id: Uuid,
+    /// Unique per task, also the last segment of the object key
+    pub name: String,
+    pub content_type: String,
+    /// In bytes
+    pub size: i64,
+}
+
+#[derive(AsChangeset, Insertable)]
+#[diesel(table_name = task_artifacts)]
+pub struct NewTaskArtifact {
+    pub task_id: Uuid,
+    pub name: String,
+    pub content_type: String,
+    pub size: i64,
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use uuid::Uuid;
+
+use crate::schema::task_events;
+
+/// Progress the agent reported while working on a task
+#[derive(Debug, Queryable, Identifiable, Selectable)]
+#[diesel(belongs_to(Task))]
+#[diesel(table_name = task_events)]
+pub struct TaskEvent {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub task_id: Uuid,
+    /// Name of the step the agent is working on
+    pub step: String,
+    pub message: Option<String>,
+    /// Between 0 and 100
+    pub percent: Option<i16>,
+    /// Milestones are mirrored into the status comment on GitHub
+    pub milestone: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = task_events)]
+pub struct NewTaskEvent {
+    pub task_id: Uuid,
+    pub step: String,
+    pub message: Option<String>,
+    pub percent: Option<i16>,
+    pub milestone: bool,
+}
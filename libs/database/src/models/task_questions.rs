@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use uuid::Uuid;
+
+use crate::schema::task_questions;
+
+/// A question the agent asked on the issue of a task
+#[derive(Debug, Queryable, Identifiable, Selectable)]
+#[diesel(belongs_to(Task))]
+#[diesel(table_name = task_questions)]
+pub struct TaskQuestion {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub task_id: Uuid,
+    pub question: String,
+    /// Node ID of the comment that asks the question
+    pub github_comment_id: String,
+    /// Unset until the issue author replies
+    pub answer: Option<String>,
+    pub answer_author_login: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = task_questions)]
+pub struct NewTaskQuestion {
+    pub task_id: Uuid,
+    pub question: String,
+    pub github_comment_id: String,
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use uuid::Uuid;
+
+use crate::schema::task_review_comments;
+
+#[derive(Debug, Queryable, Identifiable, Selectable)]
+#[diesel(belongs_to(Task))]
+#[diesel(table_name = task_review_comments)]
+pub struct TaskReviewComment {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub task_id: Uuid,
+    /// Node ID of the review comment, of the review or of the pull request comment
+    pub github_comment_id: String,
+    pub author_login: String,
+    pub body: String,
+    /// File the comment refers to
+    pub path: Option<String>,
+    pub line: Option<i32>,
+    /// Excerpt of the diff the comment refers to
+    pub diff_hunk: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = task_review_comments)]
+pub struct NewTaskReviewComment {
+    pub task_id: Uuid,
+    pub github_comment_id: String,
+    pub author_login: String,
+    pub body: String,
+    pub path: Option<String>,
+    pub line: Option<i32>,
+    pub diff_hunk: Option<String>,
+}
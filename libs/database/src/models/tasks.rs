@@ -8,6 +8,9 @@ use crate::schema::tasks;
 use crate::types::{TaskFailureReason, TaskStatus};
 use crate::Update;
 
+/// Branch tasks start from unless another one is given
+pub const DEFAULT_BASE_REF: &str = "main";
+
 #[derive(Debug, Queryable, Identifiable)]
 #[diesel(belongs_to(Installation))]
 #[diesel(belongs_to(Repository))]
@@ -26,6 +29,30 @@ pub struct Task {
     pub failure_description: Option<String>,
     pub failure_reason: Option<TaskFailureReason>,
     pub agent_config_id: Option<Uuid>,
+    /// The task whose recorded LLM interactions are replayed
+    pub replay_of_task_id: Option<Uuid>,
+    /// Branch the task branch is created from and merged into, `main` if not set
+    pub base_ref: Option<String>,
+    /// LLM the agent is asked to use, the agent's default if not set
+    pub model: Option<String>,
+    /// The pull request with the work of the task
+    pub github_pull_request_id: Option<String>,
+    /// The task whose branch and pull request this task continues
+    pub follow_up_of_task_id: Option<Uuid>,
+    /// The comment on the issue or pull request that reports the status of the task
+    pub github_status_comment_id: Option<String>,
+    /// The comment that asked for the task with a command, unset for tasks started otherwise
+    pub github_command_comment: Option<String>,
+}
+
+impl Task {
+    /// Name of the branch the agent works on
+    ///
+    /// Tasks work on a branch named after their ID, follow-up tasks continue on the branch of
+    /// the task they follow up on.
+    pub fn branch_name(&self) -> String {
+        self.follow_up_of_task_id.unwrap_or(self.id).to_string()
+    }
 }
 
 impl Update for Task {
@@ -42,6 +69,8 @@ pub struct UpdateTask {
     pub id: Uuid,
     pub status: Option<TaskStatus>,
     pub agent_config_id: Option<Option<Uuid>>,
+    pub github_pull_request_id: Option<Option<String>>,
+    pub github_status_comment_id: Option<Option<String>>,
 }
 
 impl UpdateTask {
@@ -59,6 +88,16 @@ impl UpdateTask {
         self.agent_config_id = Some(agent_config_id);
         self
     }
+
+    pub fn github_pull_request_id(mut self, github_pull_request_id: Option<String>) -> Self {
+        self.github_pull_request_id = Some(github_pull_request_id);
+        self
+    }
+
+    pub fn github_status_comment_id(mut self, github_status_comment_id: Option<String>) -> Self {
+        self.github_status_comment_id = Some(github_status_comment_id);
+        self
+    }
 }
 
 #[derive(Insertable)]
@@ -71,4 +110,10 @@ pub struct NewTask {
     pub github_issue_number: i64,
     pub status: TaskStatus,
     pub agent_config_id: Option<Uuid>,
+    pub replay_of_task_id: Option<Uuid>,
+    pub base_ref: Option<String>,
+    pub model: Option<String>,
+    pub github_pull_request_id: Option<String>,
+    pub follow_up_of_task_id: Option<Uuid>,
+    pub github_command_comment: Option<String>,
 }
@@ -5,6 +5,7 @@ use diesel::Selectable;
 use diesel::{Insertable, Queryable};
 use uuid::Uuid;
 
+use crate::encryption::{encrypt, Decrypted};
 use crate::schema::users;
 use crate::Update;
 
@@ -20,9 +21,12 @@ pub struct User {
     pub github_email: Option<String>,
     pub github_name: Option<String>,
     pub github_login: String,
+    #[diesel(deserialize_as = Decrypted)]
     pub github_access_token: Option<String>,
     pub github_access_token_expires_at: Option<DateTime<Utc>>,
+    #[diesel(deserialize_as = Decrypted)]
     pub openrouter_key: Option<String>,
+    #[diesel(deserialize_as = Decrypted)]
     pub openrouter_code_verifier: Option<String>,
 }
 
@@ -105,6 +109,13 @@ impl UpdateUser {
         self.openrouter_code_verifier = Some(openrouter_code_verifier);
         self
     }
+
+    pub(crate) fn encrypted(mut self) -> UpdateUser {
+        self.github_access_token = self.github_access_token.map(encrypt);
+        self.openrouter_key = self.openrouter_key.map(encrypt);
+        self.openrouter_code_verifier = self.openrouter_code_verifier.map(encrypt);
+        self
+    }
 }
 
 #[derive(Insertable, AsChangeset)]
@@ -117,3 +128,10 @@ pub struct NewUser {
     pub github_access_token: Option<Option<String>>,
     pub github_access_token_expires_at: Option<Option<DateTime<Utc>>>,
 }
+
+impl NewUser {
+    pub(crate) fn encrypted(mut self) -> NewUser {
+        self.github_access_token = self.github_access_token.map(encrypt);
+        self
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::schema::webhook_deliveries;
+use crate::types::WebhookDeliveryStatus;
+use crate::Update;
+
+#[derive(Debug, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Value of the X-GitHub-Delivery header
+    pub github_delivery_id: String,
+    pub event: String,
+    pub action: Option<String>,
+    pub installation_github_id: Option<i64>,
+    pub status: WebhookDeliveryStatus,
+    pub error: Option<String>,
+    /// The verified payload
+    pub payload: Option<Value>,
+    /// Number of times handling the delivery was started
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+impl Update for WebhookDelivery {
+    type Output = UpdateWebhookDelivery;
+
+    fn update(&self) -> Self::Output {
+        UpdateWebhookDelivery { id: self.id, ..Default::default() }
+    }
+}
+
+#[derive(Default, AsChangeset, Identifiable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct UpdateWebhookDelivery {
+    pub id: Uuid,
+    pub status: Option<WebhookDeliveryStatus>,
+    pub error: Option<Option<String>>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl UpdateWebhookDelivery {
+    pub fn status(mut self, status: WebhookDeliveryStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn error(mut self, error: Option<String>) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn next_attempt_at(mut self, next_attempt_at: DateTime<Utc>) -> Self {
+        self.next_attempt_at = Some(next_attempt_at);
+        self
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub github_delivery_id: String,
+    pub event: String,
+    pub action: Option<String>,
+    pub installation_github_id: Option<i64>,
+    pub status: WebhookDeliveryStatus,
+    pub payload: Option<Value>,
+}
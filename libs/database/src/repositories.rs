@@ -1,5 +1,5 @@
 use diesel::upsert::excluded;
-use diesel::{ExpressionMethods, QueryDsl};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
@@ -33,9 +33,18 @@ impl Conn<'_> {
         repositories.filter(github_full_name.eq(full_name)).first(&mut self.conn).await.unwrap()
     }
 
+    pub async fn get_repository_by_github_id(&mut self, the_github_id: &str) -> Option<Repository> {
+        repositories
+            .filter(github_id.eq(the_github_id))
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
     pub async fn update_repository(&mut self, updated_repository: UpdateRepository) -> Repository {
-        diesel::update(repositories)
-            .set(updated_repository)
+        diesel::update(&updated_repository)
+            .set(&updated_repository)
             .get_result(&mut self.conn)
             .await
             .unwrap()
@@ -1,6 +1,14 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "llm_key_precedence"))]
+    pub struct LlmKeyPrecedence;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "llm_key_source"))]
+    pub struct LlmKeySource;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "task_failure_reason"))]
     pub struct TaskFailureReason;
@@ -12,6 +20,10 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "user_role"))]
     pub struct UserRole;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "webhook_delivery_status"))]
+    pub struct WebhookDeliveryStatus;
 }
 
 diesel::table! {
@@ -23,6 +35,7 @@ diesel::table! {
         container_registry_username -> Nullable<Text>,
         container_registry_password -> Nullable<Text>,
         container_image -> Text,
+        installation_id -> Nullable<Uuid>,
     }
 }
 
@@ -50,6 +63,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::LlmKeyPrecedence;
+
     installations (id) {
         id -> Uuid,
         created_at -> Timestamptz,
@@ -58,6 +74,9 @@ diesel::table! {
         created_by_github_id -> Nullable<Text>,
         suspended_at -> Nullable<Timestamptz>,
         suspended_by_github_id -> Nullable<Text>,
+        llm_provider -> Nullable<Text>,
+        openrouter_key -> Nullable<Text>,
+        llm_key_precedence -> LlmKeyPrecedence,
     }
 }
 
@@ -68,10 +87,15 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         active -> Bool,
+        trigger_label -> Nullable<Text>,
+        llm_provider -> Nullable<Text>,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::LlmKeySource;
+
     llm_interactions (id) {
         id -> Uuid,
         created_at -> Timestamptz,
@@ -79,6 +103,10 @@ diesel::table! {
         task_id -> Uuid,
         request -> Nullable<Jsonb>,
         response -> Nullable<Jsonb>,
+        prompt_tokens -> Nullable<Int4>,
+        completion_tokens -> Nullable<Int4>,
+        cost -> Nullable<Float8>,
+        key_source -> Nullable<LlmKeySource>,
     }
 }
 
@@ -94,6 +122,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    task_artifacts (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        task_id -> Uuid,
+        name -> Text,
+        content_type -> Text,
+        size -> Int8,
+    }
+}
+
 diesel::table! {
     task_compute_usage (id) {
         id -> Uuid,
@@ -105,6 +145,47 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    task_events (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        task_id -> Uuid,
+        step -> Text,
+        message -> Nullable<Text>,
+        percent -> Nullable<Int2>,
+        milestone -> Bool,
+    }
+}
+
+diesel::table! {
+    task_questions (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        task_id -> Uuid,
+        question -> Text,
+        github_comment_id -> Text,
+        answer -> Nullable<Text>,
+        answer_author_login -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    task_review_comments (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        task_id -> Uuid,
+        github_comment_id -> Text,
+        author_login -> Text,
+        body -> Text,
+        path -> Nullable<Text>,
+        line -> Nullable<Int4>,
+        diff_hunk -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::TaskStatus;
@@ -124,6 +205,13 @@ diesel::table! {
         failure_description -> Nullable<Text>,
         failure_reason -> Nullable<TaskFailureReason>,
         agent_config_id -> Nullable<Uuid>,
+        replay_of_task_id -> Nullable<Uuid>,
+        base_ref -> Nullable<Text>,
+        model -> Nullable<Text>,
+        github_pull_request_id -> Nullable<Text>,
+        follow_up_of_task_id -> Nullable<Uuid>,
+        github_status_comment_id -> Nullable<Text>,
+        github_command_comment -> Nullable<Text>,
     }
 }
 
@@ -145,6 +233,27 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::WebhookDeliveryStatus;
+
+    webhook_deliveries (id) {
+        id -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        github_delivery_id -> Text,
+        event -> Text,
+        action -> Nullable<Text>,
+        installation_github_id -> Nullable<Int8>,
+        status -> WebhookDeliveryStatus,
+        error -> Nullable<Text>,
+        payload -> Nullable<Jsonb>,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(agent_configs -> installations (installation_id));
 diesel::joinable!(installation_repository_users -> installations (installation_id));
 diesel::joinable!(installation_repository_users -> users (user_id));
 diesel::joinable!(installation_users -> installations (installation_id));
@@ -153,7 +262,11 @@ diesel::joinable!(installations_repositories -> installations (installation_id))
 diesel::joinable!(installations_repositories -> repositories (repository_id));
 diesel::joinable!(llm_interactions -> tasks (task_id));
 diesel::joinable!(repositories -> agent_configs (default_agent_config_id));
+diesel::joinable!(task_artifacts -> tasks (task_id));
 diesel::joinable!(task_compute_usage -> tasks (task_id));
+diesel::joinable!(task_events -> tasks (task_id));
+diesel::joinable!(task_questions -> tasks (task_id));
+diesel::joinable!(task_review_comments -> tasks (task_id));
 diesel::joinable!(tasks -> agent_configs (agent_config_id));
 diesel::joinable!(tasks -> installations (installation_id));
 diesel::joinable!(tasks -> repositories (repository_id));
@@ -167,7 +280,12 @@ diesel::allow_tables_to_appear_in_same_query!(
     installations_repositories,
     llm_interactions,
     repositories,
+    task_artifacts,
     task_compute_usage,
+    task_events,
+    task_questions,
+    task_review_comments,
     tasks,
     users,
+    webhook_deliveries,
 );
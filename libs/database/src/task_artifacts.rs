@@ -0,0 +1,44 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::models::task_artifacts::{NewTaskArtifact, TaskArtifact};
+use crate::schema::task_artifacts::dsl::*;
+
+impl Conn<'_> {
+    /// Add an artifact to a task, replacing the one with the same name
+    pub async fn upsert_task_artifact(&mut self, new_artifact: NewTaskArtifact) -> TaskArtifact {
+        diesel::insert_into(task_artifacts)
+            .values(&new_artifact)
+            .on_conflict((task_id, name))
+            .do_update()
+            .set(&new_artifact)
+            .get_result(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    pub async fn task_artifacts(&mut self, the_task_id: &Uuid) -> Vec<TaskArtifact> {
+        task_artifacts
+            .filter(task_id.eq(the_task_id))
+            .order_by(name)
+            .load(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    pub async fn find_task_artifact(
+        &mut self,
+        the_task_id: &Uuid,
+        artifact_name: &str,
+    ) -> Option<TaskArtifact> {
+        task_artifacts
+            .filter(task_id.eq(the_task_id))
+            .filter(name.eq(artifact_name))
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+}
@@ -0,0 +1,22 @@
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::models::task_events::{NewTaskEvent, TaskEvent};
+use crate::schema::task_events::dsl::*;
+
+impl Conn<'_> {
+    pub async fn add_task_event(&mut self, new_event: NewTaskEvent) -> TaskEvent {
+        diesel::insert_into(task_events).values(new_event).get_result(&mut self.conn).await.unwrap()
+    }
+
+    pub async fn task_events(&mut self, the_task_id: &Uuid) -> Vec<TaskEvent> {
+        task_events
+            .filter(task_id.eq(the_task_id))
+            .order_by(created_at)
+            .load(&mut self.conn)
+            .await
+            .unwrap()
+    }
+}
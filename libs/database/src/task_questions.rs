@@ -0,0 +1,43 @@
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::models::task_questions::{NewTaskQuestion, TaskQuestion};
+use crate::schema::task_questions::dsl::*;
+
+impl Conn<'_> {
+    pub async fn add_task_question(&mut self, new_question: NewTaskQuestion) -> TaskQuestion {
+        diesel::insert_into(task_questions)
+            .values(new_question)
+            .get_result(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    /// The questions of the task in the order they were asked
+    pub async fn task_questions(&mut self, the_task_id: &Uuid) -> Vec<TaskQuestion> {
+        task_questions
+            .filter(task_id.eq(the_task_id))
+            .order_by(created_at)
+            .load(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    /// Answer the unanswered questions of the task
+    pub async fn answer_task_questions(
+        &mut self,
+        the_task_id: &Uuid,
+        the_answer: &str,
+        author_login: &str,
+    ) {
+        diesel::update(task_questions)
+            .filter(task_id.eq(the_task_id))
+            .filter(answer.is_null())
+            .set((answer.eq(the_answer), answer_author_login.eq(author_login)))
+            .execute(&mut self.conn)
+            .await
+            .unwrap();
+    }
+}
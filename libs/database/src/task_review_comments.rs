@@ -0,0 +1,28 @@
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::models::task_review_comments::{NewTaskReviewComment, TaskReviewComment};
+use crate::schema::task_review_comments::dsl::*;
+
+impl Conn<'_> {
+    /// Add review comments to a task, comments the task already has are skipped
+    pub async fn add_task_review_comments(&mut self, new_comments: Vec<NewTaskReviewComment>) {
+        diesel::insert_into(task_review_comments)
+            .values(new_comments)
+            .on_conflict_do_nothing()
+            .execute(&mut self.conn)
+            .await
+            .unwrap();
+    }
+
+    pub async fn task_review_comments(&mut self, the_task_id: &Uuid) -> Vec<TaskReviewComment> {
+        task_review_comments
+            .filter(task_id.eq(the_task_id))
+            .order_by(created_at)
+            .load(&mut self.conn)
+            .await
+            .unwrap()
+    }
+}
@@ -1,4 +1,4 @@
-use diesel::{ExpressionMethods, QueryDsl};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
@@ -48,6 +48,104 @@ impl Conn<'_> {
             .unwrap()
     }
 
+    /// GitHub ID of the installation the task belongs to, `None` if the app was uninstalled since
+    pub async fn task_installation_github_id(&mut self, task_id: &Uuid) -> Option<i64> {
+        use crate::schema::installations;
+
+        tasks
+            .filter(id.eq(task_id))
+            .inner_join(installations::table)
+            .select(installations::github_id)
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// The most recent task for the issue that is not a replay
+    pub async fn latest_task_for_issue(&mut self, issue_id: &str) -> Option<Task> {
+        tasks
+            .filter(github_issue_id.eq(issue_id))
+            .filter(replay_of_task_id.is_null())
+            .order_by(created_at.desc())
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// The most recent task that works on the pull request
+    pub async fn latest_task_for_pull_request(&mut self, pull_request_id: &str) -> Option<Task> {
+        tasks
+            .filter(github_pull_request_id.eq(pull_request_id))
+            .filter(replay_of_task_id.is_null())
+            .order_by(created_at.desc())
+            .first(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Cancel the task if it is queued, running or waiting for input
+    ///
+    /// Returns `None` if the task has already finished.
+    pub async fn cancel_task(&mut self, task_id: &Uuid) -> Option<Task> {
+        diesel::update(tasks)
+            .filter(id.eq(task_id))
+            .filter(status.eq_any([
+                TaskStatus::Queued,
+                TaskStatus::Running,
+                TaskStatus::WaitingForInput,
+            ]))
+            .set(status.eq(TaskStatus::Cancelled))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Let the task wait for input if it is running
+    ///
+    /// Returns `None` if the task is not running anymore.
+    pub async fn wait_for_input(&mut self, task_id: &Uuid) -> Option<Task> {
+        diesel::update(tasks)
+            .filter(id.eq(task_id))
+            .filter(status.eq(TaskStatus::Running))
+            .set(status.eq(TaskStatus::WaitingForInput))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Let the task run again if it is still waiting for input, e.g. when asking failed
+    ///
+    /// Returns `None` if the task is not waiting for input.
+    pub async fn stop_waiting_for_input(&mut self, task_id: &Uuid) -> Option<Task> {
+        diesel::update(tasks)
+            .filter(id.eq(task_id))
+            .filter(status.eq(TaskStatus::WaitingForInput))
+            .set(status.eq(TaskStatus::Running))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Queue the task again if it is waiting for input
+    ///
+    /// Returns `None` if the task is not waiting for input.
+    pub async fn requeue_task(&mut self, task_id: &Uuid) -> Option<Task> {
+        diesel::update(tasks)
+            .filter(id.eq(task_id))
+            .filter(status.eq(TaskStatus::WaitingForInput))
+            .set(status.eq(TaskStatus::Queued))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
     pub async fn receive_task(&mut self) -> Option<Task> {
         let tasks1 = diesel::alias!(crate::schema::tasks as tasks1);
 
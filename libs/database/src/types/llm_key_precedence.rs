@@ -0,0 +1,35 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use std::io::Write;
+
+/// Which OpenRouter key pays for the tasks of an installation when both are set
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = crate::schema::sql_types::LlmKeyPrecedence)]
+pub enum LlmKeyPrecedence {
+    /// The key of the installation, the key of the user who created the task if there is none
+    InstallationFirst,
+    /// The key of the user who created the task, the key of the installation if there is none
+    UserFirst,
+}
+
+impl ToSql<crate::schema::sql_types::LlmKeyPrecedence, Pg> for LlmKeyPrecedence {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match self {
+            LlmKeyPrecedence::InstallationFirst => out.write_all(b"installation_first")?,
+            LlmKeyPrecedence::UserFirst => out.write_all(b"user_first")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::LlmKeyPrecedence, Pg> for LlmKeyPrecedence {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"installation_first" => Ok(LlmKeyPrecedence::InstallationFirst),
+            b"user_first" => Ok(LlmKeyPrecedence::UserFirst),
+            _ => Err("Unrecognized enum variant for LlmKeyPrecedence".into()),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use std::io::Write;
+
+/// Where the key an LLM request was authenticated with came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = crate::schema::sql_types::LlmKeySource)]
+pub enum LlmKeySource {
+    /// The OpenRouter key of the installation
+    Installation,
+    /// The OpenRouter key of the user who created the task
+    User,
+    /// The key configured for the LLM provider
+    Provider,
+}
+
+impl ToSql<crate::schema::sql_types::LlmKeySource, Pg> for LlmKeySource {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match self {
+            LlmKeySource::Installation => out.write_all(b"installation")?,
+            LlmKeySource::User => out.write_all(b"user")?,
+            LlmKeySource::Provider => out.write_all(b"provider")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::LlmKeySource, Pg> for LlmKeySource {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"installation" => Ok(LlmKeySource::Installation),
+            b"user" => Ok(LlmKeySource::User),
+            b"provider" => Ok(LlmKeySource::Provider),
+            _ => Err("Unrecognized enum variant for LlmKeySource".into()),
+        }
+    }
+}
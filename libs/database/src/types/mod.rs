@@ -1,7 +1,13 @@
+mod llm_key_precedence;
+mod llm_key_source;
 mod task_failure_reason;
 mod task_status;
 mod user_role;
+mod webhook_delivery_status;
 
+pub use llm_key_precedence::*;
+pub use llm_key_source::*;
 pub use task_failure_reason::*;
 pub use task_status::*;
 pub use user_role::*;
+pub use webhook_delivery_status::*;
@@ -11,6 +11,10 @@ pub enum TaskStatus {
     Running,
     Completed,
     Failed,
+    /// Cancelled by a command, the results of the agent are discarded
+    Cancelled,
+    /// The agent asked a question on the issue, the task is queued again once it is answered
+    WaitingForInput,
 }
 
 impl ToSql<crate::schema::sql_types::TaskStatus, Pg> for TaskStatus {
@@ -21,6 +25,8 @@ impl ToSql<crate::schema::sql_types::TaskStatus, Pg> for TaskStatus {
             Running => out.write_all(b"running")?,
             Completed => out.write_all(b"completed")?,
             Failed => out.write_all(b"failed")?,
+            Cancelled => out.write_all(b"cancelled")?,
+            WaitingForInput => out.write_all(b"waiting_for_input")?,
         }
         Ok(IsNull::No)
     }
@@ -34,6 +40,8 @@ impl FromSql<crate::schema::sql_types::TaskStatus, Pg> for TaskStatus {
             b"running" => Ok(Running),
             b"completed" => Ok(Completed),
             b"failed" => Ok(Failed),
+            b"cancelled" => Ok(Cancelled),
+            b"waiting_for_input" => Ok(WaitingForInput),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -47,7 +55,8 @@ impl Into<agent_api::types::task::TaskStatus> for TaskStatus {
             Queued => agent_api::types::task::TaskStatus::Queued,
             Running => agent_api::types::task::TaskStatus::Running,
             Completed => agent_api::types::task::TaskStatus::Completed,
-            Failed => agent_api::types::task::TaskStatus::Failed,
+            // Agents only need to know that the task is not running anymore
+            Failed | Cancelled | WaitingForInput => agent_api::types::task::TaskStatus::Failed,
         }
     }
 }
@@ -0,0 +1,48 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use std::io::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = crate::schema::sql_types::WebhookDeliveryStatus)]
+pub enum WebhookDeliveryStatus {
+    /// The delivery waits in the inbox to be handled
+    Queued,
+    /// The delivery is being handled
+    Processing,
+    /// The delivery was handled successfully
+    Processed,
+    /// Handling the delivery failed, it is retried at `next_attempt_at`
+    Failed,
+    /// Handling the delivery failed too often, it is only handled again when redelivered
+    Dead,
+}
+
+impl ToSql<crate::schema::sql_types::WebhookDeliveryStatus, Pg> for WebhookDeliveryStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        use WebhookDeliveryStatus::*;
+        match *self {
+            Queued => out.write_all(b"queued")?,
+            Processing => out.write_all(b"processing")?,
+            Processed => out.write_all(b"processed")?,
+            Failed => out.write_all(b"failed")?,
+            Dead => out.write_all(b"dead")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<crate::schema::sql_types::WebhookDeliveryStatus, Pg> for WebhookDeliveryStatus {
+    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+        use WebhookDeliveryStatus::*;
+        match bytes.as_bytes() {
+            b"queued" => Ok(Queued),
+            b"processing" => Ok(Processing),
+            b"processed" => Ok(Processed),
+            b"failed" => Ok(Failed),
+            b"dead" => Ok(Dead),
+            _ => Err("Unrecognized enum variant for WebhookDeliveryStatus".into()),
+        }
+    }
+}
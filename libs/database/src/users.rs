@@ -8,6 +8,7 @@ use crate::schema::users;
 
 impl Conn<'_> {
     pub async fn update_or_add_user(&mut self, new_user: NewUser) -> User {
+        let new_user = new_user.encrypted();
         diesel::insert_into(users::table)
             .values(&new_user)
             .on_conflict(users::github_id)
@@ -29,6 +30,7 @@ impl Conn<'_> {
     }
 
     pub async fn update_user(&mut self, updated_user: UpdateUser) -> User {
+        let updated_user = updated_user.encrypted();
         diesel::update(&updated_user).set(&updated_user).get_result(&mut self.conn).await.unwrap()
     }
 }
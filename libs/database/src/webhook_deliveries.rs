@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, NullableExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::conn::Conn;
+use crate::models::webhook_deliveries::{
+    NewWebhookDelivery, UpdateWebhookDelivery, WebhookDelivery,
+};
+use crate::schema::{installation_users, installations, webhook_deliveries};
+use crate::types::{UserRole, WebhookDeliveryStatus};
+
+/// Number of deliveries that are shown for debugging
+const LIST_LIMIT: i64 = 100;
+
+impl Conn<'_> {
+    /// Add a delivery to the inbox
+    ///
+    /// Returns `None` if the delivery is already in the inbox. Dead deliveries are queued again,
+    /// so that a manual redelivery retries them.
+    pub async fn enqueue_webhook_delivery(
+        &mut self,
+        new_delivery: NewWebhookDelivery,
+    ) -> Option<WebhookDelivery> {
+        let delivery_id = new_delivery.github_delivery_id.clone();
+        let payload = new_delivery.payload.clone();
+
+        let inserted = diesel::insert_into(webhook_deliveries::table)
+            .values(new_delivery)
+            .on_conflict(webhook_deliveries::github_delivery_id)
+            .do_nothing()
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap();
+        if inserted.is_some() {
+            return inserted;
+        }
+
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::github_delivery_id.eq(delivery_id))
+            .filter(webhook_deliveries::status.eq(WebhookDeliveryStatus::Dead))
+            .set((
+                webhook_deliveries::status.eq(WebhookDeliveryStatus::Queued),
+                webhook_deliveries::payload.eq(payload),
+                webhook_deliveries::attempts.eq(0),
+                webhook_deliveries::next_attempt_at.eq(Utc::now()),
+            ))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Take the next delivery that is due from the inbox and mark it as being processed
+    pub async fn receive_webhook_delivery(&mut self) -> Option<WebhookDelivery> {
+        let deliveries1 = diesel::alias!(crate::schema::webhook_deliveries as deliveries1);
+
+        let select = deliveries1
+            .for_update()
+            .skip_locked()
+            .filter(
+                deliveries1
+                    .field(webhook_deliveries::status)
+                    .eq_any([WebhookDeliveryStatus::Queued, WebhookDeliveryStatus::Failed]),
+            )
+            .filter(deliveries1.field(webhook_deliveries::next_attempt_at).le(Utc::now()))
+            .order_by(deliveries1.field(webhook_deliveries::next_attempt_at))
+            .limit(1)
+            .select(deliveries1.field(webhook_deliveries::id));
+
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::id.eq_any(select))
+            .set((
+                webhook_deliveries::status.eq(WebhookDeliveryStatus::Processing),
+                webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+            ))
+            .get_result(&mut self.conn)
+            .await
+            .optional()
+            .unwrap()
+    }
+
+    /// Queue deliveries again whose processing was interrupted, e.g. by a restart
+    pub async fn requeue_stale_webhook_deliveries(&mut self, stale_before: DateTime<Utc>) -> usize {
+        diesel::update(webhook_deliveries::table)
+            .filter(webhook_deliveries::status.eq(WebhookDeliveryStatus::Processing))
+            .filter(webhook_deliveries::updated_at.lt(stale_before))
+            .set(webhook_deliveries::status.eq(WebhookDeliveryStatus::Queued))
+            .execute(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    /// Number of deliveries that are waiting to be handled, are being handled or will be retried
+    pub async fn pending_webhook_deliveries_count(&mut self) -> i64 {
+        webhook_deliveries::table
+            .filter(webhook_deliveries::status.eq_any([
+                WebhookDeliveryStatus::Queued,
+                WebhookDeliveryStatus::Processing,
+                WebhookDeliveryStatus::Failed,
+            ]))
+            .count()
+            .get_result(&mut self.conn)
+            .await
+            .unwrap()
+    }
+
+    pub async fn update_webhook_delivery(
+        &mut self,
+        update: UpdateWebhookDelivery,
+    ) -> WebhookDelivery {
+        diesel::update(&update).set(&update).get_result(&mut self.conn).await.unwrap()
+    }
+
+    /// The latest deliveries for installations that the user administers
+    pub async fn webhook_deliveries_for_admin(&mut self, user_id: Uuid) -> Vec<WebhookDelivery> {
+        let installation_github_ids = installation_users::table
+            .inner_join(installations::table)
+            .filter(installation_users::user_id.eq(user_id))
+            .filter(installation_users::role.eq(UserRole::Admin))
+            .select(installations::github_id.nullable());
+
+        webhook_deliveries::table
+            .filter(webhook_deliveries::installation_github_id.eq_any(installation_github_ids))
+            .order_by(webhook_deliveries::created_at.desc())
+            .limit(LIST_LIMIT)
+            .load(&mut self.conn)
+            .await
+            .unwrap()
+    }
+}
@@ -0,0 +1,58 @@
+//! Caches for installation access tokens and repository IDs
+//!
+//! Minting an installation access token signs an app JWT and calls GitHub, so tokens are reused
+//! until shortly before they expire.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::InstallationAccessToken;
+
+mod tests;
+
+/// Tokens closer than this to their expiry are minted anew, so they don't expire while in use
+const EXPIRY_MARGIN_MINUTES: i64 = 5;
+
+/// Installation and permissions an installation access token was minted for
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TokenScope {
+    /// All repositories and permissions of the installation
+    Installation { installation_id: i64 },
+    /// Write access to the contents of a single repository
+    RepositoryContents { installation_id: i64, repository_id: i64 },
+}
+
+#[derive(Default)]
+pub struct Cache {
+    tokens: Mutex<HashMap<TokenScope, InstallationAccessToken>>,
+    /// Numeric repository IDs by node ID, these never change
+    repo_numeric_ids: Mutex<HashMap<String, i64>>,
+}
+
+impl Cache {
+    /// The cached token for the scope, if it is still valid for a while
+    pub fn token(&self, scope: TokenScope, now: DateTime<Utc>) -> Option<InstallationAccessToken> {
+        let mut tokens = self.tokens.lock().unwrap();
+        // Drop expired tokens of other scopes as well, so they don't pile up
+        tokens.retain(|_, token| is_fresh(token, now));
+        tokens.get(&scope).cloned()
+    }
+
+    pub fn insert_token(&self, scope: TokenScope, token: InstallationAccessToken) {
+        self.tokens.lock().unwrap().insert(scope, token);
+    }
+
+    pub fn repo_numeric_id(&self, node_id: &str) -> Option<i64> {
+        self.repo_numeric_ids.lock().unwrap().get(node_id).copied()
+    }
+
+    pub fn insert_repo_numeric_id(&self, node_id: &str, numeric_id: i64) {
+        self.repo_numeric_ids.lock().unwrap().insert(node_id.to_owned(), numeric_id);
+    }
+}
+
+fn is_fresh(token: &InstallationAccessToken, now: DateTime<Utc>) -> bool {
+    token.expires_at - Duration::minutes(EXPIRY_MARGIN_MINUTES) > now
+}
@@ -0,0 +1,40 @@
+#![cfg(test)]
+
+use chrono::{Duration, Utc};
+
+use super::{Cache, TokenScope};
+use crate::InstallationAccessToken;
+
+fn token(name: &str, expires_in: Duration) -> InstallationAccessToken {
+    InstallationAccessToken { token: name.to_owned(), expires_at: Utc::now() + expires_in }
+}
+
+#[test]
+fn reuses_token_until_shortly_before_expiry() {
+    let cache = Cache::default();
+    let scope = TokenScope::Installation { installation_id: 1 };
+    cache.insert_token(scope, token("ghs_1", Duration::hours(1)));
+
+    let now = Utc::now();
+    assert_eq!(cache.token(scope, now).unwrap().token, "ghs_1");
+    assert!(cache.token(scope, now + Duration::minutes(56)).is_none());
+}
+
+#[test]
+fn keeps_scopes_apart() {
+    let cache = Cache::default();
+    let installation = TokenScope::Installation { installation_id: 1 };
+    let repository = TokenScope::RepositoryContents { installation_id: 1, repository_id: 2 };
+    cache.insert_token(installation, token("ghs_installation", Duration::hours(1)));
+
+    assert!(cache.token(repository, Utc::now()).is_none());
+    assert!(cache.token(TokenScope::Installation { installation_id: 2 }, Utc::now()).is_none());
+}
+
+#[test]
+fn caches_repo_numeric_ids() {
+    let cache = Cache::default();
+    assert_eq!(cache.repo_numeric_id("R_1"), None);
+    cache.insert_repo_numeric_id("R_1", 1);
+    assert_eq!(cache.repo_numeric_id("R_1"), Some(1));
+}
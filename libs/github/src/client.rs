@@ -1,7 +1,11 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
 use graphql_client::GraphQLQuery;
 use jwt_compact::alg::Rsa;
 use jwt_compact::AlgorithmExt;
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use reqwest::{RequestBuilder, Response};
 use rsa::pkcs1::DecodeRsaPrivateKey;
 use rsa::RsaPrivateKey;
 use serde::{Deserialize, Serialize};
@@ -9,19 +13,33 @@ use url::Url;
 
 use config::Config;
 
+use crate::cache::{Cache, TokenScope};
+use crate::error::{from_graphql_errors, Error, GraphQLError, Result};
+use crate::issue_comments;
+use crate::pagination::{self, next_page};
+use crate::rate_limit::{self, RateLimit, RateLimits};
 use crate::types::*;
 use crate::util::unix_time_in_seconds;
 use crate::IssueInfo;
 
 use super::graphql::{add_comment, AddComment};
+use super::graphql::{add_reaction, AddReaction};
 use super::graphql::{create_pull_request, CreatePullRequest};
 use super::graphql::{create_repo, CreateRepo};
+use super::graphql::{issue_comments_view, IssueCommentsView};
+use super::graphql::{issue_context_view, IssueContextView};
 use super::graphql::{issue_id_view, IssueIdView};
 use super::graphql::{issue_view, IssueView};
+use super::graphql::{organization_members, OrganizationMembers};
 use super::graphql::{repo_numeric_id, RepoNumericId};
+use super::graphql::{update_comment, UpdateComment};
 use super::graphql::{user_info_view, UserInfoView};
 use super::graphql::{viewer_info, ViewerInfo};
-use super::urls::{OAUTH_ACCESS_TOKEN_URL, REST_API_URL};
+use super::urls::GitHubUrls;
+
+/// The most comments [`WithAccess::issue_context`] fetches, of longer threads it fetches the first
+/// and the latest comments
+pub const MAX_ISSUE_COMMENTS: usize = 500;
 
 /// Accept header value
 const GITHUB_ACCEPT_JSON: &str = "application/vnd.github+json";
@@ -31,6 +49,9 @@ const GITHUB_API_VERSION: &str = "2022-11-28";
 pub struct GitHub {
     client: reqwest::Client,
     config: config::Config,
+    urls: GitHubUrls,
+    cache: Arc<Cache>,
+    rate_limits: Arc<RateLimits>,
 }
 
 impl GitHub {
@@ -45,33 +66,38 @@ impl GitHub {
             .build()
             .unwrap();
 
-        Self { client, config }
+        let urls = GitHubUrls::new(&config);
+        Self { client, config, urls, cache: Default::default(), rate_limits: Default::default() }
+    }
+
+    pub fn urls(&self) -> &GitHubUrls {
+        &self.urls
+    }
+
+    /// The remaining rate limit budget of each resource the app has used so far
+    pub fn rate_limits(&self) -> Vec<RateLimit> {
+        self.rate_limits.all()
     }
 
     pub fn with_access(&self, access_token: &str) -> WithAccess {
         WithAccess {
-            config: self.config.clone(),
             client: self.client.clone(),
+            urls: self.urls.clone(),
+            cache: self.cache.clone(),
+            rate_limits: self.rate_limits.clone(),
             access_token: access_token.to_owned(),
         }
     }
 
     /// Token to authenticate the GitHub app as a user
     /// https://docs.github.com/en/apps/creating-github-apps/authenticating-with-a-github-app/generating-a-user-access-token-for-a-github-app
-    pub async fn user_access_token(&self, code: &str) -> UserAccessToken {
-        self.client
-            .post(OAUTH_ACCESS_TOKEN_URL.as_str())
-            .query(&[
-                ("client_id", self.config.github_app_client_id.as_str()),
-                ("client_secret", self.config.github_app_client_secret.as_str()),
-                ("code", code),
-            ])
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap()
+    pub async fn user_access_token(&self, code: &str) -> Result<UserAccessToken> {
+        let request = self.client.post(self.urls.oauth_access_token()).query(&[
+            ("client_id", self.config.github_app_client_id.as_str()),
+            ("client_secret", self.config.github_app_client_secret.as_str()),
+            ("code", code),
+        ]);
+        Ok(self.send(request).await?.json().await?)
     }
 
     /// JWT access tokens for the GitHub app
@@ -97,77 +123,105 @@ impl GitHub {
         AppJWT { token }
     }
 
-    pub async fn set_webhook_config(&self, jwt: &AppJWT, url: Url) {
+    pub async fn set_webhook_config(&self, jwt: &AppJWT, url: Url) -> Result<()> {
         let json = WebhookConfig {
             url,
             content_type: "json".to_owned(),
             secret: "".to_owned(),
             insecure_ssl: "0".to_owned(),
         };
-        self.client
-            .patch(REST_API_URL.join("/app/hook/config").unwrap())
-            .header(AUTHORIZATION, jwt.header_value())
-            .json(&json)
-            .send()
-            .await
-            .unwrap();
+        self.send(
+            self.client
+                .patch(self.urls.rest("app/hook/config"))
+                .header(AUTHORIZATION, jwt.header_value())
+                .json(&json),
+        )
+        .await?;
+        Ok(())
     }
 
-    /// Create a installation access token for the GitHub app
-    pub async fn installation_access_token(&self, jwt: &AppJWT) -> InstallationAccessToken {
-        let installation_id = &self.config.github_app_installation_id;
-        let url = REST_API_URL
-            .join(&format!("/app/installations/{installation_id}/access_tokens"))
-            .unwrap();
-        self.client
-            .post(url)
-            .header(AUTHORIZATION, jwt.header_value())
-            .send()
-            .await
-            .unwrap()
+    /// Access GitHub as the installation of the GitHub app with the given ID
+    pub async fn with_installation_access(&self, installation_id: i64) -> Result<WithAccess> {
+        let access_token = self.installation_access_token(installation_id).await?;
+        Ok(self.with_access(&access_token.token))
+    }
+
+    /// Installation access token for the GitHub app, cached until shortly before it expires
+    pub async fn installation_access_token(
+        &self,
+        installation_id: i64,
+    ) -> Result<InstallationAccessToken> {
+        let scope = TokenScope::Installation { installation_id };
+        if let Some(token) = self.cache.token(scope, Utc::now()) {
+            return Ok(token);
+        }
+
+        let url = self.urls.rest(&format!("app/installations/{installation_id}/access_tokens"));
+        let token: InstallationAccessToken = self
+            .send(self.client.post(url).header(AUTHORIZATION, self.github_app_jwt().header_value()))
+            .await?
             .json()
-            .await
-            .unwrap()
+            .await?;
+
+        self.cache.insert_token(scope, token.clone());
+        Ok(token)
     }
 
+    /// Installation access token that can only write the contents of one repository, cached
+    /// until shortly before it expires
     pub async fn create_scoped_access_token(
         &self,
-        jwt: &AppJWT,
+        installation_id: i64,
         repository_id: i64,
-    ) -> InstallationAccessToken {
-        let installation_id = &self.config.github_app_installation_id;
-        let url = REST_API_URL
-            .join(&format!("/app/installations/{installation_id}/access_tokens"))
-            .unwrap();
+    ) -> Result<InstallationAccessToken> {
+        let scope = TokenScope::RepositoryContents { installation_id, repository_id };
+        if let Some(token) = self.cache.token(scope, Utc::now()) {
+            return Ok(token);
+        }
+
+        let url = self.urls.rest(&format!("app/installations/{installation_id}/access_tokens"));
 
         let request_body = ScopedAccessTokenRequest {
             repository_ids: vec![repository_id],
             permissions: Permissions { contents: "write".to_owned() },
         };
 
-        self.client
-            .post(url)
-            .header(AUTHORIZATION, jwt.header_value())
-            .json(&request_body)
-            .send()
-            .await
-            .expect("Failed to send scoped access token request")
+        let token: InstallationAccessToken = self
+            .send(
+                self.client
+                    .post(url)
+                    .header(AUTHORIZATION, self.github_app_jwt().header_value())
+                    .json(&request_body),
+            )
+            .await?
             .json()
-            .await
-            .expect("Failed to parse scoped access token response")
-    }
-
-    pub async fn installations(&self, jwt: &AppJWT) -> Vec<Installation> {
-        let url = REST_API_URL.join("/app/installations").unwrap();
-        self.client
-            .get(url)
-            .header(AUTHORIZATION, jwt.header_value())
-            .send()
-            .await
-            .expect("Failed to fetch app installations")
-            .json()
-            .await
-            .expect("Failed to parse app installations response")
+            .await?;
+
+        self.cache.insert_token(scope, token.clone());
+        Ok(token)
+    }
+
+    pub async fn installations(&self, jwt: &AppJWT) -> Result<Vec<Installation>> {
+        let mut url = self.urls.rest("app/installations");
+        url.query_pairs_mut().append_pair("per_page", pagination::PER_PAGE);
+
+        let mut installations = vec![];
+        loop {
+            let response =
+                self.send(self.client.get(url).header(AUTHORIZATION, jwt.header_value())).await?;
+            let next = next_page(response.headers());
+            let page: Vec<Installation> = response.json().await?;
+            installations.extend(page);
+
+            match next {
+                Some(next) => url = next,
+                None => return Ok(installations),
+            }
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        rate_limit::send(&self.rate_limits, request).await
     }
 }
 
@@ -184,92 +238,237 @@ struct Permissions {
 }
 
 pub struct WithAccess {
-    config: Config,
     client: reqwest::Client,
+    urls: GitHubUrls,
+    cache: Arc<Cache>,
+    rate_limits: Arc<RateLimits>,
     access_token: String,
 }
 
 impl WithAccess {
-    pub async fn viewer_info(&self) -> UserInfo {
-        let response_data = self.graphql::<ViewerInfo>(viewer_info::Variables).await;
+    pub async fn viewer_info(&self) -> Result<UserInfo> {
+        let response_data = self.graphql::<ViewerInfo>(viewer_info::Variables).await?;
 
-        UserInfo {
+        Ok(UserInfo {
             id: response_data.viewer.id,
             login: response_data.viewer.login,
             name: response_data.viewer.name,
-        }
+        })
     }
 
-    pub async fn user_info(&self, login: &str) -> Option<UserInfo> {
+    /// The user with the given login, `None` if there is none
+    pub async fn user_info(&self, login: &str) -> Result<Option<UserInfo>> {
         let vars = user_info_view::Variables { login: login.to_owned() };
-        let response_data = self.graphql::<UserInfoView>(vars).await;
+        let response_data = match self.graphql::<UserInfoView>(vars).await {
+            Ok(response_data) => response_data,
+            Err(Error::NotFound) => return Ok(None),
+            Err(err) => return Err(err),
+        };
 
-        response_data.user.map(|user| UserInfo { id: user.id, login: user.login, name: user.name })
+        Ok(response_data.user.map(|user| UserInfo {
+            id: user.id,
+            login: user.login,
+            name: user.name,
+        }))
     }
 
-    pub async fn user_email(&self) -> String {
-        let url = REST_API_URL.join("/user/emails").unwrap();
+    pub async fn user_email(&self) -> Result<String> {
+        let url = self.urls.rest("user/emails");
         let user_emails: Vec<UserEmail> = self
-            .client
-            .get(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .unwrap()
+            .send(
+                self.client.get(url).header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+            )
+            .await?
             .json()
-            .await
-            .unwrap();
+            .await?;
 
         user_emails
             .into_iter()
             .filter(|email| email.primary)
             .map(|email| email.email)
             .next()
-            .unwrap()
+            .ok_or(Error::UnexpectedResponse("no primary email address"))
     }
 
-    pub async fn issue_info(&self, issue_id: &str) -> IssueInfo {
+    pub async fn issue_info(&self, issue_id: &str) -> Result<IssueInfo> {
         let vars = issue_view::Variables { issue_id: issue_id.to_string() };
-        let response_data = self.graphql::<IssueView>(vars).await;
-        let issue_view::IssueViewNode::Issue(issue) = response_data.node.unwrap() else {
-            panic!("expected issue");
+        let response_data = self.graphql::<IssueView>(vars).await?;
+        let Some(issue_view::IssueViewNode::Issue(issue)) = response_data.node else {
+            return Err(Error::UnexpectedResponse("node is not an issue"));
+        };
+        Ok(IssueInfo { body: issue.body, author: issue.author.map(|author| author.login) })
+    }
+
+    /// The issue with its labels, comments and the issues and pull requests that mention it
+    pub async fn issue_context(&self, issue_id: &str) -> Result<IssueContext> {
+        use issue_context_view::{
+            IssueContextViewNode as Node, IssueContextViewNodeOnIssueTimelineItemsNodes as Item,
+            IssueContextViewNodeOnIssueTimelineItemsNodesOnCrossReferencedEventSource as Source,
+            IssueState, PullRequestState,
+        };
+
+        let vars = issue_context_view::Variables { issue_id: issue_id.to_owned() };
+        let response_data = self.graphql::<IssueContextView>(vars).await?;
+        let Some(Node::Issue(issue)) = response_data.node else {
+            return Err(Error::UnexpectedResponse("node is not an issue"));
+        };
+
+        let labels = issue.labels.and_then(|labels| labels.nodes).unwrap_or_default();
+        let labels = labels.into_iter().flatten().map(|label| label.name).collect();
+
+        let latest = (issue.comments.nodes.into_iter().flatten().flatten())
+            .map(|comment| IssueComment {
+                author: comment.author.map(|author| author.login),
+                body: comment.body,
+                created_at: comment.created_at,
+            })
+            .collect();
+        let first = issue.first_comment.nodes.into_iter().flatten().flatten().next();
+        let first = first.map(|first| IssueComment {
+            author: first.author.map(|author| author.login),
+            body: first.body,
+            created_at: first.created_at,
+        });
+        let page_info = issue.comments.page_info;
+        let before = page_info.start_cursor.filter(|_| page_info.has_previous_page);
+        let comments = issue_comments::first_and_latest(first, latest, before, |cursor| {
+            self.issue_comments_page(issue_id, cursor)
+        })
+        .await?;
+
+        let items = issue.timeline_items.nodes.into_iter().flatten().flatten();
+        let linked = items
+            .filter_map(|item| match item {
+                Item::CrossReferencedEvent(event) => Some(event.source),
+                _ => None,
+            })
+            .map(|source| match source {
+                Source::Issue(issue) => LinkedItem {
+                    kind: LinkedItemKind::Issue,
+                    repository: issue.repository.name_with_owner,
+                    number: issue.number,
+                    title: issue.title,
+                    state: match issue.issue_state {
+                        IssueState::OPEN => LinkedItemState::Open,
+                        IssueState::CLOSED => LinkedItemState::Closed,
+                        IssueState::Other(_) => LinkedItemState::Unknown,
+                    },
+                },
+                Source::PullRequest(pull_request) => LinkedItem {
+                    kind: LinkedItemKind::PullRequest,
+                    repository: pull_request.repository.name_with_owner,
+                    number: pull_request.number,
+                    title: pull_request.title,
+                    state: match pull_request.pull_request_state {
+                        PullRequestState::OPEN => LinkedItemState::Open,
+                        PullRequestState::CLOSED => LinkedItemState::Closed,
+                        PullRequestState::MERGED => LinkedItemState::Merged,
+                        PullRequestState::Other(_) => LinkedItemState::Unknown,
+                    },
+                },
+            })
+            .collect();
+
+        Ok(IssueContext {
+            number: issue.number,
+            title: issue.title,
+            body: issue.body,
+            author: issue.author.map(|author| author.login),
+            labels,
+            comments,
+            total_comments: issue.comments.total_count,
+            linked,
+        })
+    }
+
+    /// The comments on an issue before the cursor and the cursor of the previous page, if any
+    async fn issue_comments_page(
+        &self,
+        issue_id: &str,
+        before: String,
+    ) -> Result<(Vec<IssueComment>, Option<String>)> {
+        use issue_comments_view::IssueCommentsViewNode as Node;
+
+        let vars =
+            issue_comments_view::Variables { issue_id: issue_id.to_owned(), before: Some(before) };
+        let response_data = self.graphql::<IssueCommentsView>(vars).await?;
+        let Some(Node::Issue(issue)) = response_data.node else {
+            return Err(Error::UnexpectedResponse("node is not an issue"));
         };
-        IssueInfo { body: issue.body }
+        let comments = (issue.comments.nodes.into_iter().flatten().flatten())
+            .map(|comment| IssueComment {
+                author: comment.author.map(|author| author.login),
+                body: comment.body,
+                created_at: comment.created_at,
+            })
+            .collect();
+        let page_info = issue.comments.page_info;
+        Ok((comments, page_info.start_cursor.filter(|_| page_info.has_previous_page)))
     }
 
-    pub async fn issue_id(&self, repo_owner: &str, repo_name: &str, issue_number: i64) -> String {
+    pub async fn issue_id(
+        &self,
+        repo_owner: &str,
+        repo_name: &str,
+        issue_number: i64,
+    ) -> Result<String> {
         let vars = issue_id_view::Variables {
             repo_owner: repo_owner.to_owned(),
             repo_name: repo_name.to_owned(),
             issue_number,
         };
-        let response_data = self.graphql::<IssueIdView>(vars).await;
-        response_data.repository.unwrap().issue.unwrap().id
+        let response_data = self.graphql::<IssueIdView>(vars).await?;
+        let issue = response_data.repository.and_then(|repository| repository.issue);
+        Ok(issue.ok_or(Error::NotFound)?.id)
     }
 
-    pub async fn add_comment(&self, subject_id: &str, body: &str) {
+    /// Comment on an issue or pull request and return the ID of the comment
+    pub async fn add_comment(&self, subject_id: &str, body: &str) -> Result<String> {
         let vars =
             add_comment::Variables { subject_id: subject_id.to_owned(), body: body.to_owned() };
-        self.graphql::<AddComment>(vars).await;
+        let response_data = self.graphql::<AddComment>(vars).await?;
+        let comment = response_data
+            .add_comment
+            .and_then(|add_comment| add_comment.comment_edge)
+            .and_then(|comment_edge| comment_edge.node);
+        Ok(comment.ok_or(Error::UnexpectedResponse("missing comment"))?.id)
+    }
+
+    pub async fn update_comment(&self, comment_id: &str, body: &str) -> Result<()> {
+        let vars =
+            update_comment::Variables { comment_id: comment_id.to_owned(), body: body.to_owned() };
+        self.graphql::<UpdateComment>(vars).await?;
+        Ok(())
     }
 
-    pub async fn create_repo(&self, name: &str) -> String {
-        let vars = create_repo::Variables {
-            owner_id: self.config.github_app_organization_id.to_owned(),
-            name: name.to_owned(),
+    pub async fn add_reaction(&self, subject_id: &str, reaction: Reaction) -> Result<()> {
+        use add_reaction::ReactionContent;
+
+        let content = match reaction {
+            Reaction::Eyes => ReactionContent::EYES,
+            Reaction::ThumbsDown => ReactionContent::THUMBS_DOWN,
         };
-        let response_data = self.graphql::<CreateRepo>(vars).await;
-        response_data.create_repository.unwrap().repository.unwrap().id
+        let vars = add_reaction::Variables { subject_id: subject_id.to_owned(), content };
+        self.graphql::<AddReaction>(vars).await?;
+        Ok(())
     }
 
-    pub async fn delete_repo(&self, repo_name: &str) {
-        let url = REST_API_URL.join(&format!("/repos/{}", repo_name)).unwrap();
-        self.client
-            .delete(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .unwrap();
+    pub async fn create_repo(&self, owner_id: &str, name: &str) -> Result<String> {
+        let vars = create_repo::Variables { owner_id: owner_id.to_owned(), name: name.to_owned() };
+        let response_data = self.graphql::<CreateRepo>(vars).await?;
+        let repository = response_data
+            .create_repository
+            .and_then(|create_repository| create_repository.repository);
+        Ok(repository.ok_or(Error::UnexpectedResponse("missing repository"))?.id)
+    }
+
+    pub async fn delete_repo(&self, repo_name: &str) -> Result<()> {
+        let url = self.urls.rest(&format!("repos/{}", repo_name));
+        self.send(
+            self.client.delete(url).header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+        )
+        .await?;
+        Ok(())
     }
 
     pub async fn create_pull_request(
@@ -278,86 +477,211 @@ impl WithAccess {
         title: &str,
         body: &str,
         head: &str,
-    ) -> String {
+        base: &str,
+    ) -> Result<String> {
         let vars = create_pull_request::Variables {
             repo_id: repo_id.to_owned(),
             title: title.to_owned(),
             body: body.to_owned(),
             head_ref: head.to_owned(),
-            base_ref: "main".to_owned(),
+            base_ref: base.to_owned(),
         };
-        let response_data = self.graphql::<CreatePullRequest>(vars).await;
-        response_data.create_pull_request.unwrap().pull_request.unwrap().id
+        let response_data = self.graphql::<CreatePullRequest>(vars).await?;
+        let pull_request = response_data
+            .create_pull_request
+            .and_then(|create_pull_request| create_pull_request.pull_request);
+        Ok(pull_request.ok_or(Error::UnexpectedResponse("missing pull request"))?.id)
+    }
+
+    pub async fn review_comments(
+        &self,
+        repo_full_name: &str,
+        pull_request_number: i64,
+        review_id: i64,
+    ) -> Result<Vec<ReviewComment>> {
+        let url = self.urls.rest(&format!(
+            "repos/{}/pulls/{}/reviews/{}/comments",
+            repo_full_name, pull_request_number, review_id
+        ));
+        let response = self
+            .send(
+                self.client.get(url).header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+            )
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// The SHA of the commit the branch points to
+    pub async fn branch_head(&self, repo_full_name: &str, branch: &str) -> Result<String> {
+        let url = self.urls.rest(&format!("repos/{}/git/ref/heads/{}", repo_full_name, branch));
+        let git_ref: GitRef = self
+            .send(
+                self.client.get(url).header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+            )
+            .await?
+            .json()
+            .await?;
+        Ok(git_ref.object.sha)
+    }
+
+    /// Create a check run of the app on a commit and return its ID
+    pub async fn create_check_run(
+        &self,
+        repo_full_name: &str,
+        new_check_run: &NewCheckRun<'_>,
+    ) -> Result<i64> {
+        let url = self.urls.rest(&format!("repos/{}/check-runs", repo_full_name));
+        let check_run: CheckRunId = self
+            .send(
+                self.client
+                    .post(url)
+                    .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+                    .json(new_check_run),
+            )
+            .await?
+            .json()
+            .await?;
+        Ok(check_run.id)
     }
 
-    pub async fn repo_numeric_id_by_node_id(&self, node_id: &str) -> i64 {
+    pub async fn update_check_run(
+        &self,
+        repo_full_name: &str,
+        check_run_id: i64,
+        state: &CheckRunState,
+    ) -> Result<()> {
+        let url = self.urls.rest(&format!("repos/{}/check-runs/{}", repo_full_name, check_run_id));
+        self.send(
+            self.client
+                .patch(url)
+                .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+                .json(state),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The numeric ID of a repository, which the REST API uses, cached by node ID
+    pub async fn repo_numeric_id_by_node_id(&self, node_id: &str) -> Result<i64> {
+        if let Some(numeric_id) = self.cache.repo_numeric_id(node_id) {
+            return Ok(numeric_id);
+        }
+
         let vars = repo_numeric_id::Variables { node_id: node_id.to_owned() };
-        let response_data = self.graphql::<RepoNumericId>(vars).await;
+        let response_data = self.graphql::<RepoNumericId>(vars).await?;
 
-        let repo_numeric_id::RepoNumericIdNode::Repository(repo) = response_data.node.unwrap()
-        else {
-            panic!("expected repository");
+        let Some(repo_numeric_id::RepoNumericIdNode::Repository(repo)) = response_data.node else {
+            return Err(Error::UnexpectedResponse("node is not a repository"));
         };
-        repo.database_id.unwrap()
+        let numeric_id =
+            repo.database_id.ok_or(Error::UnexpectedResponse("missing repository database ID"))?;
+        self.cache.insert_repo_numeric_id(node_id, numeric_id);
+        Ok(numeric_id)
     }
 
-    pub async fn installation_repositories(&self) -> Vec<InstallationRepository> {
-        let url = REST_API_URL.join("/installation/repositories").unwrap();
-        let InstallationRepositories { repositories } = self
-            .client
-            .get(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap();
-        repositories
+    pub async fn installation_repositories(&self) -> Result<Vec<InstallationRepository>> {
+        let mut url = self.urls.rest("installation/repositories");
+        url.query_pairs_mut().append_pair("per_page", pagination::PER_PAGE);
+
+        let mut repositories = vec![];
+        loop {
+            let response = self
+                .send(
+                    self.client
+                        .get(url)
+                        .header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+                )
+                .await?;
+            let next = next_page(response.headers());
+            let page: InstallationRepositories = response.json().await?;
+            repositories.extend(page.repositories);
+
+            match next {
+                Some(next) => url = next,
+                None => return Ok(repositories),
+            }
+        }
     }
 
-    pub async fn organization_members(&self, org: &str) -> Vec<User> {
-        let url = REST_API_URL.join(&format!("/orgs/{}/members", org)).unwrap();
-        let members: Vec<User> = self
-            .client
-            .get(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap();
-        members
+    /// The members of the organization and their roles
+    pub async fn organization_members(&self, org: &str) -> Result<Vec<Member>> {
+        use organization_members::OrganizationMemberRole;
+
+        let mut members = vec![];
+        let mut after = None;
+        loop {
+            let vars = organization_members::Variables { login: org.to_owned(), after };
+            let response_data = self.graphql::<OrganizationMembers>(vars).await?;
+            let organization = response_data.organization.ok_or(Error::NotFound)?;
+            let connection = organization.members_with_role;
+
+            for edge in connection.edges.into_iter().flatten().flatten() {
+                let Some(user) = edge.node else {
+                    continue;
+                };
+                let role = match edge.role {
+                    Some(OrganizationMemberRole::ADMIN) => Role::Admin,
+                    Some(OrganizationMemberRole::MEMBER) => Role::Member,
+                    _ => Role::Unknown,
+                };
+                let user = User { name: user.name, login: user.login, node_id: user.id };
+                members.push(Member { user, role });
+            }
+
+            if !connection.page_info.has_next_page {
+                return Ok(members);
+            }
+            after = connection.page_info.end_cursor;
+        }
     }
 
-    pub async fn organization_membership(&self, org: &str, user: &str) -> Membership {
-        let url = REST_API_URL.join(&format!("/orgs/{}/memberships/{}", org, user)).unwrap();
-        self.client
-            .get(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .unwrap()
-            .json()
-            .await
-            .unwrap()
+    /// The membership of the user in the organization, `None` if the user is not a member
+    pub async fn organization_membership(
+        &self,
+        org: &str,
+        user: &str,
+    ) -> Result<Option<Membership>> {
+        let url = self.urls.rest(&format!("orgs/{}/memberships/{}", org, user));
+        let response = self
+            .send(
+                self.client.get(url).header(AUTHORIZATION, format!("Bearer {}", self.access_token)),
+            )
+            .await;
+        match response {
+            Ok(response) => Ok(Some(response.json().await?)),
+            Err(Error::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
     }
 
-    async fn graphql<Q: GraphQLQuery>(&self, vars: Q::Variables) -> Q::ResponseData {
-        let response_body = post_graphql::<Q, _>(
-            &self.client,
-            &self.access_token,
-            "https://api.github.com/graphql",
-            vars,
-        )
-        .await
-        .unwrap();
+    async fn graphql<Q: GraphQLQuery>(&self, vars: Q::Variables) -> Result<Q::ResponseData> {
+        let body = Q::build_query(vars);
+        let request = self
+            .client
+            .post(self.urls.graphql())
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+            .json(&body);
+        let response_body: GraphQLResponse<Q::ResponseData> =
+            self.send(request).await?.json().await?;
+
+        if let Some(errors) = response_body.errors.filter(|errors| !errors.is_empty()) {
+            return Err(from_graphql_errors(errors));
+        }
+        response_body.data.ok_or(Error::UnexpectedResponse("missing response data"))
+    }
 
-        response_body.data.expect("missing response data")
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        rate_limit::send(&self.rate_limits, request).await
     }
 }
 
+/// GraphQL response with the errors as GitHub reports them
+#[derive(Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQLError>>,
+}
+
 #[derive(Deserialize)]
 struct UserEmail {
     email: String,
@@ -375,10 +699,10 @@ pub struct AppJWT {
     token: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct InstallationAccessToken {
     pub token: String,
-    pub expires_at: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 impl AppJWT {
@@ -388,24 +712,6 @@ impl AppJWT {
     }
 }
 
-async fn post_graphql<Q: GraphQLQuery, U: reqwest::IntoUrl>(
-    client: &reqwest::Client,
-    access_token: &str,
-    url: U,
-    variables: Q::Variables,
-) -> Result<graphql_client::Response<Q::ResponseData>, reqwest::Error> {
-    let body = Q::build_query(variables);
-
-    let response = client
-        .post(url)
-        .header(AUTHORIZATION, &format!("Bearer {}", access_token))
-        .json(&body)
-        .send()
-        .await?;
-
-    response.json().await
-}
-
 /// https://docs.github.com/en/apps/creating-github-apps/authenticating-with-a-github-app/generating-a-json-web-token-jwt-for-a-github-app
 #[derive(Serialize)]
 struct JWTClaims {
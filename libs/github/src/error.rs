@@ -0,0 +1,96 @@
+//! Errors of requests to GitHub
+
+use std::time::Duration;
+
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The resource does not exist or is not visible to the app
+    #[error("Not found")]
+    NotFound,
+    /// The app or user lacks the permissions for the request
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    /// The rate limit did not reset in time to retry the request
+    #[error("Rate limited, retry in {} seconds", retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+    /// The GraphQL API answered with errors
+    #[error("GraphQL errors: {}", format_graphql_errors(.0))]
+    GraphQL(Vec<GraphQLError>),
+    /// Any other unsuccessful response
+    #[error("Unexpected status {status}: {body}")]
+    Status { status: StatusCode, body: String },
+    /// The response lacks data or has data of an unexpected type
+    #[error("Unexpected response: {0}")]
+    UnexpectedResponse(&'static str),
+    /// The request could not be sent or the response could not be read
+    #[error("Transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+}
+
+impl Error {
+    /// Whether the request may succeed when retried later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::RateLimited { .. } | Error::Transport(_) => true,
+            Error::Status { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
+/// An error of the GraphQL API, GitHub adds its type next to the message
+#[derive(Deserialize, Debug)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+fn format_graphql_errors(errors: &[GraphQLError]) -> String {
+    let messages: Vec<_> = errors.iter().map(|error| error.message.as_str()).collect();
+    messages.join(", ")
+}
+
+/// Distinguish the errors that are not GraphQL errors in general
+pub(crate) fn from_graphql_errors(errors: Vec<GraphQLError>) -> Error {
+    match errors.first().and_then(|error| error.kind.as_deref()) {
+        Some("NOT_FOUND") => Error::NotFound,
+        Some("FORBIDDEN") => Error::Forbidden(errors[0].message.clone()),
+        _ => Error::GraphQL(errors),
+    }
+}
+
+/// Turn unsuccessful responses into errors
+pub(crate) async fn error_for_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await?;
+    Err(error_from_body(status, body))
+}
+
+/// The error of an unsuccessful response with the given body
+pub(crate) fn error_from_body(status: StatusCode, body: String) -> Error {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        message: String,
+    }
+    let message = match serde_json::from_str::<ErrorBody>(&body) {
+        Ok(ErrorBody { message }) => message,
+        Err(_) => body,
+    };
+
+    match status {
+        StatusCode::NOT_FOUND => Error::NotFound,
+        StatusCode::FORBIDDEN => Error::Forbidden(message),
+        status => Error::Status { status, body: message },
+    }
+}
@@ -1,5 +1,8 @@
 use graphql_client::GraphQLQuery;
 
+/// Custom scalar of the schema
+type DateTime = chrono::DateTime<chrono::Utc>;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "graphql/schema/schema.graphql",
@@ -24,6 +27,22 @@ pub struct UserInfoView;
 )]
 pub struct IssueView;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.graphql",
+    query_path = "graphql/query/issue_context.graphql",
+    response_derives = "Debug"
+)]
+pub struct IssueContextView;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.graphql",
+    query_path = "graphql/query/issue_comments.graphql",
+    response_derives = "Debug"
+)]
+pub struct IssueCommentsView;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "graphql/schema/schema.graphql",
@@ -40,6 +59,22 @@ pub struct IssueIdView;
 )]
 pub struct AddComment;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.graphql",
+    query_path = "graphql/mutation/update_comment.graphql",
+    response_derives = "Debug"
+)]
+pub struct UpdateComment;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.graphql",
+    query_path = "graphql/mutation/add_reaction.graphql",
+    response_derives = "Debug"
+)]
+pub struct AddReaction;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "graphql/schema/schema.graphql",
@@ -63,3 +98,11 @@ pub struct CreatePullRequest;
     response_derives = "Debug"
 )]
 pub struct RepoNumericId;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "graphql/schema/schema.graphql",
+    query_path = "graphql/query/organization_members.graphql",
+    response_derives = "Debug"
+)]
+pub struct OrganizationMembers;
@@ -0,0 +1,37 @@
+//! Fetching the first and the latest comments of long issue threads
+
+use std::future::Future;
+
+use crate::error::Result;
+use crate::types::IssueComment;
+use crate::MAX_ISSUE_COMMENTS;
+
+mod tests;
+
+/// The latest comments, fetched backwards from the `before` cursor with `previous_page`, and the
+/// first comment if the latest ones don't reach it
+pub(crate) async fn first_and_latest<F, Fut>(
+    first: Option<IssueComment>,
+    mut comments: Vec<IssueComment>,
+    mut before: Option<String>,
+    mut previous_page: F,
+) -> Result<Vec<IssueComment>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<(Vec<IssueComment>, Option<String>)>>,
+{
+    while comments.len() < MAX_ISSUE_COMMENTS {
+        let Some(cursor) = before.take() else { break };
+        let (page, previous_cursor) = previous_page(cursor).await?;
+        comments.splice(..0, page);
+        before = previous_cursor;
+    }
+    let reached_first = before.is_none() && comments.len() <= MAX_ISSUE_COMMENTS;
+    if !reached_first {
+        comments.drain(..comments.len().saturating_sub(MAX_ISSUE_COMMENTS - 1));
+        if let Some(first) = first {
+            comments.insert(0, first);
+        }
+    }
+    Ok(comments)
+}
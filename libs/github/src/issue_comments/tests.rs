@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use chrono::Utc;
+
+use super::first_and_latest;
+use crate::types::IssueComment;
+use crate::MAX_ISSUE_COMMENTS;
+
+const PAGE_LEN: usize = 100;
+
+fn comment(index: usize) -> IssueComment {
+    IssueComment { author: None, body: index.to_string(), created_at: Utc::now() }
+}
+
+/// Fetch the comments of a thread like GitHub does, `PAGE_LEN` at a time from the end
+async fn fetch(count: usize) -> Vec<IssueComment> {
+    let start = count.saturating_sub(PAGE_LEN);
+    let latest = (start..count).map(comment).collect();
+    let before = (start > 0).then(|| start.to_string());
+    let previous_page = |cursor: String| async move {
+        let end: usize = cursor.parse().unwrap();
+        let start = end.saturating_sub(PAGE_LEN);
+        let page = (start..end).map(comment).collect();
+        Ok((page, (start > 0).then(|| start.to_string())))
+    };
+    first_and_latest(Some(comment(0)), latest, before, previous_page).await.unwrap()
+}
+
+fn bodies(comments: &[IssueComment]) -> Vec<usize> {
+    comments.iter().map(|comment| comment.body.parse().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn keeps_all_comments_of_short_threads() {
+    let comments = fetch(250).await;
+
+    assert_eq!(bodies(&comments), (0..250).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn keeps_first_and_latest_comments_of_long_threads() {
+    let count = 1_234;
+
+    let comments = fetch(count).await;
+
+    assert_eq!(comments.len(), MAX_ISSUE_COMMENTS);
+    let bodies = bodies(&comments);
+    assert_eq!(bodies[0], 0);
+    // The comments in between are left out
+    assert_eq!(bodies[1], count - (MAX_ISSUE_COMMENTS - 1));
+    assert_eq!(bodies.last(), Some(&(count - 1)));
+}
+
+#[tokio::test]
+async fn keeps_first_comment_of_threads_just_over_the_limit() {
+    let comments = fetch(MAX_ISSUE_COMMENTS + 1).await;
+
+    let bodies = bodies(&comments);
+    assert_eq!(bodies.len(), MAX_ISSUE_COMMENTS);
+    assert_eq!(bodies[..2], [0, 2]);
+}
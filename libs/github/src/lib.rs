@@ -1,8 +1,15 @@
+mod cache;
 mod client;
+mod error;
 mod graphql;
+mod issue_comments;
+mod pagination;
+mod rate_limit;
 pub mod types;
 pub mod urls;
 mod util;
 
 pub use client::*;
+pub use error::{Error, GraphQLError, Result};
+pub use rate_limit::RateLimit;
 pub use types::*;
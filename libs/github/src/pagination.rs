@@ -0,0 +1,24 @@
+//! Following the pages of REST API responses
+//!
+//! https://docs.github.com/en/rest/using-the-rest-api/using-pagination-in-the-rest-api
+
+use reqwest::header::{HeaderMap, LINK};
+use url::Url;
+
+mod tests;
+
+/// Number of items requested per page, the maximum the REST API allows
+pub const PER_PAGE: &str = "100";
+
+/// The URL of the next page from the `link` header of a response, `None` on the last page
+pub fn next_page(headers: &HeaderMap) -> Option<Url> {
+    let link = headers.get(LINK)?.to_str().ok()?;
+    link.split(',').find_map(|link| {
+        let (url, params) = link.trim().split_once(';')?;
+        if !params.split(';').any(|param| param.trim() == r#"rel="next""#) {
+            return None;
+        }
+        let url = url.trim().strip_prefix('<')?.strip_suffix('>')?;
+        Url::parse(url).ok()
+    })
+}
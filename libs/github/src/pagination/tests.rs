@@ -0,0 +1,31 @@
+#![cfg(test)]
+
+use reqwest::header::{HeaderMap, HeaderValue, LINK};
+
+use super::next_page;
+
+fn headers(link: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(LINK, HeaderValue::from_str(link).unwrap());
+    headers
+}
+
+#[test]
+fn finds_next_page() {
+    let headers = headers(
+        r#"<https://api.github.com/orgs/octo/members?page=1>; rel="prev", <https://api.github.com/orgs/octo/members?page=3>; rel="next", <https://api.github.com/orgs/octo/members?page=5>; rel="last""#,
+    );
+    assert_eq!(
+        next_page(&headers).unwrap().as_str(),
+        "https://api.github.com/orgs/octo/members?page=3"
+    );
+}
+
+#[test]
+fn last_page_has_no_next_page() {
+    let headers = headers(
+        r#"<https://api.github.com/orgs/octo/members?page=1>; rel="first", <https://api.github.com/orgs/octo/members?page=4>; rel="prev""#,
+    );
+    assert!(next_page(&headers).is_none());
+    assert!(next_page(&HeaderMap::new()).is_none());
+}
@@ -0,0 +1,128 @@
+//! Waiting out GitHub rate limits and keeping track of the remaining budget
+//!
+//! https://docs.github.com/en/rest/using-the-rest-api/rate-limits-for-the-rest-api
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::error::{error_for_status, error_from_body, Error, Result};
+use crate::util::unix_time_in_seconds;
+
+mod tests;
+
+/// Requests are sent at most this many times
+const MAX_ATTEMPTS: u32 = 4;
+/// Secondary rate limits without a `retry-after` header are retried after at least a minute
+const SECONDARY_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+/// Rate limits that reset later than this are not waited for, the request fails instead
+const MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// The rate limit budget of a resource, as of the most recent response
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    /// `core` for the REST API, `graphql` for the GraphQL API, ...
+    pub resource: String,
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix time in seconds at which the budget is reset
+    pub reset: u64,
+}
+
+#[derive(Default)]
+pub struct RateLimits {
+    by_resource: Mutex<BTreeMap<String, RateLimit>>,
+}
+
+impl RateLimits {
+    pub fn all(&self) -> Vec<RateLimit> {
+        self.by_resource.lock().unwrap().values().cloned().collect()
+    }
+
+    fn record(&self, headers: &HeaderMap) {
+        let (Some(resource), Some(limit), Some(remaining), Some(reset)) = (
+            header(headers, "x-ratelimit-resource"),
+            header(headers, "x-ratelimit-limit").and_then(|value| value.parse().ok()),
+            header(headers, "x-ratelimit-remaining").and_then(|value| value.parse().ok()),
+            header(headers, "x-ratelimit-reset").and_then(|value| value.parse().ok()),
+        ) else {
+            return;
+        };
+        let rate_limit = RateLimit { resource: resource.to_owned(), limit, remaining, reset };
+        self.by_resource.lock().unwrap().insert(resource.to_owned(), rate_limit);
+    }
+}
+
+/// Send the request, retrying with backoff while it is rate limited
+///
+/// Unsuccessful responses are turned into errors.
+pub async fn send(rate_limits: &RateLimits, request: RequestBuilder) -> Result<Response> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let Some(retry) = request.try_clone() else {
+            // Streaming bodies can't be sent again
+            return error_for_status(request.send().await?).await;
+        };
+        let response = retry.send().await?;
+        rate_limits.record(response.headers());
+
+        let status = response.status();
+        let delay = match retry_delay(&response, attempt) {
+            Some(delay) => delay,
+            // Only the message tells secondary rate limits without headers from missing permissions
+            None if status == StatusCode::FORBIDDEN => {
+                let body = response.text().await?;
+                if !is_secondary_rate_limit(&body) {
+                    return Err(error_from_body(status, body));
+                }
+                secondary_rate_limit_delay(attempt)
+            }
+            None => return error_for_status(response).await,
+        };
+        if attempt >= MAX_ATTEMPTS || delay > MAX_DELAY {
+            return Err(Error::RateLimited { retry_after: delay });
+        }
+        println!("GitHub rate limit exceeded, retrying in {} seconds", delay.as_secs());
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// How long to wait before retrying a rate limited response, `None` if it is not rate limited
+fn retry_delay(response: &Response, attempt: u32) -> Option<Duration> {
+    let status = response.status();
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let headers = response.headers();
+
+    if let Some(seconds) = header(headers, "retry-after").and_then(|value| value.parse().ok()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header(headers, "x-ratelimit-remaining") == Some("0") {
+        let reset: u64 = header(headers, "x-ratelimit-reset")?.parse().ok()?;
+        // One more second so the budget has surely been reset
+        return Some(Duration::from_secs(reset.saturating_sub(unix_time_in_seconds()) + 1));
+    }
+    // Forbidden responses are told apart by their body
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Some(secondary_rate_limit_delay(attempt));
+    }
+    None
+}
+
+fn secondary_rate_limit_delay(attempt: u32) -> Duration {
+    SECONDARY_RATE_LIMIT_DELAY * 2u32.pow(attempt - 1)
+}
+
+/// Whether the body of a forbidden response is about a secondary rate limit
+fn is_secondary_rate_limit(body: &str) -> bool {
+    body.to_lowercase().contains("secondary rate limit")
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
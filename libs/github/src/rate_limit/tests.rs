@@ -0,0 +1,15 @@
+#![cfg(test)]
+
+use super::is_secondary_rate_limit;
+
+#[test]
+fn recognises_secondary_rate_limit_message() {
+    let body = r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again.","documentation_url":"https://docs.github.com/rest"}"#;
+    assert!(is_secondary_rate_limit(body));
+}
+
+#[test]
+fn missing_permissions_are_not_rate_limits() {
+    let body = r#"{"message":"Resource not accessible by integration","documentation_url":"https://docs.github.com/rest"}"#;
+    assert!(!is_secondary_rate_limit(body));
+}
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize)]
 pub struct UserInfo {
@@ -8,6 +9,61 @@ pub struct UserInfo {
 }
 pub struct IssueInfo {
     pub body: String,
+    /// Unset if the account of the author was deleted
+    pub author: Option<String>,
+}
+
+/// An issue with everything an agent needs to work on it
+#[derive(Debug, Serialize)]
+pub struct IssueContext {
+    pub number: i64,
+    pub title: String,
+    pub body: String,
+    /// Unset if the account of the author was deleted
+    pub author: Option<String>,
+    pub labels: Vec<String>,
+    /// The first and the latest comments in the order they were made, at most
+    /// [`MAX_ISSUE_COMMENTS`] of them
+    ///
+    /// [`MAX_ISSUE_COMMENTS`]: crate::MAX_ISSUE_COMMENTS
+    pub comments: Vec<IssueComment>,
+    /// The number of comments on the issue, including those not in `comments`
+    pub total_comments: i64,
+    /// Issues and pull requests that mention the issue
+    pub linked: Vec<LinkedItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IssueComment {
+    pub author: Option<String>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkedItem {
+    pub kind: LinkedItemKind,
+    /// Owner and name of the repository the item is in
+    pub repository: String,
+    pub number: i64,
+    pub title: String,
+    pub state: LinkedItemState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkedItemKind {
+    Issue,
+    PullRequest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkedItemState {
+    Open,
+    Closed,
+    Merged,
+    Unknown,
 }
 
 #[derive(Debug, Deserialize)]
@@ -17,9 +73,36 @@ pub struct User {
     pub node_id: String,
 }
 
+/// https://docs.github.com/en/rest/orgs/members?apiVersion=2022-11-28#get-organization-membership-for-a-user
 #[derive(Debug, Deserialize)]
 pub struct Membership {
     pub role: Role,
+    pub state: MembershipState,
+    pub user: Option<User>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub enum MembershipState {
+    #[serde(rename = "active")]
+    Active,
+    /// The user was invited and has not accepted yet
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(other)]
+    Unknown,
+}
+
+/// An invitation to join an organization
+#[derive(Debug, Deserialize)]
+pub struct Invitation {
+    /// Unset for invitations by email
+    pub login: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Organization {
+    pub login: String,
+    pub node_id: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +115,13 @@ pub enum Role {
     Unknown,
 }
 
+/// A member of an organization and their role in it
+#[derive(Debug)]
+pub struct Member {
+    pub user: User,
+    pub role: Role,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Repo {
     pub full_name: String,
@@ -47,6 +137,49 @@ pub struct Issue {
     pub body: String,
     pub number: i64,
     pub user: Option<User>,
+    /// Set if the issue is a pull request
+    pub pull_request: Option<IssuePullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssuePullRequest {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequest {
+    pub node_id: String,
+    pub number: i64,
+}
+
+/// https://docs.github.com/en/rest/pulls/reviews?apiVersion=2022-11-28
+#[derive(Debug, Deserialize)]
+pub struct Review {
+    pub id: i64,
+    pub node_id: String,
+    pub body: Option<String>,
+    pub user: User,
+    pub state: String,
+}
+
+/// https://docs.github.com/en/rest/pulls/comments?apiVersion=2022-11-28
+#[derive(Debug, Deserialize)]
+pub struct ReviewComment {
+    pub id: i64,
+    pub node_id: String,
+    pub body: String,
+    pub user: User,
+    pub path: String,
+    /// Unset if the line is no longer part of the diff
+    pub line: Option<i32>,
+    pub original_line: Option<i32>,
+    pub diff_hunk: String,
+    pub pull_request_review_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Label {
+    pub name: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +189,15 @@ pub struct Comment {
     pub user: User,
 }
 
+/// Reactions the app leaves on comments
+#[derive(Debug, Clone, Copy)]
+pub enum Reaction {
+    /// The app is working on it
+    Eyes,
+    /// The app refuses
+    ThumbsDown,
+}
+
 /// An installation of a GitHub App
 #[derive(Debug, Deserialize)]
 pub struct Installation {
@@ -67,6 +209,12 @@ pub struct Installation {
     pub account: Account,
 }
 
+/// The installation a webhook was delivered for
+#[derive(Debug, Deserialize)]
+pub struct SimpleInstallation {
+    pub id: i64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Account {
     pub login: String,
@@ -99,3 +247,68 @@ pub struct InstallationRepository {
     pub full_name: String,
     pub private: bool,
 }
+
+/// https://docs.github.com/en/rest/checks/runs?apiVersion=2022-11-28#create-a-check-run
+#[derive(Serialize)]
+pub struct NewCheckRun<'a> {
+    pub name: &'a str,
+    pub head_sha: &'a str,
+    /// Page of the app that has the details of the check
+    pub details_url: &'a str,
+    /// ID of the check in the app
+    pub external_id: &'a str,
+    #[serde(flatten)]
+    pub state: &'a CheckRunState,
+}
+
+/// The state a check run is set to
+/// https://docs.github.com/en/rest/checks/runs?apiVersion=2022-11-28#update-a-check-run
+#[derive(Debug, Serialize)]
+pub struct CheckRunState {
+    pub status: CheckRunStatus,
+    /// Required once the check run is completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<CheckRunConclusion>,
+    pub output: CheckRunOutput,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunStatus {
+    Queued,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunConclusion {
+    Success,
+    Failure,
+    Cancelled,
+    /// Requires the details URL, which links to what the user has to do
+    ActionRequired,
+}
+
+/// Shown on the checks tab of pull requests, the summary supports Markdown
+#[derive(Debug, Serialize)]
+pub struct CheckRunOutput {
+    pub title: String,
+    pub summary: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CheckRunId {
+    pub id: i64,
+}
+
+/// https://docs.github.com/en/rest/git/refs?apiVersion=2022-11-28#get-a-reference
+#[derive(Deserialize)]
+pub(crate) struct GitRef {
+    pub object: GitObject,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GitObject {
+    pub sha: String,
+}
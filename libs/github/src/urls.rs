@@ -1,10 +1,91 @@
 use once_cell::sync::Lazy;
 use url::Url;
 
-pub static OAUTH_AUTHORIZE_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://github.com/login/oauth/authorize").unwrap());
+use config::Config;
 
-pub static OAUTH_ACCESS_TOKEN_URL: Lazy<Url> =
-    Lazy::new(|| Url::parse("https://github.com/login/oauth/access_token").unwrap());
+static WEB_URL: Lazy<Url> = Lazy::new(|| Url::parse("https://github.com/").unwrap());
 
-pub static REST_API_URL: Lazy<Url> = Lazy::new(|| Url::parse("https://api.github.com").unwrap());
+static REST_API_URL: Lazy<Url> = Lazy::new(|| Url::parse("https://api.github.com/").unwrap());
+
+/// Base URLs of the GitHub instance
+///
+/// These default to github.com and can be overridden in the config, e.g. to run against a
+/// GitHub Enterprise Server or against local stand-in servers in tests.
+#[derive(Clone, Debug)]
+pub struct GitHubUrls {
+    /// Base URL of the web interface
+    web: Url,
+    /// Base URL of the REST API
+    rest_api: Url,
+    graphql: Url,
+    /// Base URL of the OAuth endpoints
+    oauth: Url,
+    /// Base URL of the git smart HTTP remotes
+    git: Url,
+}
+
+impl GitHubUrls {
+    pub fn new(config: &Config) -> Self {
+        let web = config.github_web_url.clone().unwrap_or_else(|| WEB_URL.clone());
+        let web = with_trailing_slash(web);
+        let rest_api = config.github_api_url.clone().unwrap_or_else(|| REST_API_URL.clone());
+        let rest_api = with_trailing_slash(rest_api);
+        let graphql =
+            config.github_graphql_url.clone().unwrap_or_else(|| rest_api.join("graphql").unwrap());
+        let oauth = config.github_oauth_url.clone().map(with_trailing_slash);
+        let git = config.github_git_url.clone().map(with_trailing_slash);
+        Self {
+            oauth: oauth.unwrap_or_else(|| web.clone()),
+            git: git.unwrap_or_else(|| web.clone()),
+            web,
+            rest_api,
+            graphql,
+        }
+    }
+
+    pub fn oauth_authorize(&self) -> Url {
+        self.oauth.join("login/oauth/authorize").unwrap()
+    }
+
+    pub fn oauth_access_token(&self) -> Url {
+        self.oauth.join("login/oauth/access_token").unwrap()
+    }
+
+    /// URL of a REST API endpoint, `path` is relative to the API root
+    pub fn rest(&self, path: &str) -> Url {
+        self.rest_api.join(path).unwrap()
+    }
+
+    pub fn graphql(&self) -> Url {
+        self.graphql.clone()
+    }
+
+    /// URL of a page of the web interface, `path` is relative to the root
+    pub fn web(&self, path: &str) -> Url {
+        self.web.join(path).unwrap()
+    }
+
+    /// URL of the web page of a repository
+    pub fn repo(&self, full_name: &str) -> Url {
+        self.web(full_name)
+    }
+
+    /// URL of the web page of an issue or pull request
+    pub fn issue(&self, full_name: &str, number: i64) -> Url {
+        self.web(&format!("{}/issues/{}", full_name, number))
+    }
+
+    /// Git smart HTTP remote of a repository
+    pub fn git_remote(&self, full_name: &str) -> Url {
+        self.git.join(full_name).unwrap()
+    }
+}
+
+/// Relative URLs are joined onto the last path segment, so base URLs need a trailing slash.
+fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url
+}
@@ -24,6 +24,7 @@ impl S3 {
             .region(aws_sdk_s3::config::Region::new(config.region.clone()))
             .behavior_version(aws_sdk_s3::config::BehaviorVersion::v2024_03_28())
             .endpoint_url(config.endpoint.as_str())
+            .force_path_style(config.force_path_style)
             .credentials_provider(aws_sdk_s3::config::Credentials::new(
                 config.access_key,
                 config.secret_key,
@@ -43,18 +44,46 @@ impl S3 {
         task_id: &Uuid,
         log_contents: String,
     ) -> Result<(), aws_sdk_s3::Error> {
-        self.put(&format!("tasks/{}/task.log", task_id), log_contents.into_bytes()).await
+        let key = format!("tasks/{}/task.log", task_id);
+        self.put(&key, "text/plain", log_contents.into_bytes()).await
     }
 
     pub async fn log_for_task(&self, task_id: &Uuid) -> Result<Vec<u8>, GetObjectError> {
         self.get(&format!("tasks/{}/task.log", task_id)).await
     }
 
-    async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), aws_sdk_s3::Error> {
+    /// Store a file the agent produced for the task under the given name
+    ///
+    /// The name must be a valid object key segment, an artifact with the same name is replaced.
+    pub async fn upload_artifact_for_task(
+        &self,
+        task_id: &Uuid,
+        name: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<(), aws_sdk_s3::Error> {
+        self.put(&format!("tasks/{}/artifacts/{}", task_id, name), content_type, data).await
+    }
+
+    pub async fn artifact_for_task(
+        &self,
+        task_id: &Uuid,
+        name: &str,
+    ) -> Result<Vec<u8>, GetObjectError> {
+        self.get(&format!("tasks/{}/artifacts/{}", task_id, name)).await
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        content_type: &str,
+        data: Vec<u8>,
+    ) -> Result<(), aws_sdk_s3::Error> {
         self.client
             .put_object()
             .bucket(self.bucket.clone())
             .key(format!("{}/{}", self.prefix, key))
+            .content_type(content_type)
             .body(data.into())
             .send()
             .await?;
@@ -93,6 +122,8 @@ pub struct S3Config {
     pub prefix: String,
     pub access_key: String,
     pub secret_key: String,
+    /// Address the bucket in the path instead of the host name
+    pub force_path_style: bool,
 }
 
 impl From<&Config> for S3Config {
@@ -104,6 +135,7 @@ impl From<&Config> for S3Config {
             prefix: config.s3_prefix.clone(),
             access_key: config.s3_access_key.clone(),
             secret_key: config.s3_secret_key.clone(),
+            force_path_style: config.s3_force_path_style,
         }
     }
 }
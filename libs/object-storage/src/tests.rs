@@ -29,6 +29,7 @@ fn s3_config() -> S3Config {
             .expect("Failed to parse TEST_S3_ENDPOINT"),
         access_key: env::var("TEST_S3_ACCESS_KEY").expect("TEST_S3_ACCESS_KEY must be set"),
         secret_key: env::var("TEST_S3_SECRET_KEY").expect("TEST_S3_SECRET_KEY must be set"),
+        force_path_style: env::var("TEST_S3_FORCE_PATH_STYLE").is_ok_and(|value| value == "true"),
     }
 }
 
@@ -63,3 +64,27 @@ async fn test_get_non_existent_log() {
 
     assert!(matches!(err, GetObjectError::NotFound));
 }
+
+#[tokio::test]
+async fn test_put_then_get_artifact() {
+    let config = s3_config();
+
+    let s3 = S3::new(config).expect("Failed to create S3 client");
+
+    let task_id = "01954d93-b2e4-7c41-9d3a-5f0c7e8a1b22".parse::<Uuid>().unwrap();
+
+    let report = b"<testsuite tests=\"1\" failures=\"0\"/>".to_vec();
+
+    s3.upload_artifact_for_task(&task_id, "junit.xml", "application/xml", report.clone())
+        .await
+        .expect("Failed to upload artifact");
+
+    let retrieved =
+        s3.artifact_for_task(&task_id, "junit.xml").await.expect("Failed to retrieve artifact");
+
+    assert_eq!(retrieved, report);
+
+    s3.delete(&format!("tasks/{}/artifacts/junit.xml", task_id))
+        .await
+        .expect("Failed to delete artifact");
+}
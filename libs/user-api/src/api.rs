@@ -7,14 +7,24 @@ pub struct UserInfo {
     pub email_domain: String,
     pub active: bool,
     pub on_waitlist: bool,
+    /// Base URL of the GitHub web interface
+    pub github_url: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Repo {
     pub id: String,
     pub name: String,
+    /// Web page of the repository on GitHub
+    pub url: String,
     pub active: bool,
     pub role: UserRole,
+    /// Label that queues a task when it is added to an issue
+    pub trigger_label: Option<String>,
+    /// ID of the installation the repository belongs to
+    pub installation_id: String,
+    /// Name of the LLM provider the tasks use, the one of the installation if not set
+    pub llm_provider: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -35,6 +45,47 @@ pub struct AddRepoUserRequest {
     pub github_login: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SetTriggerLabelRequest {
+    /// No label disables the trigger
+    pub trigger_label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SetLlmProviderRequest {
+    /// No provider falls back to the installation, or to OpenRouter
+    pub llm_provider: Option<String>,
+}
+
+/// An installation the user administers
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct InstallationInfo {
+    pub id: String,
+    /// GitHub account the app is installed on
+    pub name: String,
+    /// The key itself is never sent back
+    pub openrouter_key_set: bool,
+    pub llm_key_precedence: LlmKeyPrecedence,
+    pub llm_provider: Option<String>,
+}
+
+/// Which OpenRouter key pays for the tasks of an installation when both are set
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LlmKeyPrecedence {
+    InstallationFirst,
+    UserFirst,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SetOpenRouterKeyRequest {
+    pub openrouter_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SetLlmKeyPrecedenceRequest {
+    pub llm_key_precedence: LlmKeyPrecedence,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct TaskInfo {
     pub id: String,
@@ -43,13 +94,21 @@ pub struct TaskInfo {
     pub status: TaskStatus,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TaskDetails {
     pub id: String,
     pub repo_name: String,
     pub issue_number: i64,
     pub status: TaskStatus,
+    /// Web page of the issue on GitHub
+    pub issue_url: String,
     pub interactions: Vec<LLMInteraction>,
+    /// Progress the agent reported, oldest first
+    pub events: Vec<TaskEvent>,
+    /// What the LLM requests of the task used and cost so far
+    pub llm_usage: LLMUsage,
+    /// ID of the task whose recorded LLM interactions this task replays
+    pub replay_of: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -57,6 +116,48 @@ pub struct LLMInteraction {
     pub id: String,
     pub request: Option<Value>,
     pub response: Option<Value>,
+    /// Whose key the request was authenticated with
+    pub key_source: Option<LlmKeySource>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LlmKeySource {
+    Installation,
+    User,
+    Provider,
+}
+
+/// Tokens and cost of the LLM interactions of a task added up
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct LLMUsage {
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    /// In USD
+    pub cost: f64,
+}
+
+/// Progress the agent reported while working on a task
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TaskEvent {
+    pub id: String,
+    pub step: String,
+    pub message: Option<String>,
+    /// Between 0 and 100
+    pub percent: Option<i16>,
+    pub milestone: bool,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+}
+
+/// A file the agent uploaded for a task
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TaskArtifact {
+    pub name: String,
+    pub content_type: String,
+    /// In bytes
+    pub size: i64,
+    /// RFC 3339 timestamp of the latest upload
+    pub uploaded_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -65,6 +166,9 @@ pub enum TaskStatus {
     Running,
     Completed,
     Failed,
+    Cancelled,
+    /// The agent asked a question on the issue and continues once it is answered
+    WaitingForInput,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -77,3 +181,28 @@ pub struct TaskPollResponse {
 pub struct OpenRouterStatus {
     pub connected: bool,
 }
+
+/// A webhook delivery from GitHub
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub id: String,
+    /// Value of the X-GitHub-Delivery header
+    pub delivery_id: String,
+    pub event: String,
+    pub action: Option<String>,
+    pub status: WebhookDeliveryStatus,
+    pub error: Option<String>,
+    /// Number of times handling the delivery was started
+    pub attempts: i32,
+    /// RFC 3339 timestamp
+    pub received_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    Queued,
+    Processing,
+    Processed,
+    Failed,
+    Dead,
+}
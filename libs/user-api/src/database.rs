@@ -7,6 +7,8 @@ impl From<database::TaskStatus> for TaskStatus {
             database::TaskStatus::Running => TaskStatus::Running,
             database::TaskStatus::Completed => TaskStatus::Completed,
             database::TaskStatus::Failed => TaskStatus::Failed,
+            database::TaskStatus::Cancelled => TaskStatus::Cancelled,
+            database::TaskStatus::WaitingForInput => TaskStatus::WaitingForInput,
         }
     }
 }
@@ -28,6 +30,69 @@ impl From<database::LLMInteraction> for LLMInteraction {
             id: value.id.to_string(),
             request: value.request,
             response: value.response,
+            key_source: value.key_source.map(Into::into),
+        }
+    }
+}
+
+impl From<database::LlmKeySource> for LlmKeySource {
+    fn from(value: database::LlmKeySource) -> Self {
+        match value {
+            database::LlmKeySource::Installation => LlmKeySource::Installation,
+            database::LlmKeySource::User => LlmKeySource::User,
+            database::LlmKeySource::Provider => LlmKeySource::Provider,
+        }
+    }
+}
+
+impl From<database::LlmKeyPrecedence> for LlmKeyPrecedence {
+    fn from(value: database::LlmKeyPrecedence) -> Self {
+        match value {
+            database::LlmKeyPrecedence::InstallationFirst => LlmKeyPrecedence::InstallationFirst,
+            database::LlmKeyPrecedence::UserFirst => LlmKeyPrecedence::UserFirst,
+        }
+    }
+}
+
+impl From<LlmKeyPrecedence> for database::LlmKeyPrecedence {
+    fn from(value: LlmKeyPrecedence) -> Self {
+        match value {
+            LlmKeyPrecedence::InstallationFirst => database::LlmKeyPrecedence::InstallationFirst,
+            LlmKeyPrecedence::UserFirst => database::LlmKeyPrecedence::UserFirst,
+        }
+    }
+}
+
+impl From<database::LLMUsage> for LLMUsage {
+    fn from(value: database::LLMUsage) -> Self {
+        LLMUsage {
+            prompt_tokens: value.prompt_tokens,
+            completion_tokens: value.completion_tokens,
+            cost: value.cost,
+        }
+    }
+}
+
+impl From<database::TaskEvent> for TaskEvent {
+    fn from(value: database::TaskEvent) -> Self {
+        TaskEvent {
+            id: value.id.to_string(),
+            step: value.step,
+            message: value.message,
+            percent: value.percent,
+            milestone: value.milestone,
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
+
+impl From<database::TaskArtifact> for TaskArtifact {
+    fn from(value: database::TaskArtifact) -> Self {
+        TaskArtifact {
+            name: value.name,
+            content_type: value.content_type,
+            size: value.size,
+            uploaded_at: value.updated_at.to_rfc3339(),
         }
     }
 }
@@ -40,3 +105,30 @@ impl From<database::UserRole> for UserRole {
         }
     }
 }
+
+impl From<database::WebhookDeliveryStatus> for WebhookDeliveryStatus {
+    fn from(value: database::WebhookDeliveryStatus) -> Self {
+        match value {
+            database::WebhookDeliveryStatus::Queued => WebhookDeliveryStatus::Queued,
+            database::WebhookDeliveryStatus::Processing => WebhookDeliveryStatus::Processing,
+            database::WebhookDeliveryStatus::Processed => WebhookDeliveryStatus::Processed,
+            database::WebhookDeliveryStatus::Failed => WebhookDeliveryStatus::Failed,
+            database::WebhookDeliveryStatus::Dead => WebhookDeliveryStatus::Dead,
+        }
+    }
+}
+
+impl From<database::WebhookDelivery> for WebhookDelivery {
+    fn from(value: database::WebhookDelivery) -> Self {
+        WebhookDelivery {
+            id: value.id.to_string(),
+            delivery_id: value.github_delivery_id,
+            event: value.event,
+            action: value.action,
+            status: value.status.into(),
+            error: value.error,
+            attempts: value.attempts,
+            received_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
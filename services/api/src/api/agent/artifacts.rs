@@ -0,0 +1,91 @@
+//! Files the agent produces besides its commits, e.g. test reports, coverage and screenshots
+
+use actix_web::{put, web, HttpMessage, HttpRequest, HttpResponse};
+
+use auth::AgentSessionId;
+use database::{Database, NewTaskArtifact};
+use object_storage::S3;
+
+use super::task::except_task_running;
+
+mod tests;
+
+/// The largest artifact the agent can upload, in bytes
+pub const MAX_ARTIFACT_SIZE: usize = 25 * 1024 * 1024;
+/// The most bytes all artifacts of a task can take together
+const MAX_TASK_ARTIFACTS_SIZE: i64 = 100 * 1024 * 1024;
+/// The most artifacts a task can have
+const MAX_TASK_ARTIFACTS: usize = 50;
+const MAX_NAME_LEN: usize = 128;
+
+/// Upload an artifact, replacing the one with the same name
+///
+/// The content type of the request is served with the artifact, it defaults to
+/// `application/octet-stream`.
+#[put("/task/artifacts/{name}")]
+pub async fn upload_artifact(
+    agent: AgentSessionId,
+    db: web::Data<Database>,
+    s3: web::Data<S3>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let name = path.into_inner();
+    if let Err(reason) = validate_name(&name) {
+        return HttpResponse::BadRequest().body(reason);
+    }
+    let content_type = match req.mime_type() {
+        Ok(Some(mime)) => mime.to_string(),
+        Ok(None) => "application/octet-stream".to_owned(),
+        Err(_) => return HttpResponse::BadRequest().body("Invalid content type"),
+    };
+
+    let mut conn = db.conn().await;
+
+    if let Err(err) = except_task_running(&mut conn, &agent).await {
+        return err;
+    }
+
+    let task_id = agent.task_id;
+    let size = body.len() as i64;
+
+    let artifacts = conn.task_artifacts(&task_id).await;
+    let others: Vec<_> = artifacts.iter().filter(|artifact| artifact.name != name).collect();
+    if others.len() >= MAX_TASK_ARTIFACTS {
+        return HttpResponse::PayloadTooLarge()
+            .body(format!("A task can have at most {MAX_TASK_ARTIFACTS} artifacts"));
+    }
+    let total_size = others.iter().map(|artifact| artifact.size).sum::<i64>() + size;
+    if total_size > MAX_TASK_ARTIFACTS_SIZE {
+        return HttpResponse::PayloadTooLarge().body(format!(
+            "The artifacts of a task can take at most {MAX_TASK_ARTIFACTS_SIZE} bytes"
+        ));
+    }
+
+    if let Err(err) =
+        s3.upload_artifact_for_task(&task_id, &name, &content_type, body.to_vec()).await
+    {
+        log::error!("Failed to upload artifact {name} of task {task_id}: {err:?}");
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    conn.upsert_task_artifact(NewTaskArtifact { task_id, name, content_type, size }).await;
+
+    HttpResponse::Ok().finish()
+}
+
+/// Names are single object key segments that are safe in URLs and file names
+fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err("Artifact names must have between 1 and 128 characters");
+    }
+    if name.starts_with('.') {
+        return Err("Artifact names must not start with a dot");
+    }
+    let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_');
+    if !name.chars().all(allowed) {
+        return Err("Artifact names may only contain ASCII letters, digits, '.', '-' and '_'");
+    }
+    Ok(())
+}
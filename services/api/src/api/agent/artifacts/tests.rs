@@ -0,0 +1,21 @@
+#![cfg(test)]
+
+use super::validate_name;
+
+#[test]
+fn test_valid_names() {
+    assert_eq!(validate_name("junit.xml"), Ok(()));
+    assert_eq!(validate_name("coverage-report_2.tar.gz"), Ok(()));
+    assert_eq!(validate_name(&"a".repeat(128)), Ok(()));
+}
+
+#[test]
+fn test_invalid_names() {
+    assert!(validate_name("").is_err());
+    assert!(validate_name(&"a".repeat(129)).is_err());
+    assert!(validate_name(".env").is_err());
+    assert!(validate_name("..").is_err());
+    assert!(validate_name("reports/junit.xml").is_err());
+    assert!(validate_name("screen shot.png").is_err());
+    assert!(validate_name("bericht-ü.txt").is_err());
+}
@@ -0,0 +1,163 @@
+//! The task description the agent works from
+//!
+//! Long issues and threads are truncated so the description fits the context of the model. The
+//! first comment usually refines the issue and the latest ones have the most recent
+//! clarifications, so comments in the middle of a thread are the first to be left out.
+
+use database::{TaskQuestion, TaskReviewComment};
+use github::types::{IssueContext, LinkedItemKind, LinkedItemState};
+
+mod tests;
+
+/// The most bytes of the issue body that are kept
+const MAX_BODY_LEN: usize = 20_000;
+/// The most bytes of a single comment that are kept
+const MAX_COMMENT_LEN: usize = 5_000;
+/// The most bytes of all kept comments together
+const MAX_COMMENTS_LEN: usize = 40_000;
+
+/// The issue with its comments and linked items, the command that asked for the task, the
+/// answers to the questions of the agent and the review feedback for follow-up tasks
+pub fn describe_task(
+    issue: &IssueContext,
+    command_comment: Option<&str>,
+    questions: &[TaskQuestion],
+    review_comments: &[TaskReviewComment],
+) -> String {
+    let mut description = format!("# {} (#{})\n\n", issue.title, issue.number);
+    if let Some(author) = &issue.author {
+        description.push_str(&format!("Opened by @{}.", author));
+    }
+    if !issue.labels.is_empty() {
+        let labels: Vec<_> = issue.labels.iter().map(|label| format!("`{}`", label)).collect();
+        description.push_str(&format!(" Labels: {}.", labels.join(", ")));
+    }
+    description.push_str("\n\n");
+    description.push_str(&truncate(&issue.body, MAX_BODY_LEN));
+    description.push('\n');
+
+    if !issue.linked.is_empty() {
+        description.push_str("\n## Linked issues and pull requests\n\n");
+        for item in &issue.linked {
+            let kind = match item.kind {
+                LinkedItemKind::Issue => "Issue",
+                LinkedItemKind::PullRequest => "Pull request",
+            };
+            let state = match item.state {
+                LinkedItemState::Open => "open",
+                LinkedItemState::Closed => "closed",
+                LinkedItemState::Merged => "merged",
+                LinkedItemState::Unknown => "unknown",
+            };
+            description.push_str(&format!(
+                "- {} {}#{} ({}): {}\n",
+                kind, item.repository, item.number, state, item.title
+            ));
+        }
+    }
+
+    if issue.total_comments > 0 {
+        description.push_str("\n## Comments\n");
+        describe_comments(&mut description, issue);
+    }
+
+    if let Some(command_comment) = command_comment {
+        description.push_str("\n## Task request\n\nThe task was requested with this comment.\n\n");
+        description.push_str(&truncate(command_comment, MAX_COMMENT_LEN));
+        description.push('\n');
+    }
+
+    let answered: Vec<_> = (questions.iter())
+        .filter_map(|question| Some((question, question.answer.as_deref()?)))
+        .collect();
+    if !answered.is_empty() {
+        description.push_str(
+            "\n## Clarifications\n\nYou asked these questions on the issue earlier and got the \
+             following answers.\n",
+        );
+        for (question, answer) in answered {
+            let author = question.answer_author_login.as_deref().unwrap_or("ghost");
+            description.push_str(&format!(
+                "\n### Question\n\n{}\n\n### Answer by @{}\n\n{}\n",
+                question.question,
+                author,
+                truncate(answer, MAX_COMMENT_LEN)
+            ));
+        }
+    }
+
+    if !review_comments.is_empty() {
+        describe_review_comments(&mut description, review_comments);
+    }
+    description
+}
+
+/// Keep the first comment and as many of the latest ones as fit, noting how many were left out
+fn describe_comments(description: &mut String, issue: &IssueContext) {
+    let comments: Vec<_> = (issue.comments.iter())
+        .map(|comment| {
+            format!(
+                "\n### @{} on {}\n\n{}\n",
+                comment.author.as_deref().unwrap_or("ghost"),
+                comment.created_at.format("%Y-%m-%d %H:%M UTC"),
+                truncate(&comment.body, MAX_COMMENT_LEN)
+            )
+        })
+        .collect();
+    let Some((first, rest)) = comments.split_first() else {
+        description.push_str(&format!("\n_{} comments omitted_\n", issue.total_comments));
+        return;
+    };
+
+    let mut budget = MAX_COMMENTS_LEN.saturating_sub(first.len());
+    let latest_len = (rest.iter().rev())
+        .take_while(|comment| match budget.checked_sub(comment.len()) {
+            Some(remaining) => {
+                budget = remaining;
+                true
+            }
+            None => false,
+        })
+        .count();
+    let latest = &rest[rest.len() - latest_len..];
+
+    description.push_str(first);
+    // Comments GitHub didn't return count as omitted too
+    let omitted = issue.total_comments - 1 - latest_len as i64;
+    if omitted > 0 {
+        description.push_str(&format!("\n_{} comments omitted_\n", omitted));
+    }
+    for comment in latest {
+        description.push_str(comment);
+    }
+}
+
+fn describe_review_comments(description: &mut String, review_comments: &[TaskReviewComment]) {
+    description.push_str(
+        "\n## Review feedback\n\nAddress the following review comments on the pull request.\n",
+    );
+    for comment in review_comments {
+        let location = match (&comment.path, comment.line) {
+            (Some(path), Some(line)) => format!(" on `{}` line {}", path, line),
+            (Some(path), None) => format!(" on `{}`", path),
+            _ => String::new(),
+        };
+        description
+            .push_str(&format!("\n### Comment by @{}{}\n\n", comment.author_login, location));
+        if let Some(diff_hunk) = &comment.diff_hunk {
+            let diff_hunk = truncate(diff_hunk, MAX_COMMENT_LEN);
+            description.push_str(&format!("```diff\n{}\n```\n\n", diff_hunk));
+        }
+        description.push_str(&truncate(&comment.body, MAX_COMMENT_LEN));
+        description.push('\n');
+    }
+}
+
+/// Cut the text to at most `max_len` bytes, on a character boundary, and mark the cut
+fn truncate(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_owned();
+    }
+    let end = (0..=max_len).rev().find(|&end| text.is_char_boundary(end)).unwrap_or(0);
+    format!("{}\n\n_[truncated]_", &text[..end])
+}
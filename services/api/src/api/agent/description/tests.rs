@@ -0,0 +1,153 @@
+#![cfg(test)]
+
+use chrono::{TimeZone, Utc};
+use database::{TaskQuestion, TaskReviewComment};
+use github::types::{IssueComment, IssueContext, LinkedItem, LinkedItemKind, LinkedItemState};
+use uuid::Uuid;
+
+use super::{describe_task, truncate, MAX_COMMENTS_LEN, MAX_COMMENT_LEN};
+
+fn issue(comments: Vec<IssueComment>, total_comments: i64) -> IssueContext {
+    IssueContext {
+        number: 7,
+        title: "Add a file".to_owned(),
+        body: "Add a file to the repository.".to_owned(),
+        author: Some("octocat".to_owned()),
+        labels: vec!["minion".to_owned(), "good first issue".to_owned()],
+        comments,
+        total_comments,
+        linked: vec![],
+    }
+}
+
+fn comment(body: &str) -> IssueComment {
+    IssueComment {
+        author: Some("hubot".to_owned()),
+        body: body.to_owned(),
+        created_at: Utc.with_ymd_and_hms(2025, 3, 1, 12, 30, 0).unwrap(),
+    }
+}
+
+#[test]
+fn test_issue() {
+    let mut issue = issue(vec![comment("Put it in `docs/`.")], 1);
+    issue.linked.push(LinkedItem {
+        kind: LinkedItemKind::PullRequest,
+        repository: "octo/repo".to_owned(),
+        number: 8,
+        title: "Add a file to docs".to_owned(),
+        state: LinkedItemState::Closed,
+    });
+
+    let description = describe_task(&issue, Some("@minion solve --base dev"), &[], &[]);
+
+    assert_eq!(
+        description,
+        "# Add a file (#7)\n\n\
+         Opened by @octocat. Labels: `minion`, `good first issue`.\n\n\
+         Add a file to the repository.\n\
+         \n## Linked issues and pull requests\n\n\
+         - Pull request octo/repo#8 (closed): Add a file to docs\n\
+         \n## Comments\n\
+         \n### @hubot on 2025-03-01 12:30 UTC\n\nPut it in `docs/`.\n\
+         \n## Task request\n\nThe task was requested with this comment.\n\n\
+         @minion solve --base dev\n"
+    );
+}
+
+#[test]
+fn test_long_thread_keeps_first_and_latest_comments() {
+    let long_body = "x".repeat(MAX_COMMENT_LEN);
+    let count = 2 * MAX_COMMENTS_LEN / MAX_COMMENT_LEN;
+    let mut comments = vec![comment("first")];
+    comments.extend((0..count).map(|_| comment(&long_body)));
+    comments.push(comment("latest"));
+    let total_comments = comments.len() as i64;
+
+    let description = describe_task(&issue(comments, total_comments), None, &[], &[]);
+
+    let first = description.find("\n\nfirst\n").unwrap();
+    let omitted = description.find("_ comments omitted_").unwrap();
+    let latest = description.find("\n\nlatest\n").unwrap();
+    assert!(first < omitted && omitted < latest);
+    assert!(description.len() < MAX_COMMENTS_LEN + 1_000);
+}
+
+#[test]
+fn test_comments_not_returned_are_omitted() {
+    let description = describe_task(&issue(vec![comment("first")], 3), None, &[], &[]);
+
+    assert!(description.ends_with("\n\nfirst\n\n_2 comments omitted_\n"));
+}
+
+#[test]
+fn test_comments_beyond_the_fetched_ones_are_omitted() {
+    let total_comments = 1_234;
+    let mut comments = vec![comment("first")];
+    let latest = total_comments - (github::MAX_ISSUE_COMMENTS - 1);
+    comments.extend((latest..total_comments).map(|index| comment(&format!("comment {index}"))));
+
+    let description = describe_task(&issue(comments, total_comments as i64), None, &[], &[]);
+
+    let first = description.find("\n\nfirst\n").unwrap();
+    let omitted = description.find(&format!("_{} comments omitted_", latest - 1)).unwrap();
+    let latest = description.find(&format!("comment {}\n", total_comments - 1)).unwrap();
+    assert!(first < omitted && omitted < latest);
+}
+
+#[test]
+fn test_answered_questions() {
+    let question = |question: &str, answer: Option<&str>| TaskQuestion {
+        id: Uuid::nil(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        task_id: Uuid::nil(),
+        question: question.to_owned(),
+        github_comment_id: "IC_question".to_owned(),
+        answer: answer.map(ToOwned::to_owned),
+        answer_author_login: answer.map(|_| "octocat".to_owned()),
+    };
+    let questions = [
+        question("Which directory?", Some("The docs directory.")),
+        question("Which format?", None),
+    ];
+
+    let description = describe_task(&issue(vec![], 0), None, &questions, &[]);
+
+    assert!(description.ends_with(
+        "\n## Clarifications\n\nYou asked these questions on the issue earlier and got the \
+         following answers.\n\
+         \n### Question\n\nWhich directory?\n\n### Answer by @octocat\n\nThe docs directory.\n"
+    ));
+    assert!(!description.contains("Which format?"));
+}
+
+#[test]
+fn test_long_review_comments_are_truncated() {
+    let long_text = "x".repeat(2 * MAX_COMMENT_LEN);
+    let review_comment = TaskReviewComment {
+        id: Uuid::nil(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        task_id: Uuid::nil(),
+        github_comment_id: "PRRC_1".to_owned(),
+        author_login: "octocat".to_owned(),
+        body: long_text.clone(),
+        path: Some("src/lib.rs".to_owned()),
+        line: Some(1),
+        diff_hunk: Some(long_text),
+    };
+
+    let description = describe_task(&issue(vec![], 0), None, &[], &[review_comment]);
+
+    assert_eq!(description.matches("_[truncated]_").count(), 2);
+    assert!(description.len() < 2 * MAX_COMMENT_LEN + 1_000);
+}
+
+#[test]
+fn test_truncate() {
+    assert_eq!(truncate("short", 10), "short");
+    assert_eq!(truncate("abcdef", 3), "abc\n\n_[truncated]_");
+    // Multi-byte characters are not split
+    assert_eq!(truncate("aé", 2), "a\n\n_[truncated]_");
+}
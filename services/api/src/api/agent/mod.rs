@@ -1,9 +1,19 @@
-use actix_web::Scope;
+use actix_web::{web, Scope};
 
+pub mod artifacts;
+mod description;
 pub mod task;
 
+use artifacts::*;
 use task::*;
 
 pub fn scope() -> Scope {
-    Scope::new("/agent").service(task_info).service(task_complete).service(task_fail)
+    Scope::new("/agent")
+        .app_data(web::PayloadConfig::new(MAX_ARTIFACT_SIZE))
+        .service(task_info)
+        .service(task_progress)
+        .service(task_ask)
+        .service(task_complete)
+        .service(task_fail)
+        .service(upload_artifact)
 }
@@ -1,11 +1,27 @@
 use actix_web::{get, post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
 
 use agent_api::types::task::*;
 use auth::AgentSessionId;
 use config::Config;
-use database::Database;
+use database::{Database, NewTaskEvent, NewTaskQuestion, TaskEvent, DEFAULT_BASE_REF};
+use github::types::IssueContext;
 use github::GitHub;
 
+use super::description::describe_task;
+use crate::api::github_error_response;
+
+/// The task with the structured context of its issue
+///
+/// Agents that only know [`Task`] ignore the context and work from the description, which
+/// includes it.
+#[derive(Serialize)]
+struct TaskWithIssue {
+    #[serde(flatten)]
+    task: Task,
+    issue: IssueContext,
+}
+
 #[get("/task")]
 pub async fn task_info(
     config: web::Data<Config>,
@@ -15,32 +31,198 @@ pub async fn task_info(
 ) -> HttpResponse {
     let mut conn = db.conn().await;
 
+    if let Err(err) = except_task_running(&mut conn, &agent).await {
+        return err;
+    }
+
     let task_id = agent.task_id;
 
     let (task, _repo) = conn.get_task_and_repository(&task_id).await;
 
-    let jwt = github.github_app_jwt();
-    let access_token = github.installation_access_token(&jwt).await;
-    let github = github.with_access(&access_token.token);
-
-    let issue_info = github.issue_info(&task.github_issue_id).await;
+    let Some(installation_id) = conn.task_installation_github_id(&task_id).await else {
+        return HttpResponse::Gone().body("The app is no longer installed");
+    };
+    let issue = async {
+        let github = github.with_installation_access(installation_id).await?;
+        github.issue_context(&task.github_issue_id).await
+    };
+    let issue = match issue.await {
+        Ok(issue) => issue,
+        Err(err) => return github_error_response(err),
+    };
+    let review_comments = conn.task_review_comments(&task.id).await;
+    let questions = conn.task_questions(&task.id).await;
 
     let git_repo_url = config.web_base_url.join("/api/agent/git").unwrap();
-    // For now we assume that the task id is the branch name
-    let git_branch = task.id.to_string();
+    // Replays don't get a branch of their own, they start from the base branch
+    let git_branch = match task.replay_of_task_id {
+        Some(_) => task.base_ref.clone().unwrap_or_else(|| DEFAULT_BASE_REF.to_owned()),
+        None => task.branch_name(),
+    };
 
-    let response = Task {
+    let description =
+        describe_task(&issue, task.github_command_comment.as_deref(), &questions, &review_comments);
+    let task = Task {
         status: task.status.into(),
-        description: issue_info.body,
+        description,
         git_user_name: config.github_git_name.clone(),
         git_user_email: config.github_git_email.clone(),
         git_repo_url,
         git_branch,
     };
+    let response = TaskWithIssue { task, issue };
 
     HttpResponse::Ok().json(response)
 }
 
+/// Progress the agent reports while working on the task
+#[derive(Deserialize)]
+pub struct TaskProgress {
+    /// Name of the step the agent is working on
+    step: String,
+    message: Option<String>,
+    /// Between 0 and 100
+    percent: Option<i16>,
+    /// Milestones are mirrored into the status comment on GitHub
+    #[serde(default)]
+    milestone: bool,
+}
+
+#[post("/task/progress")]
+pub async fn task_progress(
+    config: web::Data<Config>,
+    agent: AgentSessionId,
+    db: web::Data<Database>,
+    github: web::Data<GitHub>,
+    body: web::Json<TaskProgress>,
+) -> HttpResponse {
+    let mut conn = db.conn().await;
+
+    if let Err(err) = except_task_running(&mut conn, &agent).await {
+        return err;
+    }
+
+    let TaskProgress { step, message, percent, milestone } = body.into_inner();
+    if percent.is_some_and(|percent| !(0..=100).contains(&percent)) {
+        return HttpResponse::BadRequest().body("Percent must be between 0 and 100");
+    }
+
+    let new_event = NewTaskEvent { task_id: agent.task_id, step, message, percent, milestone };
+    let event = conn.add_task_event(new_event).await;
+
+    if event.milestone {
+        mirror_milestone(&config, &mut conn, &github, &event).await;
+    }
+
+    HttpResponse::Ok().finish()
+}
+
+/// Show the milestone in the status comment of the task, failures are only logged
+async fn mirror_milestone(
+    config: &Config,
+    conn: &mut database::Conn<'_>,
+    github: &GitHub,
+    event: &TaskEvent,
+) {
+    let task = conn.get_task(&event.task_id).await;
+    // Tasks get their status comment once they start, replays never do
+    let Some(comment_id) = task.github_status_comment_id else {
+        return;
+    };
+    let Some(installation_id) = conn.task_installation_github_id(&task.id).await else {
+        return;
+    };
+
+    let task_url = config.web_base_url.join(&format!("/tasks/{}", task.id)).unwrap();
+    let mut body = format!("Working on the [task]({task_url}): {}", event.step);
+    if let Some(percent) = event.percent {
+        body.push_str(&format!(" ({percent}%)"));
+    }
+    if let Some(message) = &event.message {
+        body.push_str(&format!("\n\n{message}"));
+    }
+
+    let result = async {
+        let github = github.with_installation_access(installation_id).await?;
+        github.update_comment(&comment_id, &body).await
+    };
+    if let Err(err) = result.await {
+        eprintln!("Failed to update the status comment of task {}: {}", task.id, err);
+    }
+}
+
+/// A question the agent asks the author of the issue
+#[derive(Deserialize)]
+pub struct TaskQuestionRequest {
+    question: String,
+}
+
+/// Ask the author of the issue a question and wait for the answer
+///
+/// The question is posted on the issue and the task waits for input. The agent is expected to
+/// exit afterwards, the dispatcher stops it and tears down its virtual machine otherwise. Once the
+/// issue author replies, the task is queued again with the answer in its description.
+#[post("/task/ask")]
+pub async fn task_ask(
+    config: web::Data<Config>,
+    agent: AgentSessionId,
+    db: web::Data<Database>,
+    github: web::Data<GitHub>,
+    body: web::Json<TaskQuestionRequest>,
+) -> HttpResponse {
+    let mut conn = db.conn().await;
+
+    if let Err(err) = except_task_running(&mut conn, &agent).await {
+        return err;
+    }
+
+    let question = body.into_inner().question;
+    if question.trim().is_empty() {
+        return HttpResponse::BadRequest().body("The question is empty");
+    }
+
+    let task = conn.get_task(&agent.task_id).await;
+    // Replays don't report back to the issue, so nobody could answer
+    if task.replay_of_task_id.is_some() {
+        return HttpResponse::BadRequest().body("Replays can't ask questions");
+    }
+    let Some(installation_id) = conn.task_installation_github_id(&task.id).await else {
+        return HttpResponse::Gone().body("The app is no longer installed");
+    };
+
+    // Waits before the question is posted, so that a cancelled task doesn't ask
+    if conn.wait_for_input(&task.id).await.is_none() {
+        return HttpResponse::BadRequest().body("Task is not running");
+    }
+
+    let task_url = config.web_base_url.join(&format!("/tasks/{}", task.id)).unwrap();
+    let comment_id = async {
+        let github = github.with_installation_access(installation_id).await?;
+        let issue = github.issue_info(&task.github_issue_id).await?;
+        let intro = match issue.author {
+            Some(author) => format!("@{author}, the"),
+            None => "The".to_owned(),
+        };
+        let body = format!(
+            "{intro} [task]({task_url}) needs more information to continue:\n\n{question}\n\n\
+             Reply on this issue to answer, the task continues once you do."
+        );
+        github.add_comment(&task.github_issue_id, &body).await
+    };
+    let github_comment_id = match comment_id.await {
+        Ok(comment_id) => comment_id,
+        Err(err) => {
+            // Nobody was asked, so the agent keeps working on the task
+            conn.stop_waiting_for_input(&task.id).await;
+            return github_error_response(err);
+        }
+    };
+
+    conn.add_task_question(NewTaskQuestion { task_id: task.id, question, github_comment_id }).await;
+
+    HttpResponse::Ok().finish()
+}
+
 #[post("/task/complete")]
 pub async fn task_complete(
     agent: AgentSessionId,
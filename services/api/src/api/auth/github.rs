@@ -9,12 +9,11 @@ use thiserror::Error;
 use ::auth::{new_session_id, SessionId, TokenSigner, UserSessionId};
 use config::{AccessControl, Config};
 use database::Database;
-use github::urls::OAUTH_AUTHORIZE_URL;
 use github::GitHub;
 
 #[get("/auth")]
-async fn auth(config: web::Data<Config>) -> HttpResponse {
-    let mut location = OAUTH_AUTHORIZE_URL.clone();
+async fn auth(config: web::Data<Config>, github: web::Data<GitHub>) -> HttpResponse {
+    let mut location = github.urls().oauth_authorize();
     let redirect_uri = config.web_base_url.join("/auth-code").unwrap();
     location
         .query_pairs_mut()
@@ -39,11 +38,17 @@ async fn auth_code(
     let mut db_conn = db.conn().await;
 
     let now = Utc::now();
-    let gh_access = github.get_ref().user_access_token(&query.code).await;
+    let github_user = async {
+        let gh_access = github.get_ref().user_access_token(&query.code).await?;
+        let github = github.get_ref().with_access(&gh_access.access_token);
+        let gh_user = github.viewer_info().await?;
+        let gh_email = github.user_email().await?;
+        Ok::<_, github::Error>((gh_access, gh_user, gh_email))
+    };
+    let (gh_access, gh_user, gh_email) = github_user
+        .await
+        .map_err(|err| AuthError::RefreshAuthorizerDenied(AuthCodeError::GitHub(err).into()))?;
     let gh_token_expires_at = now + Duration::seconds(gh_access.expires_in);
-    let github = github.get_ref().with_access(&gh_access.access_token);
-    let gh_user = github.viewer_info().await;
-    let gh_email = github.user_email().await;
 
     match config.access_control {
         AccessControl::Allowlist => {
@@ -93,14 +98,20 @@ async fn auth_code(
 pub enum AuthCodeError {
     #[error("Access is restricted by allowlist")]
     RestrictedByAllowlist,
+    #[error("Failed to sign in with GitHub: {0}")]
+    GitHub(github::Error),
 }
 
 impl ResponseError for AuthCodeError {
     fn error_response(&self) -> HttpResponse {
-        match *self {
+        match self {
             AuthCodeError::RestrictedByAllowlist => {
                 HttpResponse::Forbidden().body("Access is restricted")
             }
+            AuthCodeError::GitHub(_) => {
+                log::error!("{self}");
+                HttpResponse::BadGateway().body("Failed to sign in with GitHub")
+            }
         }
     }
 }
@@ -0,0 +1,46 @@
+//! Limits on what the agents spend on LLM requests
+//!
+//! The cost of a request is only known once it was answered, so a budget is checked before each
+//! request against what was spent so far and the request that crosses it still goes through.
+//! OpenRouter reports the cost of a request, for other providers it is computed from the token
+//! prices configured for them.
+
+use chrono::{DateTime, Utc};
+
+use config::LlmBudget;
+use database::{Conn, Task};
+
+/// Why the next request of the task is rejected, `None` if it is within all budgets
+pub async fn exceeded_budget(
+    conn: &mut Conn<'_>,
+    budget: &LlmBudget,
+    task: &Task,
+) -> Option<String> {
+    if let Some(limit) = budget.task {
+        let spent = conn.task_llm_usage(&task.id).await.cost;
+        if spent >= limit {
+            return Some(exceeded("this task", spent, limit));
+        }
+    }
+    if let Some(limit) = budget.user_daily {
+        let spent = conn.user_llm_cost_since(&task.created_by_id, start_of_day()).await;
+        if spent >= limit {
+            return Some(exceeded("the tasks of the user today", spent, limit));
+        }
+    }
+    if let Some(limit) = budget.repository_daily {
+        let spent = conn.repository_llm_cost_since(&task.repository_id, start_of_day()).await;
+        if spent >= limit {
+            return Some(exceeded("the tasks on the repository today", spent, limit));
+        }
+    }
+    None
+}
+
+fn exceeded(scope: &str, spent: f64, limit: f64) -> String {
+    format!("LLM budget exceeded: {} spent ${:.2} of ${:.2}.", scope, spent, limit)
+}
+
+fn start_of_day() -> DateTime<Utc> {
+    Utc::now().date_naive().and_time(Default::default()).and_utc()
+}
@@ -0,0 +1,87 @@
+//! Sending the requests of providers that take their key in a header other than `Authorization`
+//!
+//! The LLM proxy only sends keys as bearer tokens. These requests go through the same
+//! [`TheProxyConfig`] as all others for the URL, the key and the stored interaction, only the
+//! request to the provider is sent here with the key where the provider expects it.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::{header, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::web::BytesMut;
+use actix_web::{Error, HttpResponse};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+use config::LlmProviderAuth;
+use llm_proxy::{CompletionRequest, ProxyConfig};
+
+use super::provider::authorize;
+use super::task::ChatTask;
+use super::TheProxyConfig;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+pub async fn forward_with_header_auth(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    // The proxy rejects requests without a session
+    let Some(task) = ChatTask::of_request(req.request()) else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    let header_auth = |auth: &LlmProviderAuth| matches!(auth, LlmProviderAuth::Header(_));
+    let Some(provider) = task.provider.filter(|provider| header_auth(&provider.auth)) else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+
+    let mut payload = req.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        body.extend_from_slice(&chunk?);
+    }
+    let request: CompletionRequest =
+        serde_json::from_slice(&body).map_err(actix_web::error::ErrorBadRequest)?;
+
+    let proxy = TheProxyConfig {};
+    let ctx = proxy.extract_context(req.request()).await?;
+    let url = proxy.forward_to_url(&ctx, &request).await?;
+    let api_key = proxy.api_key(&ctx, &request).await?;
+
+    let response = authorize(&provider.auth, CLIENT.post(url), &api_key)
+        .header(header::CONTENT_TYPE.as_str(), "application/json")
+        .body(body.freeze())
+        .send()
+        .await
+        .map_err(|err| {
+            log::error!(
+                "Failed to forward the request of task {} to {}: {err}",
+                task.id,
+                provider.name
+            );
+            actix_web::error::ErrorBadGateway("LLM provider request failed")
+        })?;
+
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+    let content_type = (response.headers().get(header::CONTENT_TYPE.as_str()))
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or("application/json")
+        .to_owned();
+
+    // Event streams are passed through and captured like those the proxy passes through
+    if content_type.starts_with("text/event-stream") {
+        proxy.inspect_interaction(&ctx, &request, None).await;
+        let response = HttpResponse::build(status)
+            .content_type(content_type)
+            .streaming(response.bytes_stream());
+        return Ok(req.into_response(response));
+    }
+
+    let bytes = response.bytes().await.map_err(actix_web::error::ErrorBadGateway)?;
+    let completion = serde_json::from_slice::<Value>(&bytes).ok();
+    proxy.inspect_interaction(&ctx, &request, completion).await;
+
+    let response = HttpResponse::build(status).content_type(content_type).body(bytes);
+    Ok(req.into_response(response))
+}
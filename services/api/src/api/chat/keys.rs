@@ -0,0 +1,63 @@
+//! Choosing the key the LLM requests of a task are authenticated with
+//!
+//! OpenRouter requests are paid with the key of the installation or the key of the user who
+//! created the task, in the order the installation prefers. Other providers use their own key.
+
+use config::{LlmProvider, LlmProviderAuth};
+use database::{Conn, LlmKeyPrecedence, LlmKeySource, Task};
+
+mod tests;
+
+/// A key requests are authenticated with and where it came from
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiKey {
+    pub key: String,
+    /// `None` if the provider doesn't need a key
+    pub source: Option<LlmKeySource>,
+}
+
+/// The key for the requests of the task, `None` if neither the installation nor the user has one
+pub async fn api_key_of_task(
+    conn: &mut Conn<'_>,
+    task: &Task,
+    provider: Option<&LlmProvider>,
+) -> Option<ApiKey> {
+    match provider {
+        Some(LlmProvider {
+            auth: LlmProviderAuth::Bearer | LlmProviderAuth::Header(_),
+            api_key,
+            ..
+        }) => {
+            let key = api_key.clone().unwrap_or_default();
+            return Some(ApiKey { key, source: Some(LlmKeySource::Provider) });
+        }
+        Some(LlmProvider { auth: LlmProviderAuth::None, .. }) => {
+            return Some(ApiKey { key: String::new(), source: None });
+        }
+        _ => {}
+    }
+
+    let user_key = conn.get_user(&task.created_by_id).await.openrouter_key;
+    let (installation_key, precedence) = match task.installation_id {
+        Some(installation_id) => {
+            let installation = conn.get_installation(&installation_id).await;
+            (installation.openrouter_key, installation.llm_key_precedence)
+        }
+        None => (None, LlmKeyPrecedence::InstallationFirst),
+    };
+    choose_openrouter_key(precedence, installation_key, user_key)
+}
+
+fn choose_openrouter_key(
+    precedence: LlmKeyPrecedence,
+    installation_key: Option<String>,
+    user_key: Option<String>,
+) -> Option<ApiKey> {
+    let installation =
+        installation_key.map(|key| ApiKey { key, source: Some(LlmKeySource::Installation) });
+    let user = user_key.map(|key| ApiKey { key, source: Some(LlmKeySource::User) });
+    match precedence {
+        LlmKeyPrecedence::InstallationFirst => installation.or(user),
+        LlmKeyPrecedence::UserFirst => user.or(installation),
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use database::{LlmKeyPrecedence, LlmKeySource};
+
+use super::{choose_openrouter_key, ApiKey};
+
+fn key(key: &str, source: LlmKeySource) -> Option<ApiKey> {
+    Some(ApiKey { key: key.to_owned(), source: Some(source) })
+}
+
+#[test]
+fn test_installation_first() {
+    let choose = |installation: Option<&str>, user: Option<&str>| {
+        let installation = installation.map(ToOwned::to_owned);
+        choose_openrouter_key(
+            LlmKeyPrecedence::InstallationFirst,
+            installation,
+            user.map(ToOwned::to_owned),
+        )
+    };
+
+    assert_eq!(choose(Some("org"), Some("own")), key("org", LlmKeySource::Installation));
+    assert_eq!(choose(None, Some("own")), key("own", LlmKeySource::User));
+    assert_eq!(choose(None, None), None);
+}
+
+#[test]
+fn test_user_first() {
+    let choose = |installation: Option<&str>, user: Option<&str>| {
+        let installation = installation.map(ToOwned::to_owned);
+        choose_openrouter_key(
+            LlmKeyPrecedence::UserFirst,
+            installation,
+            user.map(ToOwned::to_owned),
+        )
+    };
+
+    assert_eq!(choose(Some("org"), Some("own")), key("own", LlmKeySource::User));
+    assert_eq!(choose(Some("org"), None), key("org", LlmKeySource::Installation));
+    assert_eq!(choose(None, None), None);
+}
@@ -1,30 +1,63 @@
-use actix_web::{dev::Payload, web, Error, FromRequest, HttpRequest, Scope};
+use actix_web::body::BoxBody;
+use actix_web::dev::{Payload, Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{middleware, web, Error, FromRequest, HttpRequest, Scope};
 use serde_json::Value;
 use url::Url;
 
 use auth::AgentSessionId;
+use config::Config;
 use database::Database;
 use llm_proxy::{CompletionRequest, ProxyConfig};
 
 use once_cell::sync::Lazy;
 
+mod budget;
+mod forward;
+mod keys;
+mod provider;
+mod replay;
 mod storage;
+mod stream;
+mod task;
 
-use storage::store_interaction;
+use storage::{store_interaction, Usage};
+use stream::StreamCapture;
+use task::ChatTask;
 
 static OPENROUTER_CHAT_COMPLETIONS_URL: Lazy<Url> = Lazy::new(|| {
     Url::parse("https://openrouter.ai/api/v1/chat/completions")
         .expect("Failed to parse OpenRouter chat completions URL")
 });
 
-pub fn scope() -> Scope {
+pub fn scope() -> Scope<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<BoxBody>,
+        Error = Error,
+        InitError = (),
+    >,
+> {
+    // The last middleware runs first: the task is looked up once, then the body is rewritten
+    // before replays and forwarding read it
     llm_proxy::scope(TheProxyConfig {})
+        .wrap(middleware::from_fn(forward::forward_with_header_auth))
+        .wrap(middleware::from_fn(replay::serve_replays))
+        .wrap_fn(|mut req, srv| {
+            provider::rewrite_request(&mut req);
+            let capture = StreamCapture::attach(&req);
+            let response = srv.call(req);
+            async move { Ok(capture.tee(response.await?)) }
+        })
+        .wrap(middleware::from_fn(task::look_up_task))
 }
 
 #[derive(Clone)]
 struct ProxyContext {
-    agent_session: AgentSessionId,
+    task: ChatTask,
     db: web::Data<Database>,
+    config: web::Data<Config>,
+    stream: StreamCapture,
 }
 
 #[derive(Clone)]
@@ -34,9 +67,16 @@ impl ProxyConfig for TheProxyConfig {
     type Context = ProxyContext;
 
     async fn extract_context(&self, req: &HttpRequest) -> Result<Self::Context, Error> {
-        let agent_session = AgentSessionId::from_request(req, &mut Payload::None).await?;
+        AgentSessionId::from_request(req, &mut Payload::None).await?;
+        let task = ChatTask::of_request(req).expect("Task of the agent session not looked up");
+        // Replays are answered from the recording before they reach the proxy
+        if task.replay_of_task_id.is_some() {
+            return Err(actix_web::error::ErrorBadRequest("Replays are not forwarded"));
+        }
         let db = req.app_data::<web::Data<Database>>().expect("Database not available").clone();
-        Ok(ProxyContext { agent_session, db })
+        let config = req.app_data::<web::Data<Config>>().expect("Config not available").clone();
+        let stream = StreamCapture::of_request(req);
+        Ok(ProxyContext { task, db, config, stream })
     }
 
     async fn api_key(
@@ -44,19 +84,26 @@ impl ProxyConfig for TheProxyConfig {
         ctx: &Self::Context,
         _req: &CompletionRequest,
     ) -> Result<String, Error> {
-        let mut conn = ctx.db.conn().await;
-        let user_id = conn.user_id_that_created_task(&ctx.agent_session.task_id).await;
-        let user = conn.get_user(&user_id).await;
-        user.openrouter_key
-            .ok_or(actix_web::error::ErrorBadRequest("No OpenRouter API key configured."))
+        // The status and the budgets of the task were checked when it was looked up
+        match &ctx.task.api_key {
+            Some(api_key) => Ok(api_key.key.clone()),
+            None => Err(actix_web::error::ErrorBadRequest(
+                "No OpenRouter API key configured for the installation or the user.",
+            )),
+        }
     }
 
     async fn forward_to_url(
         &self,
-        _ctx: &Self::Context,
+        ctx: &Self::Context,
         _req: &CompletionRequest,
     ) -> Result<Url, Error> {
-        Ok(OPENROUTER_CHAT_COMPLETIONS_URL.clone())
+        if let Some(provider) = &ctx.task.provider {
+            return Ok(provider.chat_completions_url.clone());
+        }
+
+        let url = ctx.config.llm_chat_completions_url.clone();
+        Ok(url.unwrap_or_else(|| OPENROUTER_CHAT_COMPLETIONS_URL.clone()))
     }
 
     async fn inspect_interaction(
@@ -65,7 +112,21 @@ impl ProxyConfig for TheProxyConfig {
         request: &CompletionRequest,
         response: Option<Value>,
     ) {
+        let token_prices =
+            ctx.task.provider.as_ref().and_then(|provider| provider.token_prices.clone());
+        let usage = match &response {
+            Some(response) => Usage::of_response(response, token_prices.as_ref()),
+            None => Usage::default(),
+        };
+        let streamed = response.is_none();
+        let key_source = ctx.task.api_key.as_ref().and_then(|api_key| api_key.source);
         let mut conn = ctx.db.conn().await;
-        store_interaction(&mut conn, ctx.agent_session.task_id, request, response).await;
+        let task_id = ctx.task.id;
+        let interaction_id =
+            store_interaction(&mut conn, task_id, request, response, usage, key_source).await;
+        drop(conn);
+        if streamed {
+            ctx.stream.interaction_stored(&ctx.db, interaction_id, token_prices).await;
+        }
     }
 }
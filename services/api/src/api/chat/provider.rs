@@ -0,0 +1,121 @@
+//! Choosing the LLM provider the requests of a task are forwarded to
+//!
+//! Without a provider set for the repository or its installation the requests go to OpenRouter
+//! with the key of the installation or of the user who created the task. Keys are sent as bearer
+//! token, or in the header the provider names.
+
+use std::collections::HashMap;
+
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::error::PayloadError;
+use actix_web::http::header;
+use actix_web::web::{Bytes, BytesMut};
+use futures_util::{stream, StreamExt};
+use serde_json::{json, Value};
+
+use config::{Config, LlmProvider, LlmProviderAuth};
+use database::{Conn, Task};
+
+use super::task::ChatTask;
+
+mod tests;
+
+/// The provider set for the repository of the task, else the one set for its installation
+pub async fn provider_of_task(
+    conn: &mut Conn<'_>,
+    config: &Config,
+    task: &Task,
+) -> Result<Option<LlmProvider>, String> {
+    let Some(installation_id) = task.installation_id else {
+        return Ok(None);
+    };
+    let Some(name) = conn.llm_provider_name(installation_id, task.repository_id).await else {
+        return Ok(None);
+    };
+    let Some(provider) = config.llm_provider(&name) else {
+        return Err(format!("The LLM provider {} is not configured.", name));
+    };
+    let installation = conn.get_installation(&installation_id).await;
+    if !provider.allows_installation(installation.github_id) {
+        return Err(format!("The LLM provider {} is not available to the installation.", name));
+    }
+    Ok(Some(provider.clone()))
+}
+
+/// Rewrite the request for the provider of the task
+///
+/// The model is renamed to the name the provider knows it by, and the usage including the cost is
+/// requested so that budgets can be enforced. The proxy forwards the body it reads, so the body is
+/// rewritten before the proxy reads it.
+pub fn rewrite_request(req: &mut ServiceRequest) {
+    // The proxy rejects requests without a session
+    let Some(task) = ChatTask::of_request(req.request()) else {
+        return;
+    };
+    let mut payload = req.take_payload();
+    // The length of the body changes with the rewrite
+    req.headers_mut().remove(header::CONTENT_LENGTH);
+
+    let body = async move {
+        let mut body = BytesMut::new();
+        while let Some(chunk) = payload.next().await {
+            body.extend_from_slice(&chunk?);
+        }
+        Ok::<_, PayloadError>(rewrite_body(task.provider.as_ref(), body.freeze()))
+    };
+    req.set_payload(Payload::Stream { payload: Box::pin(stream::once(body)) });
+}
+
+/// Add the key to the request to the provider the way its auth expects it
+pub fn authorize(
+    auth: &LlmProviderAuth,
+    request: reqwest::RequestBuilder,
+    api_key: &str,
+) -> reqwest::RequestBuilder {
+    match auth {
+        LlmProviderAuth::Header(header_name) => request.header(header_name.as_str(), api_key),
+        LlmProviderAuth::None => request,
+        LlmProviderAuth::OpenRouterUserKey | LlmProviderAuth::Bearer => {
+            request.bearer_auth(api_key)
+        }
+    }
+}
+
+fn rewrite_body(provider: Option<&LlmProvider>, body: Bytes) -> Bytes {
+    let Ok(mut request) = serde_json::from_slice::<Value>(&body) else {
+        return body;
+    };
+    if !request.is_object() {
+        return body;
+    }
+    if let Some(provider) = provider {
+        rename_model(&provider.models, &mut request);
+    }
+    // Only OpenRouter reports the cost, other providers may reject its `usage` parameter
+    let openrouter = provider
+        .map_or(true, |provider| matches!(provider.auth, LlmProviderAuth::OpenRouterUserKey));
+    request_usage(&mut request, openrouter);
+    serde_json::to_vec(&request).map(Bytes::from).unwrap_or(body)
+}
+
+fn rename_model(models: &HashMap<String, String>, request: &mut Value) {
+    if let Some(model) = request["model"].as_str().and_then(|model| models.get(model)) {
+        request["model"] = model.clone().into();
+    }
+}
+
+/// Ask for the usage, which streamed responses only include on request
+fn request_usage(request: &mut Value, openrouter: bool) {
+    if request["stream"].as_bool() == Some(true) {
+        if !request["stream_options"].is_object() {
+            request["stream_options"] = json!({});
+        }
+        request["stream_options"]["include_usage"] = true.into();
+    }
+    if openrouter {
+        if !request["usage"].is_object() {
+            request["usage"] = json!({});
+        }
+        request["usage"]["include"] = true.into();
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use std::collections::HashMap;
+
+use actix_web::web::Bytes;
+use serde_json::{json, Value};
+
+use config::{LlmProvider, LlmProviderAuth};
+
+use super::{authorize, rewrite_body};
+
+fn provider(auth: LlmProviderAuth) -> LlmProvider {
+    LlmProvider {
+        name: "gateway".to_owned(),
+        chat_completions_url: "https://llm.example.com/v1/chat/completions".parse().unwrap(),
+        auth,
+        api_key: Some("key".to_owned()),
+        models: HashMap::from([("openai/gpt-4o".to_owned(), "gpt-4o-2024-11-20".to_owned())]),
+        installations: None,
+        token_prices: None,
+    }
+}
+
+fn rewrite(provider: Option<&LlmProvider>, body: Value) -> Value {
+    let rewritten = rewrite_body(provider, Bytes::from(body.to_string()));
+    serde_json::from_slice(&rewritten).unwrap()
+}
+
+#[test]
+fn test_rename_model() {
+    let body = json!({ "model": "openai/gpt-4o", "messages": [] });
+
+    let rewritten = rewrite(Some(&provider(LlmProviderAuth::Bearer)), body);
+
+    assert_eq!(rewritten, json!({ "model": "gpt-4o-2024-11-20", "messages": [] }));
+}
+
+#[test]
+fn test_unmapped_model_is_kept() {
+    let body = json!({ "model": "anthropic/claude-3.7-sonnet" });
+
+    let rewritten = rewrite(Some(&provider(LlmProviderAuth::Bearer)), body.clone());
+
+    assert_eq!(rewritten, body);
+}
+
+#[test]
+fn test_stream_usage_is_requested() {
+    let body = json!({ "model": "m", "stream": true, "stream_options": { "other": 1 } });
+
+    let rewritten = rewrite(Some(&provider(LlmProviderAuth::Bearer)), body);
+
+    assert_eq!(
+        rewritten,
+        json!({ "model": "m", "stream": true, "stream_options": { "other": 1, "include_usage": true } })
+    );
+}
+
+#[test]
+fn test_openrouter_usage_is_requested() {
+    let body = json!({ "model": "openai/gpt-4o", "stream": true });
+
+    let rewritten = rewrite(None, body);
+
+    assert_eq!(
+        rewritten,
+        json!({
+            "model": "openai/gpt-4o",
+            "stream": true,
+            "stream_options": { "include_usage": true },
+            "usage": { "include": true },
+        })
+    );
+}
+
+#[test]
+fn test_invalid_body_is_kept() {
+    let body = Bytes::from_static(b"not json");
+
+    assert_eq!(rewrite_body(None, body.clone()), body);
+}
+
+#[test]
+fn test_authorize_in_named_header() {
+    let request = reqwest::Client::new().post("https://llm.example.com/v1/chat/completions");
+
+    let auth = LlmProviderAuth::Header("api-key".to_owned());
+    let request = authorize(&auth, request, "key").build().unwrap();
+
+    assert_eq!(request.headers()["api-key"], "key");
+    assert!(request.headers().get("authorization").is_none());
+}
+
+#[test]
+fn test_authorize_as_bearer() {
+    let request = reqwest::Client::new().post("https://llm.example.com/v1/chat/completions");
+
+    let request = authorize(&LlmProviderAuth::Bearer, request, "key").build().unwrap();
+
+    assert_eq!(request.headers()["authorization"], "Bearer key");
+}
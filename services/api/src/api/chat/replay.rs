@@ -0,0 +1,100 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::{self, BytesMut};
+use actix_web::{Error, HttpResponse};
+use futures_util::StreamExt;
+use serde_json::Value;
+
+use database::{Database, LLMInteraction};
+use llm_proxy::CompletionRequest;
+
+use super::storage::{store_interaction, Usage};
+use super::task::ChatTask;
+
+/// Serves the recorded responses of an earlier task to the agent of a replay task.
+///
+/// Requests of replay tasks are answered in-process and never reach the proxy, other requests are
+/// passed on to it.
+pub async fn serve_replays(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    // The proxy rejects requests without a session
+    let Some(task) = ChatTask::of_request(req.request()) else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    let Some(source_task_id) = task.replay_of_task_id else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    let db = req.app_data::<web::Data<Database>>().expect("Database not available").clone();
+
+    // Recorded requests are stored as the proxy parsed them
+    let mut payload = req.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        body.extend_from_slice(&chunk?);
+    }
+    let request: CompletionRequest =
+        serde_json::from_slice(&body).map_err(actix_web::error::ErrorBadRequest)?;
+    let request = serde_json::to_value(request)?;
+
+    let mut conn = db.conn().await;
+    let recorded = conn.llm_interactions(&source_task_id).await;
+    let replayed = conn.llm_interactions(&task.id).await;
+
+    let Some(interaction) = matching_interaction(&recorded, &replayed, &request) else {
+        let response = HttpResponse::Conflict().body("Request diverges from the recorded task");
+        return Ok(req.into_response(response));
+    };
+
+    let Some(response) = interaction.response.clone() else {
+        let response = HttpResponse::NotFound().body("No response was recorded for this request");
+        return Ok(req.into_response(response));
+    };
+
+    // Replays are answered from the recording and cost nothing
+    let usage = Usage::default();
+    store_interaction(&mut conn, task.id, &request, Some(response.clone()), usage, None).await;
+
+    let response = if request.get("stream").and_then(Value::as_bool).unwrap_or(false) {
+        HttpResponse::Ok().content_type("text/event-stream").body(as_event_stream(response))
+    } else {
+        HttpResponse::Ok().json(response)
+    };
+    Ok(req.into_response(response))
+}
+
+/// Find the recorded interaction that answers `request`.
+///
+/// Agents may send identical requests several times, e.g. when retrying. The n-th occurrence of a
+/// request in the replay is answered by the n-th occurrence in the recording.
+fn matching_interaction<'a>(
+    recorded: &'a [LLMInteraction],
+    replayed: &[LLMInteraction],
+    request: &Value,
+) -> Option<&'a LLMInteraction> {
+    let occurrence =
+        replayed.iter().filter(|interaction| interaction.request.as_ref() == Some(request)).count();
+
+    recorded
+        .iter()
+        .filter(|interaction| interaction.request.as_ref() == Some(request))
+        .nth(occurrence)
+}
+
+/// Turn a recorded completion into a server-sent event stream consisting of a single chunk.
+fn as_event_stream(mut completion: Value) -> String {
+    if let Some(completion) = completion.as_object_mut() {
+        completion.insert("object".to_owned(), "chat.completion.chunk".into());
+        let choices = completion.get_mut("choices").and_then(Value::as_array_mut);
+        for choice in choices.into_iter().flatten() {
+            if let Some(choice) = choice.as_object_mut() {
+                if let Some(message) = choice.remove("message") {
+                    choice.insert("delta".to_owned(), message);
+                }
+            }
+        }
+    }
+    format!("data: {}\n\ndata: [DONE]\n\n", completion)
+}
@@ -1,18 +1,58 @@
 use serde_json::Value;
 use uuid::Uuid;
 
-use database::{Conn, NewLLMInteraction};
+use config::TokenPrices;
+use database::{Conn, LlmKeySource, NewLLMInteraction};
+
+mod tests;
+
+/// Tokens and cost the LLM provider reported in the `usage` field of a response
+#[derive(Debug, Default, PartialEq)]
+pub struct Usage {
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+    /// In USD
+    pub cost: Option<f64>,
+}
+
+impl Usage {
+    /// The usage of the response, its cost computed from the token prices if it isn't reported
+    pub fn of_response(response: &Value, token_prices: Option<&TokenPrices>) -> Self {
+        let usage = &response["usage"];
+        let tokens = |field: &str| usage[field].as_i64().and_then(|tokens| tokens.try_into().ok());
+        let prompt_tokens = tokens("prompt_tokens");
+        let completion_tokens = tokens("completion_tokens");
+        let priced_cost = token_prices.zip(prompt_tokens.zip(completion_tokens)).map(
+            |(prices, (prompt_tokens, completion_tokens))| {
+                (prompt_tokens as f64 * prices.prompt
+                    + completion_tokens as f64 * prices.completion)
+                    / 1_000_000.0
+            },
+        );
+        Usage { prompt_tokens, completion_tokens, cost: usage["cost"].as_f64().or(priced_cost) }
+    }
+}
 
 /// Stores an interaction in the database by saving the serialized request and response.
 ///
-/// For streaming requests the response is `None` at the moment.
+/// For streaming requests the response is `None`, it is filled in once the stream ended.
 pub async fn store_interaction(
     conn: &mut Conn<'_>,
     task_id: Uuid,
     request: &impl serde::Serialize,
     response: Option<Value>,
-) {
+    usage: Usage,
+    key_source: Option<LlmKeySource>,
+) -> Uuid {
     let request = serde_json::to_value(request).ok();
-    let new_interaction = NewLLMInteraction { task_id, request, response };
-    conn.add_llm_interaction(new_interaction).await;
+    let new_interaction = NewLLMInteraction {
+        task_id,
+        request,
+        response,
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        cost: usage.cost,
+        key_source,
+    };
+    conn.add_llm_interaction(new_interaction).await.id
 }
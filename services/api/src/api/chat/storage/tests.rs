@@ -0,0 +1,45 @@
+#![cfg(test)]
+
+use serde_json::json;
+
+use config::TokenPrices;
+
+use super::Usage;
+
+#[test]
+fn test_usage_of_response() {
+    let response = json!({
+        "choices": [],
+        "usage": {
+            "prompt_tokens": 1200,
+            "completion_tokens": 300,
+            "total_tokens": 1500,
+            "cost": 0.0042
+        }
+    });
+
+    let usage = Usage::of_response(&response, None);
+
+    assert_eq!(
+        usage,
+        Usage { prompt_tokens: Some(1200), completion_tokens: Some(300), cost: Some(0.0042) }
+    );
+}
+
+#[test]
+fn test_usage_of_response_without_usage() {
+    assert_eq!(Usage::of_response(&json!({ "choices": [] }), None), Usage::default());
+}
+
+#[test]
+fn test_usage_of_response_priced_by_tokens() {
+    let response = json!({ "usage": { "prompt_tokens": 1_000_000, "completion_tokens": 500_000 } });
+    let prices = TokenPrices { prompt: 2.5, completion: 10.0 };
+
+    let usage = Usage::of_response(&response, Some(&prices));
+
+    assert_eq!(
+        usage,
+        Usage { prompt_tokens: Some(1_000_000), completion_tokens: Some(500_000), cost: Some(7.5) }
+    );
+}
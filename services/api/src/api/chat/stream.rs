@@ -0,0 +1,296 @@
+//! Capturing streamed chat completions
+//!
+//! The LLM proxy passes event streams through to the agent and hands `None` to
+//! `inspect_interaction`. A middleware tees the stream and reassembles its chunks into a
+//! completion. The completion is stored on the interaction once the stream ended and the proxy
+//! stored the interaction, in whichever order the two happen.
+
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use actix_web::body::{BodySize, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::{self, Bytes};
+use actix_web::{http::header, HttpRequest};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use config::TokenPrices;
+use database::{Database, LLMInteractionResponse};
+
+use super::storage::Usage;
+
+mod tests;
+
+/// Connects the interaction the proxy stored for a request with the stream of its response
+#[derive(Clone, Default)]
+pub struct StreamCapture(Arc<Mutex<CaptureState>>);
+
+#[derive(Default)]
+struct CaptureState {
+    interaction: Option<StoredInteraction>,
+    completion: Option<Value>,
+}
+
+struct StoredInteraction {
+    id: Uuid,
+    /// Prices of the provider the request was forwarded to
+    token_prices: Option<TokenPrices>,
+}
+
+impl StreamCapture {
+    /// Start capturing the response of the request
+    pub fn attach(req: &ServiceRequest) -> Self {
+        let capture = StreamCapture::default();
+        req.extensions_mut().insert(capture.clone());
+        capture
+    }
+
+    /// The capture of the request, a detached one if the request isn't captured
+    pub fn of_request(req: &HttpRequest) -> Self {
+        req.extensions().get::<StreamCapture>().cloned().unwrap_or_default()
+    }
+
+    /// Tee the body of the response if it is an event stream
+    pub fn tee(self, res: ServiceResponse) -> ServiceResponse {
+        let is_stream = res.status().is_success()
+            && (res.headers().get(header::CONTENT_TYPE))
+                .and_then(|content_type| content_type.to_str().ok())
+                .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+        if !is_stream {
+            return res;
+        }
+
+        let db = (res.request().app_data::<web::Data<Database>>())
+            .expect("Database not available")
+            .clone();
+        res.map_body(|_, body| {
+            let assembler = Some(CompletionAssembler::default());
+            TeeBody { inner: body, assembler, capture: self, db }.boxed()
+        })
+    }
+
+    /// The proxy stored the interaction without a response
+    pub async fn interaction_stored(
+        &self,
+        db: &Database,
+        id: Uuid,
+        token_prices: Option<TokenPrices>,
+    ) {
+        let interaction = StoredInteraction { id, token_prices };
+        let ready = {
+            let mut state = self.0.lock().unwrap();
+            match state.completion.take() {
+                Some(completion) => Some((interaction, completion)),
+                None => {
+                    state.interaction = Some(interaction);
+                    None
+                }
+            }
+        };
+        if let Some((interaction, completion)) = ready {
+            store_completion(db, interaction, completion).await;
+        }
+    }
+
+    fn stream_ended(&self, db: web::Data<Database>, completion: Value) {
+        let mut state = self.0.lock().unwrap();
+        match state.interaction.take() {
+            Some(interaction) => {
+                actix_web::rt::spawn(async move {
+                    store_completion(&db, interaction, completion).await;
+                });
+            }
+            None => state.completion = Some(completion),
+        }
+    }
+}
+
+async fn store_completion(db: &Database, interaction: StoredInteraction, completion: Value) {
+    let usage = Usage::of_response(&completion, interaction.token_prices.as_ref());
+    let response = LLMInteractionResponse {
+        response: Some(completion),
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        cost: usage.cost,
+    };
+    db.conn().await.set_llm_interaction_response(interaction.id, response).await;
+}
+
+/// Passes the body through while feeding it to the assembler
+struct TeeBody {
+    inner: BoxBody,
+    /// Taken when the stream ends
+    assembler: Option<CompletionAssembler>,
+    capture: StreamCapture,
+    db: web::Data<Database>,
+}
+
+impl MessageBody for TeeBody {
+    type Error = Box<dyn std::error::Error>;
+
+    fn size(&self) -> BodySize {
+        self.inner.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(assembler) = &mut this.assembler {
+                    assembler.push(chunk);
+                }
+            }
+            Poll::Ready(None) => {
+                if let Some(assembler) = this.assembler.take() {
+                    this.capture.stream_ended(this.db.clone(), assembler.finish());
+                }
+            }
+            _ => {}
+        }
+        poll
+    }
+}
+
+/// Reassembles the chunks of a chat completion event stream into a completion
+#[derive(Default)]
+pub struct CompletionAssembler {
+    /// Received bytes that don't form a complete event yet
+    pending: Vec<u8>,
+    /// Fields of the chunks besides the choices, e.g. the model and the usage
+    fields: Map<String, Value>,
+    choices: BTreeMap<u64, Choice>,
+}
+
+#[derive(Default)]
+struct Choice {
+    role: Option<Value>,
+    content: Option<String>,
+    reasoning: Option<String>,
+    tool_calls: BTreeMap<u64, ToolCall>,
+    finish_reason: Option<Value>,
+}
+
+#[derive(Default)]
+struct ToolCall {
+    id: Option<String>,
+    kind: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+impl CompletionAssembler {
+    pub fn push(&mut self, bytes: &[u8]) {
+        // Carriage returns only ever separate lines, JSON escapes them in strings
+        self.pending.extend(bytes.iter().filter(|&&byte| byte != b'\r'));
+        while let Some(end) = self.pending.windows(2).position(|window| window == b"\n\n") {
+            let event: Vec<u8> = self.pending.drain(..end + 2).collect();
+            self.event(&event);
+        }
+    }
+
+    /// The completion the chunks add up to
+    pub fn finish(mut self) -> Value {
+        // The last event may lack the blank line that terminates it
+        let pending = std::mem::take(&mut self.pending);
+        self.event(&pending);
+
+        let choices = (self.choices.into_iter())
+            .map(|(index, choice)| {
+                let mut message = Map::new();
+                message.insert("role".to_owned(), choice.role.unwrap_or("assistant".into()));
+                message.insert("content".to_owned(), choice.content.into());
+                if let Some(reasoning) = choice.reasoning {
+                    message.insert("reasoning".to_owned(), reasoning.into());
+                }
+                if !choice.tool_calls.is_empty() {
+                    let tool_calls = (choice.tool_calls.into_values())
+                        .map(|call| {
+                            json!({
+                                "id": call.id,
+                                "type": call.kind.unwrap_or_else(|| "function".to_owned()),
+                                "function": { "name": call.name, "arguments": call.arguments },
+                            })
+                        })
+                        .collect();
+                    message.insert("tool_calls".to_owned(), Value::Array(tool_calls));
+                }
+                json!({
+                    "index": index,
+                    "message": message,
+                    "finish_reason": choice.finish_reason,
+                })
+            })
+            .collect();
+
+        let mut completion = self.fields;
+        completion.insert("object".to_owned(), "chat.completion".into());
+        completion.insert("choices".to_owned(), Value::Array(choices));
+        Value::Object(completion)
+    }
+
+    fn event(&mut self, event: &[u8]) {
+        let event = String::from_utf8_lossy(event);
+        // Other lines are comments, e.g. keep-alives, or name the event
+        let data = event.lines().filter_map(|line| line.strip_prefix("data:"));
+        for data in data.map(str::trim) {
+            if data == "[DONE]" {
+                continue;
+            }
+            if let Ok(Value::Object(chunk)) = serde_json::from_str(data) {
+                self.chunk(chunk);
+            }
+        }
+    }
+
+    fn chunk(&mut self, mut chunk: Map<String, Value>) {
+        let choices = chunk.remove("choices");
+        for (key, value) in chunk {
+            if !value.is_null() {
+                self.fields.insert(key, value);
+            }
+        }
+
+        for choice in choices.as_ref().and_then(Value::as_array).into_iter().flatten() {
+            let index = choice["index"].as_u64().unwrap_or(0);
+            let state = self.choices.entry(index).or_default();
+            let delta = &choice["delta"];
+            if !delta["role"].is_null() {
+                state.role = Some(delta["role"].clone());
+            }
+            append(&mut state.content, &delta["content"]);
+            append(&mut state.reasoning, &delta["reasoning"]);
+            for call in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = call["index"].as_u64().unwrap_or(0);
+                let state = state.tool_calls.entry(index).or_default();
+                if let Some(id) = call["id"].as_str() {
+                    state.id = Some(id.to_owned());
+                }
+                if let Some(kind) = call["type"].as_str() {
+                    state.kind = Some(kind.to_owned());
+                }
+                if let Some(name) = call["function"]["name"].as_str() {
+                    state.name.push_str(name);
+                }
+                if let Some(arguments) = call["function"]["arguments"].as_str() {
+                    state.arguments.push_str(arguments);
+                }
+            }
+            if !choice["finish_reason"].is_null() {
+                state.finish_reason = Some(choice["finish_reason"].clone());
+            }
+        }
+    }
+}
+
+fn append(text: &mut Option<String>, delta: &Value) {
+    if let Some(delta) = delta.as_str() {
+        text.get_or_insert_with(String::new).push_str(delta);
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(test)]
+
+use serde_json::{json, Value};
+
+use super::CompletionAssembler;
+
+fn event(chunk: Value) -> String {
+    format!("data: {}\n\n", chunk)
+}
+
+fn chunk(delta: Value, finish_reason: Value) -> Value {
+    json!({
+        "id": "gen-1",
+        "object": "chat.completion.chunk",
+        "created": 1741000000,
+        "model": "openai/gpt-4o",
+        "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+    })
+}
+
+#[test]
+fn test_content_deltas() {
+    let stream = [
+        ": OPENROUTER PROCESSING\n\n".to_owned(),
+        event(chunk(json!({ "role": "assistant", "content": "I added " }), Value::Null)),
+        event(chunk(json!({ "content": "the file." }), json!("stop"))),
+        event(json!({
+            "id": "gen-1",
+            "object": "chat.completion.chunk",
+            "choices": [],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5, "cost": 0.0001 },
+        })),
+        "data: [DONE]\n\n".to_owned(),
+    ]
+    .concat();
+
+    // Events are split across network chunks
+    let mut assembler = CompletionAssembler::default();
+    for part in stream.as_bytes().chunks(7) {
+        assembler.push(part);
+    }
+
+    assert_eq!(
+        assembler.finish(),
+        json!({
+            "id": "gen-1",
+            "object": "chat.completion",
+            "created": 1741000000,
+            "model": "openai/gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "I added the file." },
+                "finish_reason": "stop",
+            }],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5, "cost": 0.0001 },
+        })
+    );
+}
+
+#[test]
+fn test_tool_call_deltas() {
+    let call = |index: u64, call: Value| {
+        let mut call = call;
+        call["index"] = index.into();
+        json!({ "tool_calls": [call] })
+    };
+    let stream = [
+        event(chunk(json!({ "role": "assistant", "content": null }), Value::Null)),
+        event(chunk(
+            call(0, json!({ "id": "call_1", "type": "function", "function": { "name": "read" } })),
+            Value::Null,
+        )),
+        event(chunk(call(0, json!({ "function": { "arguments": "{\"path\":" } })), Value::Null)),
+        event(chunk(
+            call(1, json!({ "id": "call_2", "function": { "name": "ls", "arguments": "{}" } })),
+            Value::Null,
+        )),
+        event(chunk(call(0, json!({ "function": { "arguments": "\"a.rs\"}" } })), Value::Null)),
+        event(chunk(json!({}), json!("tool_calls"))),
+    ]
+    .concat();
+
+    let mut assembler = CompletionAssembler::default();
+    assembler.push(stream.replace('\n', "\r\n").as_bytes());
+
+    let completion = assembler.finish();
+    assert_eq!(
+        completion["choices"][0],
+        json!({
+            "index": 0,
+            "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [
+                    {
+                        "id": "call_1",
+                        "type": "function",
+                        "function": { "name": "read", "arguments": "{\"path\":\"a.rs\"}" },
+                    },
+                    {
+                        "id": "call_2",
+                        "type": "function",
+                        "function": { "name": "ls", "arguments": "{}" },
+                    },
+                ],
+            },
+            "finish_reason": "tool_calls",
+        })
+    );
+}
+
+#[test]
+fn test_unterminated_last_event() {
+    let mut assembler = CompletionAssembler::default();
+    assembler
+        .push(format!("data: {}", chunk(json!({ "content": "Done" }), json!("stop"))).as_bytes());
+
+    let completion = assembler.finish();
+    assert_eq!(completion["choices"][0]["message"]["content"], "Done");
+    assert_eq!(completion["choices"][0]["finish_reason"], "stop");
+}
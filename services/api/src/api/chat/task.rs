@@ -0,0 +1,83 @@
+//! Looking up the task of a chat request
+//!
+//! The task, its provider and its key are looked up once per request, before its body is read,
+//! and kept in the request extensions for the rewrite, the replays and the proxy.
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest};
+use uuid::Uuid;
+
+use auth::AgentSessionId;
+use config::{Config, LlmProvider};
+use database::{Conn, Database, TaskStatus};
+
+use super::budget::exceeded_budget;
+use super::keys::{api_key_of_task, ApiKey};
+use super::provider::provider_of_task;
+
+/// The task a chat request was sent for
+#[derive(Clone)]
+pub struct ChatTask {
+    pub id: Uuid,
+    /// The task whose recorded responses answer the requests of a replay
+    pub replay_of_task_id: Option<Uuid>,
+    /// The provider requests are forwarded to instead of OpenRouter, of the recorded task for
+    /// replays
+    pub provider: Option<LlmProvider>,
+    /// The key requests are authenticated with, `None` for replays and if no key is configured
+    pub api_key: Option<ApiKey>,
+}
+
+impl ChatTask {
+    /// The task of the request, `None` if the request has no agent session
+    pub fn of_request(req: &HttpRequest) -> Option<Self> {
+        req.extensions().get::<ChatTask>().cloned()
+    }
+}
+
+/// Look up the task of the request and reject it if the task may not send any more requests
+pub async fn look_up_task(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    // The proxy rejects requests without a session
+    let Ok(agent) = AgentSessionId::extract(req.request()).await else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    let db = req.app_data::<web::Data<Database>>().expect("Database not available").clone();
+    let config = req.app_data::<web::Data<Config>>().expect("Config not available").clone();
+
+    let task = {
+        let mut conn = db.conn().await;
+        chat_task(&mut conn, &config, agent.task_id).await?
+    };
+    req.extensions_mut().insert(task);
+    Ok(next.call(req).await?.map_into_boxed_body())
+}
+
+async fn chat_task(conn: &mut Conn<'_>, config: &Config, task_id: Uuid) -> Result<ChatTask, Error> {
+    let task = conn.get_task(&task_id).await;
+    // Agents of cancelled tasks may still be running, they don't get to spend any more
+    if !matches!(task.status, TaskStatus::Running) {
+        return Err(actix_web::error::ErrorConflict("Task is not running"));
+    }
+
+    // Replays cost nothing, but must send the requests the recorded task sent to be matched
+    if let Some(recorded_task_id) = task.replay_of_task_id {
+        let recorded_task = conn.get_task(&recorded_task_id).await;
+        let provider = provider_of_task(conn, config, &recorded_task).await.ok().flatten();
+        let replay_of_task_id = Some(recorded_task_id);
+        return Ok(ChatTask { id: task_id, replay_of_task_id, provider, api_key: None });
+    }
+
+    if let Some(reason) = exceeded_budget(conn, &config.llm_budget, &task).await {
+        return Err(actix_web::error::ErrorPaymentRequired(reason));
+    }
+    let provider = provider_of_task(conn, config, &task)
+        .await
+        .map_err(actix_web::error::ErrorServiceUnavailable)?;
+    let api_key = api_key_of_task(conn, &task, provider.as_ref()).await;
+    Ok(ChatTask { id: task_id, replay_of_task_id: None, provider, api_key })
+}
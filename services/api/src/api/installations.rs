@@ -0,0 +1,174 @@
+use actix_web::{delete, get, put, web, HttpResponse};
+
+use auth::UserSessionId;
+use config::Config;
+use database::{Database, Update};
+use user_api::{
+    InstallationInfo, SetLlmKeyPrecedenceRequest, SetLlmProviderRequest, SetOpenRouterKeyRequest,
+};
+use uuid::Uuid;
+
+/// The installations the user administers
+#[get("/installations")]
+pub async fn list_installations(user: UserSessionId, db: web::Data<Database>) -> HttpResponse {
+    if !auth::user_is_active(&db, user.user_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let mut conn = db.conn().await;
+
+    let mut installations = Vec::new();
+    for installation in conn.installations_of_admin(user.user_id).await {
+        let repositories = conn.installation_repositories(installation.id).await;
+        // The account is only known through the names of its repositories
+        let name = (repositories.first())
+            .and_then(|repository| repository.github_full_name.split_once('/'))
+            .map(|(owner, _)| owner.to_owned())
+            .unwrap_or_else(|| format!("Installation {}", installation.github_id));
+        installations.push(InstallationInfo {
+            id: installation.id.to_string(),
+            name,
+            openrouter_key_set: installation.openrouter_key.is_some(),
+            llm_key_precedence: installation.llm_key_precedence.into(),
+            llm_provider: installation.llm_provider,
+        });
+    }
+
+    HttpResponse::Ok().json(installations)
+}
+
+#[put("/installations/{id}/openrouter-key")]
+pub async fn set_installation_openrouter_key(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetOpenRouterKeyRequest>,
+) -> HttpResponse {
+    let installation_id: Uuid = *path;
+
+    if !auth::user_can_admin_installation(&db, user.user_id, installation_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let openrouter_key = payload.into_inner().openrouter_key.trim().to_owned();
+    if openrouter_key.is_empty() {
+        return HttpResponse::BadRequest().body("The key must not be empty");
+    }
+
+    let mut conn = db.conn().await;
+
+    let installation = conn.get_installation(&installation_id).await;
+
+    conn.update_installation(installation.update().openrouter_key(Some(openrouter_key))).await;
+
+    HttpResponse::Ok().finish()
+}
+
+#[delete("/installations/{id}/openrouter-key")]
+pub async fn delete_installation_openrouter_key(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let installation_id: Uuid = *path;
+
+    if !auth::user_can_admin_installation(&db, user.user_id, installation_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let mut conn = db.conn().await;
+
+    let installation = conn.get_installation(&installation_id).await;
+
+    conn.update_installation(installation.update().openrouter_key(None)).await;
+
+    HttpResponse::Ok().finish()
+}
+
+#[put("/installations/{id}/llm-key-precedence")]
+pub async fn set_installation_llm_key_precedence(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetLlmKeyPrecedenceRequest>,
+) -> HttpResponse {
+    let installation_id: Uuid = *path;
+
+    if !auth::user_can_admin_installation(&db, user.user_id, installation_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let llm_key_precedence = payload.into_inner().llm_key_precedence.into();
+
+    let mut conn = db.conn().await;
+
+    let installation = conn.get_installation(&installation_id).await;
+
+    conn.update_installation(installation.update().llm_key_precedence(llm_key_precedence)).await;
+
+    HttpResponse::Ok().finish()
+}
+
+#[put("/installations/{id}/llm-provider")]
+pub async fn set_installation_llm_provider(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetLlmProviderRequest>,
+) -> HttpResponse {
+    let user_id = user.user_id;
+    let installation_id: Uuid = *path;
+
+    if !auth::user_can_admin_installation(&db, user_id, installation_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let mut conn = db.conn().await;
+
+    let installation = conn.get_installation(&installation_id).await;
+
+    let llm_provider = payload.into_inner().llm_provider;
+    if let Some(name) = &llm_provider {
+        match config.llm_provider(name) {
+            None => return HttpResponse::BadRequest().body("Unknown LLM provider"),
+            Some(provider) if !provider.allows_installation(installation.github_id) => {
+                return HttpResponse::Forbidden().body("LLM provider not available");
+            }
+            Some(_) => {}
+        }
+    }
+
+    let update = installation.update().llm_provider(llm_provider);
+
+    conn.update_installation(update).await;
+
+    HttpResponse::Ok().finish()
+}
+
+/// Names of the LLM providers the installations the user administers can choose from
+#[get("/llm-providers")]
+pub async fn list_llm_providers(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+) -> HttpResponse {
+    if !auth::user_is_active(&db, user.user_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let installations = db.conn().await.installations_of_admin(user.user_id).await;
+
+    let names: Vec<&str> = config
+        .llm_providers
+        .iter()
+        .filter(|provider| {
+            installations
+                .iter()
+                .any(|installation| provider.allows_installation(installation.github_id))
+        })
+        .map(|provider| provider.name.as_str())
+        .collect();
+
+    HttpResponse::Ok().json(names)
+}
@@ -1,6 +1,21 @@
 pub mod agent;
 pub mod auth;
 pub mod chat;
+pub mod installations;
 pub mod repos;
 pub mod tasks;
 pub mod user;
+pub mod webhook_deliveries;
+
+use actix_web::HttpResponse;
+
+/// Response for a failed request to GitHub while handling an API request
+pub fn github_error_response(err: github::Error) -> HttpResponse {
+    log::error!("GitHub request failed: {err}");
+    match err {
+        github::Error::RateLimited { retry_after } => HttpResponse::ServiceUnavailable()
+            .insert_header(("Retry-After", retry_after.as_secs().to_string()))
+            .body("GitHub rate limit exceeded"),
+        _ => HttpResponse::BadGateway().body("GitHub request failed"),
+    }
+}
@@ -1,13 +1,22 @@
 use actix_web::{delete, get, post, put, web, HttpResponse};
 
 use auth::UserSessionId;
+use config::Config;
 use database::{Database, Update};
 use github::GitHub;
-use user_api::{AddRepoUserRequest, Repo, RepoUserInfo};
+use user_api::{
+    AddRepoUserRequest, Repo, RepoUserInfo, SetLlmProviderRequest, SetTriggerLabelRequest,
+};
 use uuid::Uuid;
 
+use crate::api::github_error_response;
+
 #[get("/repos")]
-pub async fn list_repos(user: UserSessionId, db: web::Data<Database>) -> HttpResponse {
+pub async fn list_repos(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    github: web::Data<GitHub>,
+) -> HttpResponse {
     if !auth::user_is_active(&db, user.user_id).await {
         return HttpResponse::Forbidden().finish();
     }
@@ -21,9 +30,13 @@ pub async fn list_repos(user: UserSessionId, db: web::Data<Database>) -> HttpRes
         .into_iter()
         .map(|(repo, repo_inst, role)| Repo {
             id: repo.id.to_string(),
+            url: github.urls().repo(&repo.github_full_name).to_string(),
             name: repo.github_full_name,
             active: repo_inst.active,
             role: role.into(),
+            trigger_label: repo_inst.trigger_label,
+            installation_id: repo_inst.installation_id.to_string(),
+            llm_provider: repo_inst.llm_provider,
         })
         .collect::<Vec<_>>();
 
@@ -35,6 +48,7 @@ pub async fn get_repo(
     user: UserSessionId,
     db: web::Data<Database>,
     path: web::Path<Uuid>,
+    github: web::Data<GitHub>,
 ) -> HttpResponse {
     let user_id = user.user_id;
     let repo_id: Uuid = *path;
@@ -50,9 +64,13 @@ pub async fn get_repo(
 
     let response = Repo {
         id: repo.id.to_string(),
+        url: github.urls().repo(&repo.github_full_name).to_string(),
         name: repo.github_full_name,
         active: inst_repo.active,
         role: user_api::UserRole::Admin,
+        trigger_label: inst_repo.trigger_label,
+        installation_id: inst_repo.installation_id.to_string(),
+        llm_provider: inst_repo.llm_provider,
     };
 
     HttpResponse::Ok().json(response)
@@ -106,6 +124,72 @@ pub async fn repos_activate(
     HttpResponse::Ok().finish()
 }
 
+#[put("/repos/{id}/trigger-label")]
+pub async fn set_trigger_label(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetTriggerLabelRequest>,
+) -> HttpResponse {
+    let user_id = user.user_id;
+    let repo_id: Uuid = *path;
+
+    if !auth::user_can_admin_repo(&db, user_id, repo_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let trigger_label = payload.into_inner().trigger_label.map(|label| label.trim().to_owned());
+    let trigger_label = trigger_label.filter(|label| !label.is_empty());
+
+    let mut conn = db.conn().await;
+
+    let inst_repo = conn.installation_repository_by_repo_id(repo_id).await;
+
+    let update = inst_repo.update().trigger_label(trigger_label);
+
+    conn.update_installation_repository(update).await;
+
+    HttpResponse::Ok().finish()
+}
+
+#[put("/repos/{id}/llm-provider")]
+pub async fn set_repo_llm_provider(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    config: web::Data<Config>,
+    path: web::Path<Uuid>,
+    payload: web::Json<SetLlmProviderRequest>,
+) -> HttpResponse {
+    let user_id = user.user_id;
+    let repo_id: Uuid = *path;
+
+    if !auth::user_can_admin_repo(&db, user_id, repo_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let mut conn = db.conn().await;
+
+    let inst_repo = conn.installation_repository_by_repo_id(repo_id).await;
+
+    let llm_provider = payload.into_inner().llm_provider;
+    if let Some(name) = &llm_provider {
+        let installation = conn.get_installation(&inst_repo.installation_id).await;
+        match config.llm_provider(name) {
+            None => return HttpResponse::BadRequest().body("Unknown LLM provider"),
+            Some(provider) if !provider.allows_installation(installation.github_id) => {
+                return HttpResponse::Forbidden().body("LLM provider not available");
+            }
+            Some(_) => {}
+        }
+    }
+
+    let update = inst_repo.update().llm_provider(llm_provider);
+
+    conn.update_installation_repository(update).await;
+
+    HttpResponse::Ok().finish()
+}
+
 #[get("/repos/{id}/users")]
 pub async fn list_repo_users(
     user: UserSessionId,
@@ -157,13 +241,16 @@ pub async fn add_repo_user(
     let mut conn = db.conn().await;
 
     let inst_repo = conn.installation_repository_by_repo_id(repo_id).await;
+    let installation = conn.get_installation(&inst_repo.installation_id).await;
 
-    let jwt = github.github_app_jwt();
-    let access_token = github.installation_access_token(&jwt).await;
-    let github = github.with_access(&access_token.token);
-
-    let Some(user_info) = github.user_info(&payload.github_login).await else {
-        return HttpResponse::NotFound().finish();
+    let user_info = async {
+        let github = github.with_installation_access(installation.github_id).await?;
+        github.user_info(&payload.github_login).await
+    };
+    let user_info = match user_info.await {
+        Ok(Some(user_info)) => user_info,
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(err) => return github_error_response(err),
     };
 
     let Some(existing_user) = conn.get_user_by_github_id(&user_info.id).await else {
@@ -1,12 +1,14 @@
-use actix_web::{get, web, HttpResponse};
+use actix_web::http::header::{self, ContentDisposition};
+use actix_web::{get, post, web, HttpResponse};
 use serde::Deserialize;
 
-use database::Database;
+use database::{Database, NewTask, TaskStatus};
+use github::GitHub;
 use object_storage::{GetObjectError, S3};
 use uuid::Uuid;
 
 use auth::UserSessionId;
-use user_api::{TaskDetails, TaskInfo, TaskPollResponse};
+use user_api::{TaskArtifact, TaskDetails, TaskInfo, TaskPollResponse};
 
 #[get("/tasks")]
 pub async fn list_tasks(user: UserSessionId, db: web::Data<Database>) -> HttpResponse {
@@ -28,6 +30,7 @@ pub async fn task_details(
     user: UserSessionId,
     db: web::Data<Database>,
     path: web::Path<Uuid>,
+    github: web::Data<GitHub>,
 ) -> HttpResponse {
     let task_id: Uuid = path.into_inner();
 
@@ -39,20 +42,95 @@ pub async fn task_details(
 
     let (task, repo) = conn.get_task_and_repository(&task_id).await;
     let interactions = conn.llm_interactions(&task_id).await;
+    let events = conn.task_events(&task_id).await;
+    let llm_usage = conn.task_llm_usage(&task_id).await;
 
     let interactions = interactions.into_iter().map(Into::into).collect();
+    let events = events.into_iter().map(Into::into).collect();
+
+    let issue_url = github.urls().issue(&repo.github_full_name, task.github_issue_number);
 
     let response = TaskDetails {
         id: task_id.to_string(),
         repo_name: repo.github_full_name,
         issue_number: task.github_issue_number,
         status: task.status.into(),
+        issue_url: issue_url.to_string(),
         interactions,
+        events,
+        llm_usage: llm_usage.into(),
+        replay_of: task.replay_of_task_id.map(|id| id.to_string()),
     };
 
     HttpResponse::Ok().json(response)
 }
 
+/// Start a new task that replays the recorded LLM interactions of the given task.
+#[post("/tasks/{id}/replay")]
+pub async fn task_replay(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let task_id: Uuid = path.into_inner();
+
+    if !auth::user_can_read_task(&db, user.user_id, task_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let mut conn = db.conn().await;
+
+    let (task, repo) = conn.get_task_and_repository(&task_id).await;
+
+    // Only finished tasks have a complete recording
+    if !matches!(task.status, TaskStatus::Completed | TaskStatus::Failed) {
+        return HttpResponse::BadRequest().body("Only finished tasks can be replayed");
+    }
+
+    let (Some(installation_id), Some(github_installation_id)) =
+        (task.installation_id, conn.task_installation_github_id(&task.id).await)
+    else {
+        return HttpResponse::BadRequest().body("Task is not associated with an installation");
+    };
+
+    // Replays run an agent, so they need the same permission as new tasks
+    let github_user_id = conn.get_user(&user.user_id).await.github_id;
+    drop(conn);
+    let create_task = auth::github_user_can_create_task(
+        &db,
+        github_installation_id,
+        &github_user_id,
+        &repo.github_id,
+    )
+    .await;
+    if let Err(denied) = create_task {
+        return HttpResponse::Forbidden().body(format!("You can't replay this task, {denied}."));
+    }
+    let mut conn = db.conn().await;
+
+    let new_task = NewTask {
+        installation_id,
+        repository_id: task.repository_id,
+        created_by_id: user.user_id,
+        github_issue_id: task.github_issue_id,
+        github_issue_number: task.github_issue_number,
+        status: TaskStatus::Queued,
+        agent_config_id: task.agent_config_id,
+        replay_of_task_id: Some(task.id),
+        base_ref: task.base_ref,
+        model: task.model,
+        // Replays start from the base branch, push nothing and don't open pull requests
+        github_pull_request_id: None,
+        follow_up_of_task_id: None,
+        github_command_comment: task.github_command_comment,
+    };
+
+    let replay = conn.add_task(new_task).await;
+    let replay: TaskInfo = conn.get_task_and_repository(&replay.id).await.into();
+
+    HttpResponse::Ok().json(replay)
+}
+
 #[get("/tasks/{id}/logs")]
 pub async fn task_logs(
     user: UserSessionId,
@@ -76,6 +154,59 @@ pub async fn task_logs(
     }
 }
 
+#[get("/tasks/{id}/artifacts")]
+pub async fn list_task_artifacts(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<Uuid>,
+) -> HttpResponse {
+    let task_id = path.into_inner();
+
+    if !auth::user_can_read_task(&db, user.user_id, task_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let artifacts = db.conn().await.task_artifacts(&task_id).await;
+    let artifacts: Vec<TaskArtifact> = artifacts.into_iter().map(Into::into).collect();
+
+    HttpResponse::Ok().json(artifacts)
+}
+
+/// Download an artifact
+///
+/// Artifacts are always served as attachments so that the browser doesn't render uploaded
+/// HTML or SVG on this origin.
+#[get("/tasks/{id}/artifacts/{name}")]
+pub async fn download_task_artifact(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    path: web::Path<(Uuid, String)>,
+    s3: web::Data<S3>,
+) -> HttpResponse {
+    let (task_id, name) = path.into_inner();
+
+    if !auth::user_can_read_task(&db, user.user_id, task_id).await {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let Some(artifact) = db.conn().await.find_task_artifact(&task_id, &name).await else {
+        return HttpResponse::NotFound().finish();
+    };
+
+    match s3.artifact_for_task(&task_id, &artifact.name).await {
+        Ok(contents) => HttpResponse::Ok()
+            .content_type(artifact.content_type)
+            .insert_header(ContentDisposition::attachment(artifact.name))
+            .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+            .body(contents),
+        Err(GetObjectError::NotFound) => HttpResponse::NotFound().finish(),
+        Err(GetObjectError::Unexpected(err)) => {
+            log::error!("Failed to get task artifact: {:?}", err);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct PollQuery {
     after: Uuid,
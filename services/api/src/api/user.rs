@@ -3,10 +3,15 @@ use chrono::offset::Utc;
 
 use auth::UserSessionId;
 use database::{Database, UpdateUser};
+use github::GitHub;
 use user_api::{OpenRouterStatus, UserInfo};
 
 #[get("/user/info")]
-async fn user_info(user: UserSessionId, db: web::Data<Database>) -> HttpResponse {
+async fn user_info(
+    user: UserSessionId,
+    db: web::Data<Database>,
+    github: web::Data<GitHub>,
+) -> HttpResponse {
     let mut conn = db.conn().await;
     let user = conn.get_user(&user.user_id).await;
 
@@ -19,6 +24,7 @@ async fn user_info(user: UserSessionId, db: web::Data<Database>) -> HttpResponse
         email_domain,
         active: user.active,
         on_waitlist: user.joined_waitlist_at.is_some(),
+        github_url: github.urls().web("").to_string(),
     };
 
     HttpResponse::Ok().json(user_info)
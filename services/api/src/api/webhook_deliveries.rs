@@ -0,0 +1,21 @@
+use actix_web::{get, web, HttpResponse};
+
+use auth::UserSessionId;
+use database::Database;
+use user_api::WebhookDelivery;
+
+/// Recent webhook deliveries for the installations the user administers, for debugging
+#[get("/webhook-deliveries")]
+pub async fn list_webhook_deliveries(user: UserSessionId, db: web::Data<Database>) -> HttpResponse {
+    if !auth::user_is_active(&db, user.user_id).await {
+        return HttpResponse::Forbidden().finish();
+    };
+
+    let mut conn = db.conn().await;
+
+    let deliveries = conn.webhook_deliveries_for_admin(user.user_id).await;
+
+    let deliveries: Vec<WebhookDelivery> = deliveries.into_iter().map(Into::into).collect();
+
+    HttpResponse::Ok().json(deliveries)
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use actix_web::dev::ServiceRequest;
-use actix_web::error::ErrorUnauthorized;
+use actix_web::error::{ErrorBadGateway, ErrorConflict, ErrorForbidden, ErrorUnauthorized};
 use actix_web::{Error, HttpMessage};
 use actix_web_httpauth::extractors::basic::BasicAuth;
 use ed25519_compact::PublicKey;
@@ -10,7 +10,7 @@ use jwt_compact::{alg::Ed25519, Token};
 use jwt_compact::{AlgorithmExt, UntrustedToken};
 
 use auth::{AgentSessionId, SessionId};
-use database::Database;
+use database::{Database, TaskStatus};
 
 use git_proxy::{ForwardToRemote, ProxyBehaivor};
 
@@ -40,18 +40,35 @@ pub async fn basic_auth_validator(
     let mut conn = db.conn().await;
     let (task, repo) = conn.get_task_and_repository(&task_id).await;
 
-    let access_token = github.installation_access_token(&github.github_app_jwt()).await;
-    let github_inst = github.with_access(&access_token.token);
+    // Agents of cancelled tasks may still be running, they don't get to push any more
+    if !matches!(task.status, TaskStatus::Running) {
+        return Err((ErrorConflict("Task is not running"), req));
+    }
 
-    let numeric_repo_id = github_inst.repo_numeric_id_by_node_id(&repo.github_id).await;
+    // Replays only read the repository
+    if task.replay_of_task_id.is_some() && is_push(&req) {
+        return Err((ErrorForbidden("Replays can't push"), req));
+    }
 
-    let github_access_token =
-        github.create_scoped_access_token(&github.github_app_jwt(), numeric_repo_id).await;
+    let Some(installation_id) = conn.task_installation_github_id(&task_id).await else {
+        return Err((ErrorUnauthorized("The app is no longer installed"), req));
+    };
 
-    let raw_repo_url = format!("https://github.com/{}", repo.github_full_name);
-    let url = raw_repo_url.parse().expect("Expected valid repo url");
-    // For now we assume that the task id is the branch name
-    let allowed_ref = format!("refs/heads/{}", task.id);
+    let github_access_token = async {
+        let github_inst = github.with_installation_access(installation_id).await?;
+        let numeric_repo_id = github_inst.repo_numeric_id_by_node_id(&repo.github_id).await?;
+        github.create_scoped_access_token(installation_id, numeric_repo_id).await
+    };
+    let github_access_token = match github_access_token.await {
+        Ok(github_access_token) => github_access_token,
+        Err(err) => {
+            log::error!("Failed to create an access token for task {task_id}: {err}");
+            return Err((ErrorBadGateway("GitHub request failed"), req));
+        }
+    };
+
+    let url = github.urls().git_remote(&repo.github_full_name);
+    let allowed_ref = format!("refs/heads/{}", task.branch_name());
 
     req.extensions_mut().insert(ProxyBehaivor {
         allowed_ref,
@@ -66,6 +83,11 @@ pub async fn basic_auth_validator(
     Ok(req)
 }
 
+/// Whether the request is part of a push, which git does through the receive-pack service
+fn is_push(req: &ServiceRequest) -> bool {
+    req.path().ends_with("/git-receive-pack") || req.query_string().contains("git-receive-pack")
+}
+
 fn extract_session_jwt(
     token_str: &str,
     public_key: &PublicKey,
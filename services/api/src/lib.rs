@@ -0,0 +1,136 @@
+//! The API server receives GitHub webhooks and serves the frontend, agents and the proxies.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_governor::{Governor, GovernorConfigBuilder};
+use actix_jwt_auth_middleware::{use_jwt::UseJWTOnApp, Authority, TokenSigner};
+use actix_web::{middleware, web, App, HttpServer};
+use jwt_compact::alg::{Ed25519, SigningKey, VerifyingKey};
+
+use config::Config;
+use database::{Database, Keyring};
+
+mod api;
+mod git_proxy_auth;
+mod metrics;
+mod probes;
+mod webhooks;
+
+use auth::SessionId;
+
+/// Run the API server until it is stopped
+pub async fn run(config: Config) -> std::io::Result<()> {
+    // Connect to database
+    let encryption = &config.encryption;
+    let keyring = Keyring::new(&encryption.master_key, &encryption.previous_master_keys);
+    let db = Database::connect_and_init(config.postgres_url.as_str(), keyring).await;
+
+    // Create API clients
+    let github = github::GitHub::new(config.clone());
+    let s3 = object_storage::S3::new(&config).expect("Failed to create S3 client");
+
+    // Keys for session handling
+    let jwt_private_key = config.jwt_expanded_private_key.clone();
+    let secret_key = move || SigningKey::from_slice(&jwt_private_key).unwrap();
+    let public_key = VerifyingKey::from_slice(&config.jwt_public_key).unwrap();
+
+    // Simple rate limiter
+    let governor_conf = GovernorConfigBuilder::default().finish().unwrap();
+
+    // Handle webhook deliveries in the background
+    actix_web::rt::spawn(webhooks::github::inbox::run(config.clone(), db.clone(), github.clone()));
+
+    let (host, port) = (config.host.clone(), config.port);
+
+    HttpServer::new(move || {
+        let authority = Authority::<SessionId, Ed25519, _, _>::new()
+            .enable_header_tokens(true)
+            .enable_authorization_header(true)
+            .refresh_authorizer(|| async move { Ok(()) })
+            .token_signer(Some(
+                TokenSigner::new()
+                    .signing_key(secret_key())
+                    .algorithm(Ed25519)
+                    .access_token_lifetime(Duration::from_secs(60 * 60))
+                    .refresh_token_lifetime(Duration::from_secs(12 * 60 * 60))
+                    .build()
+                    .expect("Failed to create TokenSigner"),
+            ))
+            .verifying_key(public_key)
+            .build()
+            .expect("Failed to create authority");
+
+        let git_auth_validator = {
+            let db = Arc::new(db.clone());
+            let github = Arc::new(github.clone());
+            let public_key = Arc::new(public_key);
+            move |req, credentials| {
+                git_proxy_auth::basic_auth_validator(
+                    db.clone(),
+                    github.clone(),
+                    public_key.clone(),
+                    req,
+                    credentials,
+                )
+            }
+        };
+
+        App::new()
+            .app_data(web::Data::new(github.clone()))
+            .app_data(web::Data::new(config.clone()))
+            .app_data(web::Data::new(db.clone()))
+            .app_data(web::Data::new(s3.clone()))
+            .service(api::auth::github::auth)
+            .service(api::auth::github::auth_code)
+            .service(webhooks::github::github_hook)
+            .service(probes::readiness)
+            .service(probes::healthz)
+            .service(metrics::metrics)
+            .service(api::auth::openrouter::openrouter_auth_code)
+            .service(git_proxy::scope("/api/agent/git", git_auth_validator))
+            .service(actix_files::Files::new("/static", &config.static_dir))
+            .use_jwt(
+                authority,
+                web::scope("").service(api::auth::openrouter::openrouter_connect).service(
+                    web::scope("/api")
+                        .service(api::user::user_info)
+                        .service(api::user::waitlist_join)
+                        .service(api::user::waitlist_leave)
+                        .service(api::user::openrouter_status)
+                        .service(api::user::disconnect_openrouter)
+                        .service(api::repos::list_repos)
+                        .service(api::repos::get_repo)
+                        .service(api::repos::repos_activate)
+                        .service(api::repos::repos_deactivate)
+                        .service(api::repos::set_trigger_label)
+                        .service(api::repos::set_repo_llm_provider)
+                        .service(api::repos::list_repo_users)
+                        .service(api::repos::add_repo_user)
+                        .service(api::repos::delete_repo_user)
+                        .service(api::installations::list_installations)
+                        .service(api::installations::set_installation_openrouter_key)
+                        .service(api::installations::delete_installation_openrouter_key)
+                        .service(api::installations::set_installation_llm_key_precedence)
+                        .service(api::installations::set_installation_llm_provider)
+                        .service(api::installations::list_llm_providers)
+                        .service(api::tasks::list_tasks)
+                        .service(api::tasks::task_details)
+                        .service(api::tasks::task_logs)
+                        .service(api::tasks::list_task_artifacts)
+                        .service(api::tasks::download_task_artifact)
+                        .service(api::tasks::task_poll)
+                        .service(api::tasks::task_replay)
+                        .service(api::webhook_deliveries::list_webhook_deliveries)
+                        .service(api::agent::scope())
+                        .service(api::chat::scope()),
+                ),
+            )
+            .wrap(middleware::NormalizePath::new(middleware::TrailingSlash::Trim))
+            .wrap(middleware::Logger::default())
+            .wrap(Governor::new(&governor_conf))
+    })
+    .bind((host, port))?
+    .run()
+    .await
+}
@@ -0,0 +1,37 @@
+//! Metrics in the Prometheus text format
+
+use std::fmt::Write;
+
+use actix_web::{get, web, HttpResponse};
+
+use github::GitHub;
+
+#[get("/metrics")]
+async fn metrics(github: web::Data<GitHub>) -> HttpResponse {
+    let mut body = String::new();
+    let rate_limits = github.rate_limits();
+
+    let gauges: [(&str, &str, fn(&github::RateLimit) -> u64); 3] = [
+        ("github_rate_limit_limit", "Requests allowed per GitHub rate limit window", |r| r.limit),
+        (
+            "github_rate_limit_remaining",
+            "Requests remaining in the current GitHub rate limit window",
+            |r| r.remaining,
+        ),
+        (
+            "github_rate_limit_reset_timestamp_seconds",
+            "Unix time at which the GitHub rate limit window resets",
+            |r| r.reset,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        writeln!(body, "# HELP {name} {help}").unwrap();
+        writeln!(body, "# TYPE {name} gauge").unwrap();
+        for rate_limit in &rate_limits {
+            writeln!(body, "{name}{{resource=\"{}\"}} {}", rate_limit.resource, value(rate_limit))
+                .unwrap();
+        }
+    }
+
+    HttpResponse::Ok().content_type("text/plain; version=0.0.4").body(body)
+}
@@ -0,0 +1,164 @@
+//! Commands users give the bot by mentioning it in issue comments, e.g. `@minion solve --base dev`
+
+use thiserror::Error;
+use uuid::Uuid;
+
+mod tests;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Create a task for the issue
+    Solve(TaskOptions),
+    /// Stop the latest task for the issue
+    Cancel,
+    /// Create a task like the latest one for the issue, options override the ones it used
+    Retry(TaskOptions),
+    /// Report the state of the latest task for the issue
+    Status,
+    Help,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TaskOptions {
+    /// Branch the task starts from and the pull request targets
+    pub base: Option<String>,
+    /// Agent config that runs the task
+    pub config: Option<Uuid>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("No command was given.")]
+    MissingCommand,
+    #[error("`{0}` is not a command.")]
+    UnknownCommand(String),
+    #[error("`{0}` is not an option of `{1}`.")]
+    UnknownOption(String, &'static str),
+    #[error("`{0}` needs a value.")]
+    MissingValue(&'static str),
+    #[error("`{0}` was given more than once.")]
+    DuplicateOption(&'static str),
+    #[error("`{0}` is not a valid agent config ID.")]
+    InvalidConfig(String),
+    #[error("Unexpected argument `{0}`.")]
+    UnexpectedArgument(String),
+}
+
+/// Usage shown by `help` and after commands that could not be parsed
+pub fn usage(handle: &str) -> String {
+    format!(
+        "Usage: `{handle} <command> [options]`
+
+Commands:
+- `solve`: work on this issue and open a pull request
+- `cancel`: stop the latest task for this issue
+- `retry`: run the latest task for this issue again
+- `status`: show the state of the latest task for this issue
+- `help`: show this message
+
+Options of `solve` and `retry`:
+- `--base <branch>`: start from and open the pull request against `<branch>`
+- `--config <id>`: run the task with the given agent config of the installation
+- `--model <model>`: ask the agent to use `<model>`"
+    )
+}
+
+/// Parse the command of the first line that mentions the bot
+///
+/// Returns `None` if the bot is not mentioned outside of quotes and code blocks.
+pub fn parse(body: &str, handle: &str) -> Option<Result<Command, ParseError>> {
+    let mut in_code_block = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || trimmed.starts_with('>') {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let mentioned = |word: &str| word.trim_end_matches([':', ',']).eq_ignore_ascii_case(handle);
+        if words.by_ref().any(mentioned) {
+            return Some(parse_words(words));
+        }
+    }
+    None
+}
+
+fn parse_words<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Command, ParseError> {
+    let command = words.next().ok_or(ParseError::MissingCommand)?;
+    match command.to_lowercase().as_str() {
+        "solve" => Ok(Command::Solve(parse_task_options("solve", words)?)),
+        "retry" => Ok(Command::Retry(parse_task_options("retry", words)?)),
+        "cancel" => expect_end("cancel", words).map(|()| Command::Cancel),
+        "status" => expect_end("status", words).map(|()| Command::Status),
+        "help" => Ok(Command::Help),
+        _ => Err(ParseError::UnknownCommand(command.to_owned())),
+    }
+}
+
+fn parse_task_options<'a>(
+    command: &'static str,
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<TaskOptions, ParseError> {
+    let mut options = TaskOptions::default();
+    while let Some(word) = words.next() {
+        // Values are given either as `--option value` or as `--option=value`
+        let (name, inline_value) = match word.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (word, None),
+        };
+        let (option, slot) = match name {
+            "--base" => ("--base", &mut options.base),
+            "--model" => ("--model", &mut options.model),
+            "--config" => {
+                let value = option_value("--config", inline_value, &mut words)?;
+                let id = value.parse().map_err(|_| ParseError::InvalidConfig(value.clone()))?;
+                if options.config.replace(id).is_some() {
+                    return Err(ParseError::DuplicateOption("--config"));
+                }
+                continue;
+            }
+            name if name.starts_with('-') => {
+                return Err(ParseError::UnknownOption(name.to_owned(), command))
+            }
+            _ => return Err(ParseError::UnexpectedArgument(word.to_owned())),
+        };
+        let value = option_value(option, inline_value, &mut words)?;
+        if slot.replace(value).is_some() {
+            return Err(ParseError::DuplicateOption(option));
+        }
+    }
+    Ok(options)
+}
+
+fn option_value<'a>(
+    option: &'static str,
+    inline_value: Option<&str>,
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Result<String, ParseError> {
+    let value = match inline_value {
+        Some(value) => Some(value),
+        None => words.next(),
+    };
+    match value {
+        Some(value) if !value.is_empty() && !value.starts_with("--") => Ok(value.to_owned()),
+        _ => Err(ParseError::MissingValue(option)),
+    }
+}
+
+fn expect_end<'a>(
+    command: &'static str,
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<(), ParseError> {
+    match words.next() {
+        None => Ok(()),
+        Some(word) if word.starts_with('-') => {
+            Err(ParseError::UnknownOption(word.to_owned(), command))
+        }
+        Some(word) => Err(ParseError::UnexpectedArgument(word.to_owned())),
+    }
+}
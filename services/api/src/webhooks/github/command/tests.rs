@@ -0,0 +1,81 @@
+#![cfg(test)]
+
+use uuid::Uuid;
+
+use super::{parse, Command, ParseError, TaskOptions};
+
+const HANDLE: &str = "@minion";
+
+fn parse_ok(body: &str) -> Command {
+    parse(body, HANDLE).expect("bot is mentioned").expect("command is valid")
+}
+
+fn parse_err(body: &str) -> ParseError {
+    parse(body, HANDLE).expect("bot is mentioned").expect_err("command is invalid")
+}
+
+#[test]
+fn test_solve() {
+    assert_eq!(parse_ok("@minion solve"), Command::Solve(TaskOptions::default()));
+    assert_eq!(parse_ok("  @minion   solve  \n"), Command::Solve(TaskOptions::default()));
+    assert_eq!(parse_ok("@Minion: Solve"), Command::Solve(TaskOptions::default()));
+}
+
+#[test]
+fn test_mention_anywhere() {
+    let body = "Looks doable.\n\nThanks @minion solve --base dev\n\nMore text";
+    let options = TaskOptions { base: Some("dev".to_owned()), ..Default::default() };
+    assert_eq!(parse_ok(body), Command::Solve(options));
+}
+
+#[test]
+fn test_no_mention() {
+    assert_eq!(parse("solve", HANDLE), None);
+    assert_eq!(parse("@minions solve", HANDLE), None);
+    assert_eq!(parse("> @minion solve", HANDLE), None);
+    assert_eq!(parse("```\n@minion solve\n```", HANDLE), None);
+}
+
+#[test]
+fn test_options() {
+    let config = Uuid::new_v4();
+    let body = format!("@minion retry --base=release/1.0 --config {config} --model openai/gpt-4o");
+    let options = TaskOptions {
+        base: Some("release/1.0".to_owned()),
+        config: Some(config),
+        model: Some("openai/gpt-4o".to_owned()),
+    };
+    assert_eq!(parse_ok(&body), Command::Retry(options));
+}
+
+#[test]
+fn test_commands_without_options() {
+    assert_eq!(parse_ok("@minion cancel"), Command::Cancel);
+    assert_eq!(parse_ok("@minion status"), Command::Status);
+    assert_eq!(parse_ok("@minion help"), Command::Help);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(parse_err("@minion"), ParseError::MissingCommand);
+    assert_eq!(parse_err("@minion fix"), ParseError::UnknownCommand("fix".to_owned()));
+    assert_eq!(
+        parse_err("@minion solve --branch dev"),
+        ParseError::UnknownOption("--branch".to_owned(), "solve")
+    );
+    assert_eq!(
+        parse_err("@minion cancel --base dev"),
+        ParseError::UnknownOption("--base".to_owned(), "cancel")
+    );
+    assert_eq!(parse_err("@minion solve --base"), ParseError::MissingValue("--base"));
+    assert_eq!(parse_err("@minion solve --base --model x"), ParseError::MissingValue("--base"));
+    assert_eq!(
+        parse_err("@minion solve --model a --model b"),
+        ParseError::DuplicateOption("--model")
+    );
+    assert_eq!(
+        parse_err("@minion solve --config default"),
+        ParseError::InvalidConfig("default".to_owned())
+    );
+    assert_eq!(parse_err("@minion solve this"), ParseError::UnexpectedArgument("this".to_owned()));
+}
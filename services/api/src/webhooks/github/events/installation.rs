@@ -3,15 +3,14 @@ use chrono::Utc;
 use serde::Deserialize;
 
 use config::Config;
-use database::{
-    Database, NewInstallation, NewRepository, NewUser, UpdateInstallationByGitHubId, UserRole,
-};
+use database::{Database, NewInstallation, NewRepository, UpdateInstallationByGitHubId, UserRole};
 use github::{
     types::{Installation, Repo, User},
     AccountType, GitHub,
 };
 
 use super::Event;
+use crate::webhooks::github::members;
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "action")]
@@ -33,7 +32,7 @@ pub enum InstallationEvent {
 
 #[async_trait]
 impl Event for InstallationEvent {
-    async fn handle(self, _config: &Config, db: Database, github: GitHub) {
+    async fn handle(self, _config: &Config, db: Database, github: GitHub) -> github::Result<()> {
         use InstallationEvent::*;
         let mut conn = db.conn().await;
         println!("Installation event received: {:?}", self);
@@ -45,45 +44,33 @@ impl Event for InstallationEvent {
                         created_by_github_id: Some(Some(sender.node_id)),
                     })
                     .await;
-                // TODO: Consider running the following code detached from this event handler
-                let members = if github_installation.account.r#type == AccountType::Organization {
+                if github_installation.account.r#type == AccountType::Organization {
                     let org = github_installation.account.login;
-                    let app_jwt = github.github_app_jwt();
-                    let access_token = github.installation_access_token(&app_jwt).await;
-                    let github = github.with_access(&access_token.token);
-                    let members = github.organization_members(&org).await;
-                    let mut members_with_role = vec![];
-                    for member in members {
-                        let membership = github.organization_membership(&org, &member.login).await;
-                        let role = match membership.role {
-                            github::Role::Admin => UserRole::Admin,
-                            github::Role::Member => UserRole::Member,
-                            github::Role::Unknown => UserRole::Member,
-                        };
-                        members_with_role.push((member, role));
+                    let github = github.with_installation_access(github_installation.id).await?;
+                    let org_members = github.organization_members(&org).await?;
+                    for member in org_members {
+                        let role = members::user_role(&member.role);
+                        members::update_or_add_member(
+                            &mut conn,
+                            installation.id,
+                            member.user,
+                            role,
+                        )
+                        .await;
                     }
-                    members_with_role
                 } else {
-                    vec![(
-                        User {
-                            node_id: github_installation.account.node_id,
-                            login: github_installation.account.login,
-                            name: None,
-                        },
-                        UserRole::Admin,
-                    )]
-                };
-                for (member, role) in members {
-                    let new_user = NewUser {
-                        github_id: member.node_id,
-                        github_email: None,
-                        github_name: None,
-                        github_login: member.login,
-                        github_access_token: None,
-                        github_access_token_expires_at: None,
+                    let owner = User {
+                        node_id: github_installation.account.node_id,
+                        login: github_installation.account.login,
+                        name: None,
                     };
-                    let user = conn.update_or_add_user(new_user).await;
-                    conn.add_installation_user(installation.id, user.id, role).await;
+                    members::update_or_add_member(
+                        &mut conn,
+                        installation.id,
+                        owner,
+                        UserRole::Admin,
+                    )
+                    .await;
                 }
                 conn.update_or_add_installation_repositories(
                     installation.id,
@@ -116,5 +103,6 @@ impl Event for InstallationEvent {
             NewPermissionsAccepted { .. } => (),
             Other => (),
         }
+        Ok(())
     }
 }
@@ -30,13 +30,13 @@ pub struct Data {
 
 #[async_trait]
 impl Event for InstallationRepositoriesEvent {
-    async fn handle(self, _config: &Config, db: Database, _github: GitHub) {
+    async fn handle(self, _config: &Config, db: Database, _github: GitHub) -> github::Result<()> {
         use InstallationRepositoriesEvent::*;
         println!("Installation repositories event received: {:?}", self);
         let mut conn = db.conn().await;
         let Data { repositories_added, repositories_removed, installation } = match self {
             Added(data) | Removed(data) => data,
-            Other => return,
+            Other => return Ok(()),
         };
         let installation = conn.get_installation_by_github_id(installation.id).await.unwrap();
         conn.update_or_add_installation_repositories(
@@ -60,5 +60,6 @@ impl Event for InstallationRepositoriesEvent {
                 .as_slice(),
         )
         .await;
+        Ok(())
     }
 }
@@ -3,58 +3,173 @@ use auth::CreateTask;
 use serde::Deserialize;
 
 use config::Config;
-use database::{Database, NewTask, TaskStatus};
-use github::types::{Comment, Issue, Repo, User};
-use github::GitHub;
+use database::Database;
+use github::types::{Comment, Issue, Repo, SimpleInstallation, User};
+use github::{GitHub, WithAccess};
 
 use super::Event;
+use crate::webhooks::github::command::{self, Command, TaskOptions};
+use crate::webhooks::github::tasks::{
+    self, is_unfinished, not_allowed, queue_follow_up, queue_task, status, task_link, Feedback,
+};
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "action")]
 pub enum IssueCommentEvent {
     #[serde(rename = "created")]
-    Created { repository: Repo, issue: Box<Issue>, comment: Box<Comment>, sender: User },
+    Created {
+        repository: Repo,
+        issue: Box<Issue>,
+        comment: Box<Comment>,
+        sender: User,
+        installation: SimpleInstallation,
+    },
     #[serde(other)]
     Other,
 }
 
 #[async_trait]
 impl Event for IssueCommentEvent {
-    async fn handle(self, config: &Config, db: Database, _github: GitHub) {
-        let IssueCommentEvent::Created { repository, issue, comment, sender } = self else {
-            return;
+    async fn handle(self, config: &Config, db: Database, github: GitHub) -> github::Result<()> {
+        let IssueCommentEvent::Created { repository, issue, comment, sender, installation } = self
+        else {
+            return Ok(());
         };
 
-        println!("{}", comment.body);
-
-        if comment.body != format!("{} solve", config.github_bot_handle) {
-            return;
+        // Replies of the bot mention it as well, e.g. in the usage
+        if sender.login.ends_with("[bot]") {
+            return Ok(());
         }
 
-        println!("Checking if user is authorized");
+        let handle = &config.github_bot_handle;
+        let parsed = command::parse(&comment.body, handle);
 
-        println!("{}", sender.node_id);
-        println!("{}", repository.node_id);
+        // Replies of the issue author that are not commands answer a waiting task
+        if issue.pull_request.is_none() && !matches!(parsed, Some(Ok(_))) {
+            if let Some(task) = tasks::answer_question(&db, &issue, &comment, &sender).await {
+                println!("Answer from {} for task {}", sender.login, task.id);
+                let github = github.with_installation_access(installation.id).await?;
+                tasks::respond(&github, &issue.node_id, &comment.node_id, Ok(None)).await;
+                return Ok(());
+            }
+        }
 
-        let Some(CreateTask { inst_repo, user }) =
-            auth::github_user_can_create_task(&db, &sender.node_id, &repository.node_id).await
-        else {
-            return;
+        let Some(parsed) = parsed else {
+            return Ok(());
         };
 
-        let new_task = NewTask {
-            installation_id: inst_repo.installation_id,
-            repository_id: inst_repo.repository_id,
-            created_by_id: user.id,
-            github_issue_id: issue.node_id,
-            github_issue_number: issue.number,
-            status: TaskStatus::Queued,
-            agent_config_id: None,
+        println!("Command from {}: {:?}", sender.login, parsed);
+
+        let github = github.with_installation_access(installation.id).await?;
+
+        let is_pull_request = issue.pull_request.is_some();
+
+        let response = match parsed {
+            Ok(Command::Help) => Ok(Some(command::usage(handle))),
+            Err(err) if !is_pull_request => Err(format!("{err}\n\n{}", command::usage(handle))),
+            parsed => {
+                let create_task = auth::github_user_can_create_task(
+                    &db,
+                    installation.id,
+                    &sender.node_id,
+                    &repository.node_id,
+                )
+                .await;
+                match (create_task, parsed) {
+                    (Err(denied), _) => Err(not_allowed(&sender, denied)),
+                    (Ok(create_task), Ok(command))
+                        if !is_pull_request
+                            || matches!(command, Command::Cancel | Command::Status) =>
+                    {
+                        run_command(command, config, &db, &github, &issue, &comment, create_task)
+                            .await
+                    }
+                    // Any other mention on a pull request asks for changes
+                    (Ok(create_task), _) => {
+                        let feedback = Feedback {
+                            github_comment_id: comment.node_id.clone(),
+                            author_login: sender.login,
+                            body: comment.body,
+                            path: None,
+                            line: None,
+                            diff_hunk: None,
+                        };
+                        queue_follow_up(config, &db, &issue.node_id, create_task, vec![feedback])
+                            .await
+                            .map(|()| None)
+                    }
+                }
+            }
         };
 
-        println!("Adding task to queue");
+        tasks::respond(&github, &issue.node_id, &comment.node_id, response).await;
+        Ok(())
+    }
+}
 
-        let mut conn = db.conn().await;
-        conn.add_task(new_task).await;
+/// Run a command of an authorized user and return the reply, if any
+///
+/// Returns the reason if the command was rejected.
+async fn run_command(
+    command: Command,
+    config: &Config,
+    db: &Database,
+    github: &WithAccess,
+    issue: &Issue,
+    comment: &Comment,
+    create_task: CreateTask,
+) -> Result<Option<String>, String> {
+    let latest_task = match issue.pull_request {
+        Some(_) => db.conn().await.latest_task_for_pull_request(&issue.node_id).await,
+        None => db.conn().await.latest_task_for_issue(&issue.node_id).await,
+    };
+
+    match command {
+        Command::Solve(options) => {
+            queue_task(config, db, issue, create_task, options, Some(&comment.body))
+                .await
+                .map(|()| None)
+        }
+        Command::Retry(options) => {
+            let Some(task) = latest_task else {
+                return Err("There is no task to retry for this issue.".to_owned());
+            };
+            // Options that are not given again are taken from the retried task
+            let options = TaskOptions {
+                base: options.base.or(task.base_ref),
+                config: options.config.or(task.agent_config_id),
+                model: options.model.or(task.model),
+            };
+            queue_task(config, db, issue, create_task, options, Some(&comment.body))
+                .await
+                .map(|()| None)
+        }
+        Command::Cancel => {
+            let Some(task) = latest_task.filter(is_unfinished) else {
+                return Err("There is no unfinished task for this issue.".to_owned());
+            };
+            let Some(task) = db.conn().await.cancel_task(&task.id).await else {
+                return Err(format!("The {} has already finished.", task_link(config, &task)));
+            };
+            let body = format!("Cancelled the {}.", task_link(config, &task));
+            // Tasks that have started report in their status comment, the others in a reply
+            match &task.github_status_comment_id {
+                Some(comment_id) => match github.update_comment(comment_id, &body).await {
+                    Ok(()) => Ok(None),
+                    Err(err) => {
+                        eprintln!("Failed to update the status comment of {}: {err}", task.id);
+                        Ok(Some(body))
+                    }
+                },
+                None => Ok(Some(body)),
+            }
+        }
+        Command::Status => {
+            let Some(task) = latest_task else {
+                return Err("There is no task for this issue.".to_owned());
+            };
+            Ok(Some(format!("The {} is {}.", task_link(config, &task), status(&task))))
+        }
+        Command::Help => Ok(Some(command::usage(&config.github_bot_handle))),
     }
 }
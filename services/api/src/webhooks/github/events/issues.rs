@@ -4,11 +4,12 @@ use serde::Deserialize;
 use config::Config;
 use database::Database;
 use github::{
-    types::{Issue, Repo, User},
+    types::{Issue, Label, Repo, SimpleInstallation, User},
     GitHub,
 };
 
 use super::Event;
+use crate::webhooks::github::tasks::{self, not_allowed, queue_task};
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "action")]
@@ -16,11 +17,76 @@ use super::Event;
 pub enum IssuesEvent {
     #[serde(rename = "opened")]
     Opened { repository: Repo, issue: Box<Issue>, sender: User },
+    #[serde(rename = "labeled")]
+    Labeled {
+        repository: Repo,
+        issue: Box<Issue>,
+        label: Label,
+        sender: User,
+        installation: SimpleInstallation,
+    },
+    #[serde(rename = "assigned")]
+    Assigned {
+        repository: Repo,
+        issue: Box<Issue>,
+        assignee: User,
+        sender: User,
+        installation: SimpleInstallation,
+    },
     #[serde(other)]
     Other,
 }
 
 #[async_trait]
 impl Event for IssuesEvent {
-    async fn handle(self, _config: &Config, _db: Database, _github: GitHub) {}
+    async fn handle(self, config: &Config, db: Database, github: GitHub) -> github::Result<()> {
+        use IssuesEvent::*;
+
+        let (repository, issue, sender, installation) = match self {
+            Labeled { repository, issue, label, sender, installation } => {
+                let inst_repo = db
+                    .conn()
+                    .await
+                    .installation_repository_by_github_id(installation.id, &repository.node_id)
+                    .await;
+                let trigger_label = inst_repo.and_then(|inst_repo| inst_repo.trigger_label);
+                // Label names are case-insensitive on GitHub
+                if !trigger_label.is_some_and(|trigger| trigger.eq_ignore_ascii_case(&label.name)) {
+                    return Ok(());
+                }
+                (repository, issue, sender, installation)
+            }
+            Assigned { repository, issue, assignee, sender, installation } => {
+                let bot_login = config.github_bot_handle.trim_start_matches('@');
+                if !assignee.login.eq_ignore_ascii_case(bot_login) {
+                    return Ok(());
+                }
+                (repository, issue, sender, installation)
+            }
+            Opened { .. } | Other => return Ok(()),
+        };
+
+        println!("Issue {} triggered by {}", issue.number, sender.login);
+
+        let create_task = auth::github_user_can_create_task(
+            &db,
+            installation.id,
+            &sender.node_id,
+            &repository.node_id,
+        )
+        .await;
+        let response = match create_task {
+            Ok(create_task) => {
+                queue_task(config, &db, &issue, create_task, Default::default(), None)
+                    .await
+                    .map(|()| None)
+            }
+            Err(denied) => Err(not_allowed(&sender, denied)),
+        };
+
+        // There is no comment to react to, the issue itself asked for the task
+        let github = github.with_installation_access(installation.id).await?;
+        tasks::respond(&github, &issue.node_id, &issue.node_id, response).await;
+        Ok(())
+    }
 }
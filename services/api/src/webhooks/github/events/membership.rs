@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use config::Config;
+use database::Database;
+use github::types::{Organization, SimpleInstallation, User};
+use github::GitHub;
+
+use super::Event;
+use crate::webhooks::github::members;
+
+/// Team membership changes
+///
+/// Teams don't grant access themselves, but a change in teams often comes with a change of the
+/// organization role, so the member's role is refreshed from GitHub.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action")]
+pub enum MembershipEvent {
+    #[serde(rename = "added")]
+    Added { installation: SimpleInstallation, organization: Organization, member: User },
+    #[serde(rename = "removed")]
+    Removed { installation: SimpleInstallation, organization: Organization, member: User },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait]
+impl Event for MembershipEvent {
+    async fn handle(self, _config: &Config, db: Database, github: GitHub) -> github::Result<()> {
+        use MembershipEvent::*;
+
+        let (installation, organization, member) = match self {
+            Added { installation, organization, member }
+            | Removed { installation, organization, member } => {
+                (installation, organization, member)
+            }
+            Other => return Ok(()),
+        };
+
+        let mut conn = db.conn().await;
+        let Some(installation) = conn.get_installation_by_github_id(installation.id).await else {
+            return Ok(());
+        };
+
+        println!("Refreshing the organization role of {}", member.login);
+
+        let membership = github
+            .with_installation_access(installation.github_id)
+            .await?
+            .organization_membership(&organization.login, &member.login)
+            .await?;
+        members::sync_member(&mut conn, installation.id, member, membership).await;
+        Ok(())
+    }
+}
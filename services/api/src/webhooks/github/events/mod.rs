@@ -1,6 +1,7 @@
 use actix_web::web::Bytes;
 use async_trait::async_trait;
 use serde::Deserialize;
+use thiserror::Error;
 
 use config::Config;
 use database::Database;
@@ -10,25 +11,54 @@ mod installation;
 mod installation_repositories;
 mod issue_comment;
 mod issues;
+mod membership;
+mod organization;
 mod ping;
+mod pull_request_review;
+mod pull_request_review_comment;
+mod repository;
 
 pub use installation::*;
 pub use installation_repositories::*;
 pub use issue_comment::*;
 pub use issues::*;
+pub use membership::*;
+pub use organization::*;
 pub use ping::*;
+pub use pull_request_review::*;
+pub use pull_request_review_comment::*;
+pub use repository::*;
+
+/// Why a webhook delivery could not be handled
+#[derive(Debug, Error)]
+pub enum HandleError {
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(#[from] serde_json::Error),
+    #[error("GitHub request failed: {0}")]
+    GitHub(#[from] github::Error),
+}
+
+impl HandleError {
+    /// Whether handling the delivery again may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            HandleError::InvalidPayload(_) => false,
+            HandleError::GitHub(err) => err.is_transient(),
+        }
+    }
+}
 
 #[async_trait]
 pub trait Event: for<'de> Deserialize<'de> {
-    async fn handle(self, config: &Config, db: Database, github: GitHub);
+    async fn handle(self, config: &Config, db: Database, github: GitHub) -> github::Result<()>;
     async fn handle_bytes(
         bytes: &Bytes,
         config: &Config,
         db: Database,
         github: GitHub,
-    ) -> serde_json::Result<()> {
+    ) -> Result<(), HandleError> {
         let body = Self::parse(bytes)?;
-        body.handle(config, db, github).await;
+        body.handle(config, db, github).await?;
         Ok(())
     }
     fn parse(bytes: &Bytes) -> serde_json::Result<Self> {
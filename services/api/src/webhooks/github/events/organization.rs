@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use config::Config;
+use database::Database;
+use github::types::{Invitation, Membership, SimpleInstallation};
+use github::GitHub;
+
+use super::Event;
+use crate::webhooks::github::members;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action")]
+pub enum OrganizationEvent {
+    #[serde(rename = "member_added")]
+    MemberAdded { installation: SimpleInstallation, membership: Membership },
+    #[serde(rename = "member_removed")]
+    MemberRemoved { installation: SimpleInstallation, membership: Membership },
+    #[serde(rename = "member_invited")]
+    MemberInvited { installation: SimpleInstallation, invitation: Invitation },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait]
+impl Event for OrganizationEvent {
+    async fn handle(self, _config: &Config, db: Database, _github: GitHub) -> github::Result<()> {
+        use OrganizationEvent::*;
+
+        let installation_id = match &self {
+            MemberAdded { installation, .. }
+            | MemberRemoved { installation, .. }
+            | MemberInvited { installation, .. } => installation.id,
+            Other => return Ok(()),
+        };
+
+        let mut conn = db.conn().await;
+        let Some(installation) = conn.get_installation_by_github_id(installation_id).await else {
+            return Ok(());
+        };
+
+        match self {
+            MemberAdded { membership, .. } => {
+                let Some(member) = membership.user else {
+                    return Ok(());
+                };
+                println!("Organization member {} added", member.login);
+                let role = members::user_role(&membership.role);
+                members::update_or_add_member(&mut conn, installation.id, member, role).await;
+            }
+            MemberRemoved { membership, .. } => {
+                let Some(member) = membership.user else {
+                    return Ok(());
+                };
+                println!("Organization member {} removed", member.login);
+                members::remove_member(&mut conn, installation.id, &member).await;
+            }
+            // Invited users become members once they accept, which is a `member_added` event
+            MemberInvited { invitation, .. } => {
+                let login = invitation.login.as_deref().unwrap_or("<email>");
+                println!("Organization member {} invited", login);
+            }
+            Other => (),
+        }
+        Ok(())
+    }
+}
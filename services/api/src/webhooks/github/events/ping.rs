@@ -12,5 +12,7 @@ pub struct PingEvent {}
 
 #[async_trait]
 impl Event for PingEvent {
-    async fn handle(self, _config: &Config, _db: Database, _github: GitHub) {}
+    async fn handle(self, _config: &Config, _db: Database, _github: GitHub) -> github::Result<()> {
+        Ok(())
+    }
 }
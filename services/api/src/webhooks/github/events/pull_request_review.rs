@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use config::Config;
+use database::Database;
+use github::types::{PullRequest, Repo, Review, SimpleInstallation, User};
+use github::GitHub;
+
+use super::Event;
+use crate::webhooks::github::command;
+use crate::webhooks::github::tasks::{self, not_allowed, queue_follow_up, Feedback};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action")]
+pub enum PullRequestReviewEvent {
+    #[serde(rename = "submitted")]
+    Submitted {
+        repository: Repo,
+        pull_request: PullRequest,
+        review: Review,
+        sender: User,
+        installation: SimpleInstallation,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait]
+impl Event for PullRequestReviewEvent {
+    async fn handle(self, config: &Config, db: Database, github: GitHub) -> github::Result<()> {
+        let PullRequestReviewEvent::Submitted {
+            repository,
+            pull_request,
+            review,
+            sender,
+            installation,
+        } = self
+        else {
+            return Ok(());
+        };
+
+        if sender.login.ends_with("[bot]") {
+            return Ok(());
+        }
+
+        // Comments of the review that mention the bot are handled on their own
+        let review_body = review.body.unwrap_or_default();
+        if command::parse(&review_body, &config.github_bot_handle).is_none() {
+            return Ok(());
+        }
+
+        let review_id = review.node_id;
+
+        println!("Review of pull request {} by {}", pull_request.number, sender.login);
+
+        let github = github.with_installation_access(installation.id).await?;
+
+        let create_task = auth::github_user_can_create_task(
+            &db,
+            installation.id,
+            &sender.node_id,
+            &repository.node_id,
+        )
+        .await;
+        let response = match create_task {
+            Ok(create_task) => {
+                let review_comments = github
+                    .review_comments(&repository.full_name, pull_request.number, review.id)
+                    .await?;
+
+                let review_feedback = Feedback {
+                    github_comment_id: review_id.clone(),
+                    author_login: review.user.login,
+                    body: review_body,
+                    path: None,
+                    line: None,
+                    diff_hunk: None,
+                };
+                let feedback = std::iter::once(review_feedback)
+                    .chain(review_comments.into_iter().map(Feedback::from))
+                    .collect();
+
+                queue_follow_up(config, &db, &pull_request.node_id, create_task, feedback)
+                    .await
+                    .map(|()| None)
+            }
+            Err(denied) => Err(not_allowed(&sender, denied)),
+        };
+
+        tasks::respond(&github, &pull_request.node_id, &review_id, response).await;
+        Ok(())
+    }
+}
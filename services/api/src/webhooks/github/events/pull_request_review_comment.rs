@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use config::Config;
+use database::Database;
+use github::types::{PullRequest, Repo, ReviewComment, SimpleInstallation, User};
+use github::GitHub;
+
+use super::Event;
+use crate::webhooks::github::command;
+use crate::webhooks::github::tasks::{self, not_allowed, queue_follow_up};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action")]
+pub enum PullRequestReviewCommentEvent {
+    #[serde(rename = "created")]
+    Created {
+        repository: Repo,
+        pull_request: PullRequest,
+        comment: Box<ReviewComment>,
+        sender: User,
+        installation: SimpleInstallation,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait]
+impl Event for PullRequestReviewCommentEvent {
+    async fn handle(self, config: &Config, db: Database, github: GitHub) -> github::Result<()> {
+        let PullRequestReviewCommentEvent::Created {
+            repository,
+            pull_request,
+            comment,
+            sender,
+            installation,
+        } = self
+        else {
+            return Ok(());
+        };
+
+        if sender.login.ends_with("[bot]") {
+            return Ok(());
+        }
+
+        if command::parse(&comment.body, &config.github_bot_handle).is_none() {
+            return Ok(());
+        }
+
+        println!("Review comment on pull request {} by {}", pull_request.number, sender.login);
+
+        let github = github.with_installation_access(installation.id).await?;
+
+        let create_task = auth::github_user_can_create_task(
+            &db,
+            installation.id,
+            &sender.node_id,
+            &repository.node_id,
+        )
+        .await;
+        let comment_id = comment.node_id.clone();
+        let response = match create_task {
+            Ok(create_task) => {
+                let feedback = vec![(*comment).into()];
+                queue_follow_up(config, &db, &pull_request.node_id, create_task, feedback)
+                    .await
+                    .map(|()| None)
+            }
+            Err(denied) => Err(not_allowed(&sender, denied)),
+        };
+
+        tasks::respond(&github, &pull_request.node_id, &comment_id, response).await;
+        Ok(())
+    }
+}
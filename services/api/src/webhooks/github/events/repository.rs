@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use config::Config;
+use database::{Database, Update};
+use github::types::{Repo, User};
+use github::GitHub;
+
+use super::Event;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action")]
+#[allow(dead_code)]
+pub enum RepositoryEvent {
+    #[serde(rename = "renamed")]
+    Renamed { repository: Repo, sender: User },
+    #[serde(rename = "transferred")]
+    Transferred { repository: Repo, sender: User },
+    #[serde(rename = "privatized")]
+    Privatized { repository: Repo, sender: User },
+    #[serde(rename = "publicized")]
+    Publicized { repository: Repo, sender: User },
+    #[serde(rename = "archived")]
+    Archived { repository: Repo, sender: User },
+    #[serde(rename = "deleted")]
+    Deleted { repository: Repo, sender: User },
+    #[serde(other)]
+    Other,
+}
+
+#[async_trait]
+impl Event for RepositoryEvent {
+    async fn handle(self, _config: &Config, db: Database, _github: GitHub) -> github::Result<()> {
+        use RepositoryEvent::*;
+
+        let (repository, deactivate) = match self {
+            Renamed { repository, .. }
+            | Transferred { repository, .. }
+            | Privatized { repository, .. }
+            | Publicized { repository, .. } => (repository, false),
+            // Tasks can no longer push to archived or deleted repositories
+            Archived { repository, .. } | Deleted { repository, .. } => (repository, true),
+            Other => return Ok(()),
+        };
+
+        let mut conn = db.conn().await;
+        let Some(repo) = conn.get_repository_by_github_id(&repository.node_id).await else {
+            return Ok(());
+        };
+
+        println!("Repository {} changed", repo.github_full_name);
+
+        // The payload has the current name and visibility whatever the action
+        let update =
+            repo.update().github_full_name(repository.full_name).github_private(repository.private);
+        conn.update_repository(update).await;
+
+        if deactivate {
+            conn.deactivate_installation_repositories(repo.id).await;
+        }
+        Ok(())
+    }
+}
@@ -11,6 +11,11 @@ pub enum XGitHubEvent {
     InstallationRepositories,
     Issues,
     IssueComment,
+    Organization,
+    Membership,
+    PullRequestReview,
+    PullRequestReviewComment,
+    Repository,
     Other,
 }
 
@@ -24,6 +29,11 @@ impl XGitHubEvent {
             InstallationRepositories => "installation_repositories",
             Issues => "issues",
             IssueComment => "issue_comment",
+            Organization => "organization",
+            Membership => "membership",
+            PullRequestReview => "pull_request_review",
+            PullRequestReviewComment => "pull_request_review_comment",
+            Repository => "repository",
             Other => "",
         }
     }
@@ -51,6 +61,11 @@ impl FromStr for XGitHubEvent {
             "installation_repositories" => Ok(InstallationRepositories),
             "issues" => Ok(Issues),
             "issue_comment" => Ok(IssueComment),
+            "organization" => Ok(Organization),
+            "membership" => Ok(Membership),
+            "pull_request_review" => Ok(PullRequestReview),
+            "pull_request_review_comment" => Ok(PullRequestReviewComment),
+            "repository" => Ok(Repository),
             _ => Ok(Other),
         }
     }
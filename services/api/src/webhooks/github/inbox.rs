@@ -0,0 +1,123 @@
+//! Background worker that handles the deliveries stored in the webhook inbox
+
+use std::time::Duration;
+
+use actix_web::rt::task::JoinError;
+use actix_web::web;
+use chrono::Utc;
+
+use config::Config;
+use database::{Database, Update, WebhookDelivery, WebhookDeliveryStatus};
+use github::GitHub;
+
+use super::events::*;
+use super::headers::XGitHubEvent;
+
+/// Interval in which the inbox is checked for due deliveries
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Deliveries that failed this often are not retried anymore
+const MAX_ATTEMPTS: i32 = 5;
+/// Delay before the first retry, it doubles with every further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+/// Deliveries that are processed for longer than this were interrupted
+const STALE_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Handle the deliveries in the inbox until the server stops
+pub async fn run(config: Config, db: Database, github: GitHub) {
+    loop {
+        let stale_before = Utc::now() - STALE_AFTER;
+        let requeued = db.conn().await.requeue_stale_webhook_deliveries(stale_before).await;
+        if requeued > 0 {
+            log::warn!("Requeued {requeued} interrupted webhook deliveries");
+        }
+
+        while let Some(delivery) = db.conn().await.receive_webhook_delivery().await {
+            process(delivery, &config, &db, &github).await;
+        }
+
+        actix_web::rt::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn process(delivery: WebhookDelivery, config: &Config, db: &Database, github: &GitHub) {
+    let handled = {
+        let event = delivery.event.parse().unwrap_or(XGitHubEvent::Other);
+        let body = web::Bytes::from(serde_json::to_vec(&delivery.payload).unwrap());
+        let config = config.clone();
+        let db = db.clone();
+        let github = github.clone();
+        // Running handlers as a task contains panics
+        actix_web::rt::spawn(async move { handle_event(event, &body, &config, db, github).await })
+            .await
+    };
+
+    let (error, retryable) = match handled {
+        Ok(Ok(())) => (None, false),
+        Ok(Err(err)) => (Some(err.to_string()), err.is_retryable()),
+        Err(err) => (Some(panic_message(err)), true),
+    };
+
+    let update = match &error {
+        None => delivery.update().status(WebhookDeliveryStatus::Processed),
+        Some(error) if !retryable || delivery.attempts >= MAX_ATTEMPTS => {
+            log::error!("Giving up on delivery {}: {error}", delivery.github_delivery_id);
+            delivery.update().status(WebhookDeliveryStatus::Dead)
+        }
+        Some(error) => {
+            log::error!("Failed to process delivery {}: {error}", delivery.github_delivery_id);
+            delivery
+                .update()
+                .status(WebhookDeliveryStatus::Failed)
+                .next_attempt_at(Utc::now() + retry_delay(delivery.attempts))
+        }
+    };
+    db.conn().await.update_webhook_delivery(update.error(error)).await;
+}
+
+async fn handle_event(
+    event: XGitHubEvent,
+    body: &web::Bytes,
+    config: &Config,
+    db: Database,
+    github: GitHub,
+) -> Result<(), HandleError> {
+    use XGitHubEvent::*;
+
+    match event {
+        Ping => PingEvent::handle_bytes(body, config, db, github).await,
+        Installation => InstallationEvent::handle_bytes(body, config, db, github).await,
+        InstallationRepositories => {
+            InstallationRepositoriesEvent::handle_bytes(body, config, db, github).await
+        }
+        Issues => IssuesEvent::handle_bytes(body, config, db, github).await,
+        IssueComment => IssueCommentEvent::handle_bytes(body, config, db, github).await,
+        Organization => OrganizationEvent::handle_bytes(body, config, db, github).await,
+        Membership => MembershipEvent::handle_bytes(body, config, db, github).await,
+        PullRequestReview => PullRequestReviewEvent::handle_bytes(body, config, db, github).await,
+        PullRequestReviewComment => {
+            PullRequestReviewCommentEvent::handle_bytes(body, config, db, github).await
+        }
+        Repository => RepositoryEvent::handle_bytes(body, config, db, github).await,
+        Other => Ok(()),
+    }
+}
+
+/// Exponential backoff after the given number of attempts
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    RETRY_BASE_DELAY.saturating_mul(2u32.pow(exponent)).min(RETRY_MAX_DELAY)
+}
+
+fn panic_message(err: JoinError) -> String {
+    let Ok(panic) = err.try_into_panic() else {
+        return "Handler was cancelled".to_owned();
+    };
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        format!("Handler panicked: {message}")
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        format!("Handler panicked: {message}")
+    } else {
+        "Handler panicked".to_owned()
+    }
+}
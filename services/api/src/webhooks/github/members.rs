@@ -0,0 +1,59 @@
+//! Keeping the members of installations and their roles in sync with GitHub
+
+use uuid::Uuid;
+
+use database::{Conn, NewUser, UserRole};
+use github::types::{Membership, MembershipState, User};
+
+pub fn user_role(role: &github::Role) -> UserRole {
+    match role {
+        github::Role::Admin => UserRole::Admin,
+        github::Role::Member => UserRole::Member,
+        github::Role::Unknown => UserRole::Member,
+    }
+}
+
+/// Add the user to the installation with the given role, or update their role
+pub async fn update_or_add_member(
+    conn: &mut Conn<'_>,
+    installation_id: Uuid,
+    member: User,
+    role: UserRole,
+) {
+    let new_user = NewUser {
+        github_id: member.node_id,
+        github_email: None,
+        github_name: None,
+        github_login: member.login,
+        github_access_token: None,
+        github_access_token_expires_at: None,
+    };
+    let user = conn.update_or_add_user(new_user).await;
+    conn.update_or_add_installation_user(installation_id, user.id, role).await;
+}
+
+pub async fn remove_member(conn: &mut Conn<'_>, installation_id: Uuid, member: &User) {
+    // Users that never signed in and were never a member are unknown
+    if let Some(user) = conn.get_user_by_github_id(&member.node_id).await {
+        conn.remove_installation_user(installation_id, user.id).await;
+    }
+}
+
+/// Bring the installation user in line with their organization membership
+///
+/// Pending invitations and users that are no longer members of the organization remove the user
+/// from the installation.
+pub async fn sync_member(
+    conn: &mut Conn<'_>,
+    installation_id: Uuid,
+    member: User,
+    membership: Option<Membership>,
+) {
+    match membership {
+        Some(membership) if membership.state == MembershipState::Active => {
+            let role = user_role(&membership.role);
+            update_or_add_member(conn, installation_id, member, role).await;
+        }
+        _ => remove_member(conn, installation_id, &member).await,
+    }
+}
@@ -1,16 +1,20 @@
 use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::Value;
 
+mod command;
 mod events;
 mod headers;
+pub mod inbox;
+mod members;
+mod tasks;
 mod verify_signature;
 
-use events::*;
 use headers::XGitHubEvent;
 use verify_signature::verify_github_signature;
 
 use config::Config;
-use database::Database;
-use github::GitHub;
+use database::{Database, NewWebhookDelivery, WebhookDeliveryStatus};
 
 #[post("/webhooks/github")]
 pub async fn github_hook(
@@ -19,7 +23,6 @@ pub async fn github_hook(
     req: HttpRequest,
     config: web::Data<Config>,
     db: web::Data<Database>,
-    github: web::Data<GitHub>,
 ) -> impl Responder {
     let signature_header = match req.headers().get("X-Hub-Signature-256") {
         Some(sig) => sig.to_str().unwrap_or_default(),
@@ -34,23 +37,47 @@ pub async fn github_hook(
         return HttpResponse::Unauthorized().finish();
     }
 
+    let Some(delivery_id) = header_str(&req, "X-GitHub-Delivery") else {
+        log::warn!("Missing X-GitHub-Delivery header");
+        return HttpResponse::BadRequest().finish();
+    };
+
     println!("GitHub hook fired {event_header:?}");
 
-    use XGitHubEvent::*;
-    let db = db.get_ref().clone();
-    let github = github.get_ref().clone();
-    let config = config.get_ref();
+    let Ok(payload) = serde_json::from_slice::<Value>(&body) else {
+        log::warn!("Invalid payload for delivery {delivery_id}");
+        return HttpResponse::BadRequest().finish();
+    };
 
-    match event_header.into_inner() {
-        Ping => PingEvent::handle_bytes(&body, config, db, github).await.unwrap(),
-        Installation => InstallationEvent::handle_bytes(&body, config, db, github).await.unwrap(),
-        InstallationRepositories => {
-            InstallationRepositoriesEvent::handle_bytes(&body, config, db, github).await.unwrap()
-        }
-        Issues => IssuesEvent::handle_bytes(&body, config, db, github).await.unwrap(),
-        IssueComment => IssueCommentEvent::handle_bytes(&body, config, db, github).await.unwrap(),
-        Other => (),
+    // The inbox worker handles the delivery, redeliveries that are already in the inbox are skipped
+    let summary: DeliverySummary = serde_json::from_value(payload.clone()).unwrap_or_default();
+    let new_delivery = NewWebhookDelivery {
+        github_delivery_id: delivery_id.to_owned(),
+        event: header_str(&req, "X-GitHub-Event").unwrap_or_default().to_owned(),
+        action: summary.action,
+        installation_github_id: summary.installation.map(|installation| installation.id),
+        status: WebhookDeliveryStatus::Queued,
+        payload: Some(payload),
+    };
+    if db.conn().await.enqueue_webhook_delivery(new_delivery).await.is_none() {
+        log::info!("Skipping delivery {delivery_id}, it is already in the inbox");
     }
 
     HttpResponse::Ok().finish()
 }
+
+/// The fields of a webhook payload that are recorded with the delivery
+#[derive(Default, Deserialize)]
+struct DeliverySummary {
+    action: Option<String>,
+    installation: Option<InstallationId>,
+}
+
+#[derive(Deserialize)]
+struct InstallationId {
+    id: i64,
+}
+
+fn header_str<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|value| value.to_str().ok())
+}
@@ -0,0 +1,238 @@
+//! Queueing tasks for issues and replying to the users who asked for them
+
+use auth::{CreateTask, CreateTaskDenied};
+use config::Config;
+use database::{Database, NewTask, NewTaskReviewComment, Task, TaskStatus};
+use github::types::{Comment, Issue, Reaction, ReviewComment, User};
+use github::WithAccess;
+
+use super::command::TaskOptions;
+
+/// Queue a task for the issue unless one is already queued or running
+///
+/// Returns the reply to the user if no task was queued. Queued tasks are not replied to, the
+/// dispatcher adds the status comment once the task starts. `command_comment` is the body of the
+/// comment that asked for the task, if it was asked for with a command.
+pub async fn queue_task(
+    config: &Config,
+    db: &Database,
+    issue: &Issue,
+    CreateTask { inst_repo, user }: CreateTask,
+    options: TaskOptions,
+    command_comment: Option<&str>,
+) -> Result<(), String> {
+    let mut conn = db.conn().await;
+
+    if let Some(task) = conn.latest_task_for_issue(&issue.node_id).await.filter(is_unfinished) {
+        return Err(format!(
+            "The {} for this issue is still {}.",
+            task_link(config, &task),
+            status(&task)
+        ));
+    }
+
+    if let Some(agent_config_id) = options.config {
+        // Configs hold registry credentials, the configs of other installations count as missing
+        let repository = conn.get_repository(&inst_repo.repository_id).await;
+        let usable = match conn.find_agent_config(&agent_config_id).await {
+            Some(agent_config) => {
+                repository.default_agent_config_id == Some(agent_config.id)
+                    || agent_config.installation_id == Some(inst_repo.installation_id)
+            }
+            None => false,
+        };
+        if !usable {
+            return Err(format!("There is no agent config `{agent_config_id}`."));
+        }
+    }
+
+    let new_task = NewTask {
+        installation_id: inst_repo.installation_id,
+        repository_id: inst_repo.repository_id,
+        created_by_id: user.id,
+        github_issue_id: issue.node_id.clone(),
+        github_issue_number: issue.number,
+        status: TaskStatus::Queued,
+        agent_config_id: options.config,
+        replay_of_task_id: None,
+        base_ref: options.base,
+        model: options.model,
+        github_pull_request_id: None,
+        follow_up_of_task_id: None,
+        github_command_comment: command_comment.map(ToOwned::to_owned),
+    };
+
+    println!("Adding task to queue");
+
+    conn.add_task(new_task).await;
+    Ok(())
+}
+
+/// A review comment, review or pull request comment asking for changes
+pub struct Feedback {
+    pub github_comment_id: String,
+    pub author_login: String,
+    pub body: String,
+    pub path: Option<String>,
+    pub line: Option<i32>,
+    pub diff_hunk: Option<String>,
+}
+
+impl Feedback {
+    fn for_task(self, task: &Task) -> NewTaskReviewComment {
+        NewTaskReviewComment {
+            task_id: task.id,
+            github_comment_id: self.github_comment_id,
+            author_login: self.author_login,
+            body: self.body,
+            path: self.path,
+            line: self.line,
+            diff_hunk: self.diff_hunk,
+        }
+    }
+}
+
+impl From<ReviewComment> for Feedback {
+    fn from(comment: ReviewComment) -> Self {
+        Feedback {
+            github_comment_id: comment.node_id,
+            author_login: comment.user.login,
+            body: comment.body,
+            path: Some(comment.path),
+            // Comments on lines that changed since keep their original line
+            line: comment.line.or(comment.original_line),
+            diff_hunk: Some(comment.diff_hunk),
+        }
+    }
+}
+
+/// Queue a follow-up task that addresses feedback on a pull request of the app
+///
+/// Feedback that arrives while a follow-up task is still queued is added to that task. Returns
+/// the reply to the user if no task was queued.
+pub async fn queue_follow_up(
+    config: &Config,
+    db: &Database,
+    pull_request_id: &str,
+    CreateTask { inst_repo, user }: CreateTask,
+    feedback: Vec<Feedback>,
+) -> Result<(), String> {
+    let mut conn = db.conn().await;
+
+    let Some(task) = conn.latest_task_for_pull_request(pull_request_id).await else {
+        return Err("There is no task for this pull request.".to_owned());
+    };
+
+    let follow_up = match task.status {
+        TaskStatus::Running | TaskStatus::WaitingForInput => {
+            return Err(format!(
+                "The {} for this pull request is still {}, please comment again once it has \
+                 finished.",
+                task_link(config, &task),
+                status(&task)
+            ));
+        }
+        TaskStatus::Queued if task.follow_up_of_task_id.is_some() => task,
+        _ => {
+            let new_task = NewTask {
+                installation_id: inst_repo.installation_id,
+                repository_id: inst_repo.repository_id,
+                created_by_id: user.id,
+                github_issue_id: task.github_issue_id.clone(),
+                github_issue_number: task.github_issue_number,
+                status: TaskStatus::Queued,
+                agent_config_id: task.agent_config_id,
+                replay_of_task_id: None,
+                base_ref: task.base_ref.clone(),
+                model: task.model.clone(),
+                github_pull_request_id: Some(pull_request_id.to_owned()),
+                follow_up_of_task_id: Some(task.follow_up_of_task_id.unwrap_or(task.id)),
+                // The feedback is passed on as review comments
+                github_command_comment: None,
+            };
+
+            println!("Adding follow-up task to queue");
+
+            conn.add_task(new_task).await
+        }
+    };
+
+    let comments = feedback.into_iter().map(|feedback| feedback.for_task(&follow_up)).collect();
+    conn.add_task_review_comments(comments).await;
+    Ok(())
+}
+
+/// Answer the questions of the latest task for the issue and queue it again
+///
+/// Only comments of the issue author on a task that is waiting for input are answers. Returns
+/// the queued task.
+pub async fn answer_question(
+    db: &Database,
+    issue: &Issue,
+    comment: &Comment,
+    sender: &User,
+) -> Option<Task> {
+    let is_author = issue.user.as_ref().is_some_and(|author| author.node_id == sender.node_id);
+    if !is_author {
+        return None;
+    }
+
+    let mut conn = db.conn().await;
+    let task = conn.latest_task_for_issue(&issue.node_id).await?;
+    if !matches!(task.status, TaskStatus::WaitingForInput) {
+        return None;
+    }
+    // Answered first, so that the task sees the answer once the dispatcher receives it
+    conn.answer_task_questions(&task.id, &comment.body, &sender.login).await;
+    conn.requeue_task(&task.id).await
+}
+
+/// Reply to a user who is not allowed to create tasks for the repository
+pub fn not_allowed(sender: &User, denied: CreateTaskDenied) -> String {
+    format!("@{} you can't create tasks for this repository, {denied}.", sender.login)
+}
+
+/// Acknowledge the comment, issue or review a user asked the app with
+///
+/// Accepted requests get 👀 and the reply, if any. Rejected requests get 👎 and the reason.
+/// Replies are comments on `reply_to`, the issue or pull request. Failures are only logged, the
+/// request was handled and retrying the delivery would handle it again.
+pub async fn respond(
+    github: &WithAccess,
+    reply_to: &str,
+    request_id: &str,
+    response: Result<Option<String>, String>,
+) {
+    let (reaction, reply) = match response {
+        Ok(reply) => (Reaction::Eyes, reply),
+        Err(reason) => (Reaction::ThumbsDown, Some(reason)),
+    };
+    if let Err(err) = github.add_reaction(request_id, reaction).await {
+        log::error!("Failed to react to {request_id}: {err}");
+    }
+    if let Some(body) = reply {
+        if let Err(err) = github.add_comment(reply_to, &body).await {
+            log::error!("Failed to reply to {request_id}: {err}");
+        }
+    }
+}
+
+pub fn is_unfinished(task: &Task) -> bool {
+    matches!(task.status, TaskStatus::Queued | TaskStatus::Running | TaskStatus::WaitingForInput)
+}
+
+pub fn status(task: &Task) -> &'static str {
+    match task.status {
+        TaskStatus::Queued => "queued",
+        TaskStatus::Running => "running",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Failed => "failed",
+        TaskStatus::Cancelled => "cancelled",
+        TaskStatus::WaitingForInput => "waiting for input",
+    }
+}
+
+pub fn task_link(config: &Config, task: &Task) -> String {
+    let task_url = config.web_base_url.join(&format!("/tasks/{}", task.id)).unwrap();
+    format!("[task]({task_url})")
+}
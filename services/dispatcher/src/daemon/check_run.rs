@@ -0,0 +1,123 @@
+//! The check run that shows the progress of a task on the task branch
+//!
+//! Check runs belong to a commit, so the run is created on the head of the task branch when the
+//! task starts. Once the agent has pushed, the final state is reported on the new head as well.
+
+use uuid::Uuid;
+
+use github::{
+    CheckRunConclusion, CheckRunOutput, CheckRunState, CheckRunStatus, NewCheckRun, WithAccess,
+};
+
+/// Name of the check on GitHub
+const CHECK_NAME: &str = "Task";
+/// GitHub rejects summaries longer than this
+const MAX_SUMMARY_LEN: usize = 65535;
+
+/// Failing to report the progress doesn't fail the task.
+pub struct CheckRun<'a> {
+    pub github: &'a WithAccess,
+    pub task_id: Uuid,
+    pub repo_name: &'a str,
+    pub branch_name: &'a str,
+    pub details_url: &'a str,
+    /// ID and commit of the check run once it is created
+    pub created: Option<(i64, String)>,
+}
+
+impl CheckRun<'_> {
+    /// Create the check run on the head of the task branch
+    pub async fn start(&mut self, title: &str) {
+        let state = self.in_progress(title);
+        let head_sha = match self.github.branch_head(self.repo_name, self.branch_name).await {
+            Ok(head_sha) => head_sha,
+            Err(err) => {
+                eprintln!("Failed to find the head of the branch of task {}: {err}", self.task_id);
+                return;
+            }
+        };
+        if let Some(id) = self.create(&head_sha, &state).await {
+            self.created = Some((id, head_sha));
+        }
+    }
+
+    /// Report the phase the task is in
+    pub async fn progress(&self, title: &str) {
+        let Some((id, _)) = &self.created else {
+            return;
+        };
+        let state = self.in_progress(title);
+        if let Err(err) = self.github.update_check_run(self.repo_name, *id, &state).await {
+            eprintln!("Failed to update the check run of task {}: {err}", self.task_id);
+        }
+    }
+
+    /// Complete the check run, on the current head of the task branch as well
+    pub async fn finish(&self, conclusion: CheckRunConclusion, title: &str, summary: &str) {
+        let Some((id, started_sha)) = &self.created else {
+            return;
+        };
+        let state = CheckRunState {
+            status: CheckRunStatus::Completed,
+            conclusion: Some(conclusion),
+            output: CheckRunOutput {
+                title: title.to_owned(),
+                summary: truncate(summary, MAX_SUMMARY_LEN).to_owned(),
+            },
+        };
+        if let Err(err) = self.github.update_check_run(self.repo_name, *id, &state).await {
+            eprintln!("Failed to complete the check run of task {}: {err}", self.task_id);
+        }
+
+        match self.github.branch_head(self.repo_name, self.branch_name).await {
+            Ok(head_sha) if head_sha != *started_sha => {
+                self.create(&head_sha, &state).await;
+            }
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("Failed to find the head of the branch of task {}: {err}", self.task_id);
+            }
+        }
+    }
+
+    async fn create(&self, head_sha: &str, state: &CheckRunState) -> Option<i64> {
+        let external_id = self.task_id.to_string();
+        let new_check_run = NewCheckRun {
+            name: CHECK_NAME,
+            head_sha,
+            details_url: self.details_url,
+            external_id: &external_id,
+            state,
+        };
+        match self.github.create_check_run(self.repo_name, &new_check_run).await {
+            Ok(id) => Some(id),
+            Err(err) => {
+                eprintln!("Failed to create the check run of task {}: {err}", self.task_id);
+                None
+            }
+        }
+    }
+
+    fn in_progress(&self, title: &str) -> CheckRunState {
+        CheckRunState {
+            status: CheckRunStatus::InProgress,
+            conclusion: None,
+            output: CheckRunOutput {
+                title: title.to_owned(),
+                summary: format!("Follow the progress on the [task page]({}).", self.details_url),
+            },
+        }
+    }
+}
+
+/// The longest prefix of `s` that has at most `max_len` bytes
+fn truncate(s: &str, max_len: usize) -> &str {
+    if s.len() <= max_len {
+        return s;
+    }
+    let mut end = max_len;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
@@ -2,24 +2,69 @@ use std::time::Duration;
 
 use config::Config;
 use config::DispatchMode;
-use database::Database;
-use github::GitHub;
+use database::{AgentConfig, Database, TaskStatus, Update, DEFAULT_BASE_REF};
+use github::{CheckRunConclusion, GitHub, WithAccess};
 use object_storage::S3;
 use uuid::Uuid;
 
 use crate::vm::CommandResult;
 use crate::vm::{AwsVm, LocalVM, VirtualMachine};
 
+use super::check_run::CheckRun;
 use super::git;
 use super::Args;
 
 const TOKEN_LIFETIME: Duration = Duration::from_secs(60 * 60);
+/// How often a running agent checks whether its task was stopped
+const STOP_POLL_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct Job {
+    /// GitHub ID of the installation the task belongs to
+    pub installation_id: i64,
     pub issue_id: String,
     pub repo_github_id: String,
     pub repo_name: String,
     pub task_id: Uuid,
+    /// Whether the task replays the recorded LLM interactions of an earlier task
+    pub replay: bool,
+    pub base_ref: Option<String>,
+    pub model: Option<String>,
+    pub agent_image: AgentImage,
+    pub branch_name: String,
+    /// The pull request a follow-up task continues, unset for tasks that open a pull request
+    pub pull_request_id: Option<String>,
+    /// Whether the task ran before and waited for the answer to a question
+    pub resumed: bool,
+    /// The status comment of the task, set if it ran before
+    pub status_comment_id: Option<String>,
+}
+
+/// The container image that runs the agent
+pub struct AgentImage {
+    pub registry_host: String,
+    pub registry_username: String,
+    pub registry_password: String,
+    pub image: String,
+}
+
+impl AgentImage {
+    pub fn from_config(config: &Config) -> Self {
+        AgentImage {
+            registry_host: config.default_agent_container_registry_host.clone(),
+            registry_username: config.default_agent_container_registry_username.clone(),
+            registry_password: config.default_agent_container_registry_password.clone(),
+            image: config.default_agent_container_image.clone(),
+        }
+    }
+
+    pub fn from_agent_config(agent_config: AgentConfig) -> Self {
+        AgentImage {
+            registry_host: agent_config.container_registry_host,
+            registry_username: agent_config.container_registry_username.unwrap_or_default(),
+            registry_password: agent_config.container_registry_password.unwrap_or_default(),
+            image: agent_config.container_image,
+        }
+    }
 }
 
 pub async fn run(
@@ -30,35 +75,76 @@ pub async fn run(
     s3: &S3,
     token_signer: &auth::TokenSigner,
     job: &Job,
-) {
-    let jwt = github.github_app_jwt();
-    let access_token = github.installation_access_token(&jwt).await;
-    let github_inst = github.with_access(&access_token.token);
+) -> github::Result<()> {
+    let github_inst = github.with_installation_access(job.installation_id).await?;
 
-    let issue_info = github_inst.issue_info(&job.issue_id).await;
+    let issue_info = github_inst.issue_info(&job.issue_id).await?;
     let description = issue_info.body;
     println!("{}", description);
 
     let task_url = config.web_base_url.join(&format!("/tasks/{}", job.task_id)).unwrap();
-    let body = format!("Started working on the [task]({task_url}).");
-    github_inst.add_comment(&job.issue_id, &body).await;
+    let mut status_comment = StatusComment {
+        github: &github_inst,
+        db: &db,
+        task_id: job.task_id,
+        // Follow-up tasks report back to the pull request they continue
+        subject_id: job.pull_request_id.as_ref().unwrap_or(&job.issue_id),
+        comment_id: job.status_comment_id.clone(),
+    };
+    // Replays are for debugging and don't report back to the issue
+    if !job.replay {
+        let body = match job.resumed {
+            true => format!("Continuing the [task]({task_url}) with the answer."),
+            false => format!("Working on the [task]({task_url})."),
+        };
+        status_comment.set(&body).await;
+    }
 
-    let repo_numeric_id = github_inst.repo_numeric_id_by_node_id(&job.repo_github_id).await;
+    let repo_numeric_id = github_inst.repo_numeric_id_by_node_id(&job.repo_github_id).await?;
 
     let repo_access_token =
-        github.create_scoped_access_token(&github.github_app_jwt(), repo_numeric_id).await;
-
-    let repo_url =
-        format!("https://oauth2:{}@github.com/{}", &repo_access_token.token, job.repo_name);
-
-    // For now we assume that the task id is the branch name
-    let branch_ref_name = format!("refs/heads/{}", job.task_id);
-    git::push_task_branch(&repo_url, &branch_ref_name).await;
+        github.create_scoped_access_token(job.installation_id, repo_numeric_id).await?;
+
+    let mut repo_url = github.urls().git_remote(&job.repo_name);
+    repo_url.set_username("oauth2").unwrap();
+    repo_url.set_password(Some(&repo_access_token.token)).unwrap();
+
+    let branch_ref_name = format!("refs/heads/{}", job.branch_name);
+    let base_ref = job.base_ref.as_deref().unwrap_or(DEFAULT_BASE_REF);
+    // Follow-up tasks continue on the branch of the pull request, resumed tasks on their own.
+    // Replays don't write to the repository, their agents start from the base branch.
+    let push_result = match (&job.pull_request_id, job.resumed) {
+        (None, false) if !job.replay => {
+            git::push_task_branch(repo_url.as_str(), base_ref, &branch_ref_name).await
+        }
+        _ => Ok(()),
+    };
+    if let Err(err) = push_result {
+        eprintln!("Failed to create the branch for task {}: {}", job.task_id, err);
+        let description = format!("Branch `{}` could not be found.", base_ref);
+        db.conn().await.fail_task(&job.task_id, None, &description).await;
+        if !job.replay {
+            status_comment.set(&format!("[Task]({task_url}) failed: {description}")).await;
+        }
+        return Ok(());
+    }
 
     let agent_token = auth::issue_agent_token(token_signer, &job.task_id, TOKEN_LIFETIME);
 
     if matches!(config.dispatch_mode, DispatchMode::None) {
-        return;
+        return Ok(());
+    }
+
+    let mut check_run = CheckRun {
+        github: &github_inst,
+        task_id: job.task_id,
+        repo_name: &job.repo_name,
+        branch_name: &job.branch_name,
+        details_url: task_url.as_str(),
+        created: None,
+    };
+    if !job.replay {
+        check_run.start("Starting the virtual machine").await;
     }
 
     let usage_start = chrono::Utc::now();
@@ -69,9 +155,9 @@ pub async fn run(
     };
 
     let log_output = if args.local {
-        run_vm::<LocalVM>(config, &agent_token).await
+        run_vm::<LocalVM>(config, args, &db, job, &agent_token, &check_run).await
     } else {
-        run_vm::<AwsVm>(config, &agent_token).await
+        run_vm::<AwsVm>(config, args, &db, job, &agent_token, &check_run).await
     };
 
     println!("{}", log_output);
@@ -83,40 +169,160 @@ pub async fn run(
         conn.end_compute_usage(usage.id, usage_end).await.unwrap();
     }
 
-    let pr_title = format!("Pull request from {}", config.service_name);
-    let pr_body = r#"---
+    // A missing log should not keep the results from being reported, the status notes it instead
+    let log_note = match s3.upload_log_for_task(&job.task_id, log_output).await {
+        Ok(()) => "",
+        Err(err) => {
+            log::error!("Failed to upload the log of task {}: {:?}", job.task_id, err);
+            " Its log could not be stored."
+        }
+    };
+
+    if job.replay {
+        return Ok(());
+    }
+
+    // The agent may have pushed before the task was cancelled, but its work is not proposed
+    let task = db.conn().await.get_task(&job.task_id).await;
+    let status = task.status;
+    if matches!(status, TaskStatus::Cancelled) {
+        check_run
+            .finish(CheckRunConclusion::Cancelled, "Task cancelled", "The task was cancelled.")
+            .await;
+        return Ok(());
+    }
+
+    // The task is queued again once the question is answered, the pull request waits until then
+    if matches!(status, TaskStatus::WaitingForInput | TaskStatus::Queued) {
+        let questions = db.conn().await.task_questions(&job.task_id).await;
+        let question = questions.last().map_or("", |question| question.question.as_str());
+        check_run.finish(CheckRunConclusion::ActionRequired, "Waiting for input", question).await;
+        // Already answered tasks report their status once they continue
+        if matches!(status, TaskStatus::WaitingForInput) {
+            status_comment
+                .set(&format!("[Task]({task_url}) is waiting for an answer to its question."))
+                .await;
+        }
+        return Ok(());
+    }
+
+    // The agent exited without completing or failing the task, its work is not proposed
+    if matches!(status, TaskStatus::Running) {
+        let description = "The agent exited without reporting.";
+        db.conn().await.fail_task(&job.task_id, None, description).await;
+        check_run.finish(CheckRunConclusion::Failure, "Task failed", description).await;
+        status_comment.set(&format!("[Task]({task_url}) failed: {description}{log_note}")).await;
+        return Ok(());
+    }
+
+    // Finished before the pull request is opened, so that it shows the final state right away
+    match status {
+        TaskStatus::Failed => {
+            let description = task.failure_description.as_deref().unwrap_or("The task failed.");
+            check_run.finish(CheckRunConclusion::Failure, "Task failed", description).await;
+        }
+        _ => {
+            let description =
+                task.completion_description.as_deref().unwrap_or("The task completed.");
+            check_run.finish(CheckRunConclusion::Success, "Task completed", description).await;
+        }
+    }
+
+    if job.pull_request_id.is_none() {
+        let pr_title = format!("Pull request from {}", config.service_name);
+        let pr_body = r#"---
 
 AI-generated. Review carefully.
 "#;
 
-    github_inst
-        .create_pull_request(&job.repo_github_id, &pr_title, pr_body, &branch_ref_name)
-        .await;
+        let pull_request_id = github_inst
+            .create_pull_request(
+                &job.repo_github_id,
+                &pr_title,
+                pr_body,
+                &branch_ref_name,
+                base_ref,
+            )
+            .await?;
+
+        // Review feedback on the pull request is addressed by follow-up tasks of this task
+        let mut conn = db.conn().await;
+        let task = conn.get_task(&job.task_id).await;
+        conn.update_task(task.update().github_pull_request_id(Some(pull_request_id))).await;
+    }
+
+    let body = match status {
+        TaskStatus::Failed => format!("[Task]({task_url}) failed.{log_note}"),
+        _ => format!("[Task]({task_url}) completed.{log_note}"),
+    };
+    status_comment.set(&body).await;
+    Ok(())
+}
 
-    let body = format!("[Task]({task_url}) completed.");
-    github_inst.add_comment(&job.issue_id, &body).await;
-    s3.upload_log_for_task(&job.task_id, log_output).await.unwrap();
+/// The comment on the issue or pull request that reports the status of the task
+///
+/// The comment is added with the first status and updated in place afterwards. Failing to report
+/// the status doesn't fail the task.
+struct StatusComment<'a> {
+    github: &'a WithAccess,
+    db: &'a Database,
+    task_id: Uuid,
+    subject_id: &'a str,
+    comment_id: Option<String>,
 }
 
-async fn run_vm<V: VirtualMachine>(config: &Config, access_token: &str) -> String {
+impl StatusComment<'_> {
+    async fn set(&mut self, body: &str) {
+        if let Some(comment_id) = &self.comment_id {
+            if let Err(err) = self.github.update_comment(comment_id, body).await {
+                eprintln!("Failed to update the status comment of task {}: {}", self.task_id, err);
+            }
+            return;
+        }
+
+        let comment_id = match self.github.add_comment(self.subject_id, body).await {
+            Ok(comment_id) => comment_id,
+            Err(err) => {
+                eprintln!("Failed to add the status comment of task {}: {}", self.task_id, err);
+                return;
+            }
+        };
+        // Cancelling the task updates the comment as well
+        let mut conn = self.db.conn().await;
+        let task = conn.get_task(&self.task_id).await;
+        conn.update_task(task.update().github_status_comment_id(Some(comment_id.clone()))).await;
+        self.comment_id = Some(comment_id);
+    }
+}
+
+async fn run_vm<V: VirtualMachine>(
+    config: &Config,
+    args: &Args,
+    db: &Database,
+    job: &Job,
+    access_token: &str,
+    check_run: &CheckRun<'_>,
+) -> String {
     let mut vm = V::create(config).await;
 
     // Setups the VM with the necessary tools.
     vm.install_docker().await;
 
-    let container_registry_host = &config.default_agent_container_registry_host;
-    let container_registry_username = &config.default_agent_container_registry_username;
-    let container_registry_password = &config.default_agent_container_registry_password;
-    let container_image = &config.default_agent_container_image;
-
-    // Login to the container registry and pull the image.
-    vm.run_command(&format!(
-        "docker login -u {} -p {} {}",
-        shlex::try_quote(container_registry_username).unwrap(),
-        shlex::try_quote(container_registry_password).unwrap(),
-        shlex::try_quote(container_registry_host).unwrap()
-    ))
-    .await;
+    let container_registry_host = &job.agent_image.registry_host;
+    let container_registry_username = &job.agent_image.registry_username;
+    let container_registry_password = &job.agent_image.registry_password;
+    let container_image = &job.agent_image.image;
+
+    // Login to the container registry, unless it is accessed anonymously, and pull the image.
+    if !container_registry_username.is_empty() {
+        vm.run_command(&format!(
+            "docker login -u {} -p {} {}",
+            shlex::try_quote(container_registry_username).unwrap(),
+            shlex::try_quote(container_registry_password).unwrap(),
+            shlex::try_quote(container_registry_host).unwrap()
+        ))
+        .await;
+    }
 
     let formatted_image = format!("{}/{}", container_registry_host, container_image);
     let registry_and_image = shlex::try_quote(&formatted_image).unwrap();
@@ -130,12 +336,43 @@ async fn run_vm<V: VirtualMachine>(config: &Config, access_token: &str) -> Strin
 
     let api_base_url = config.web_base_url.join("/api/").unwrap();
 
+    // Sysbox lets the agent run Docker inside its container
+    let mut run_options = match args.host_network {
+        true => "--network=host",
+        false => "--runtime=sysbox-runc",
+    }
+    .to_owned();
+    if let Some(model) = &job.model {
+        run_options.push_str(&format!(" -e MINION_MODEL={}", shlex::try_quote(model).unwrap()));
+    }
+
+    check_run.progress("Running the agent").await;
+
+    // Named so that the container can be stopped when the task is cancelled or waits for input
+    let container_name = format!("minion-{}", job.task_id);
+
     // Run the agent software in detached mode.
-    let CommandResult { log_output, .. } = vm.run_command(&format!(
-        "docker run --runtime=sysbox-runc --pull never -e MINION_API_BASE_URL={} -e MINION_API_TOKEN={} {}",
-        api_base_url, access_token, registry_and_image
-    ))
-    .await;
+    let command = format!(
+        "docker run {} --name {} --pull never -e MINION_API_BASE_URL={} -e MINION_API_TOKEN={} {}",
+        run_options, container_name, api_base_url, access_token, registry_and_image
+    );
+    let stopped_status = tokio::select! {
+        CommandResult { log_output, .. } = vm.run_command(&command) => Err(log_output),
+        status = stopped(db, job.task_id) => Ok(status),
+    };
+    let log_output = match stopped_status {
+        Err(log_output) => log_output,
+        Ok(status) => {
+            let CommandResult { log_output, .. } =
+                vm.run_command(&format!("docker logs {}", container_name)).await;
+            vm.run_command(&format!("docker rm -f {}", container_name)).await;
+            let reason = match status {
+                TaskStatus::Cancelled => "The task was cancelled",
+                _ => "The task is waiting for input",
+            };
+            format!("{log_output}\n{reason}, the agent was stopped.")
+        }
+    };
 
     // Disconnect the SSH connection.
     vm.detach().await;
@@ -144,3 +381,20 @@ async fn run_vm<V: VirtualMachine>(config: &Config, access_token: &str) -> Strin
 
     log_output
 }
+
+/// Resolves with the status of the task once it was cancelled or waits for input
+///
+/// Agents are expected to exit after they asked a question, the virtual machine is not kept
+/// running for those that don't. An answer may have queued the task again in the meantime.
+async fn stopped(db: &Database, task_id: Uuid) -> TaskStatus {
+    loop {
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        let status = db.conn().await.get_task_status(&task_id).await;
+        if matches!(
+            status,
+            TaskStatus::Cancelled | TaskStatus::WaitingForInput | TaskStatus::Queued
+        ) {
+            return status;
+        }
+    }
+}
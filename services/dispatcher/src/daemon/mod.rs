@@ -3,24 +3,36 @@ use std::time::Duration;
 
 use auth::TokenSigner;
 use config::Config;
-use database::{Database, Task};
+use database::{Database, Keyring, Task, TaskStatus};
 use github::GitHub;
 use object_storage::S3;
 
+mod check_run;
 mod git;
 mod job;
 
 #[derive(clap::Args, Clone)]
 pub struct Args {
+    /// Run agents in containers on this machine instead of in AWS virtual machines
     #[clap(long, num_args = 0)]
-    local: bool,
+    pub local: bool,
+    /// Run local agents on the network of this machine instead of with Sysbox, e.g. to reach an
+    /// API on localhost
+    #[clap(long, num_args = 0, requires = "local")]
+    pub host_network: bool,
 }
 
 pub async fn exec(args: Args) {
-    let config = Config::load();
+    run(Config::load(), args).await
+}
+
+/// Receive queued tasks and dispatch them until the process is stopped
+pub async fn run(config: Config, args: Args) {
     let github = GitHub::new(config.clone());
     let s3 = S3::new(&config).unwrap();
-    let db = Database::connect(config.postgres_url.as_str()).await;
+    let encryption = &config.encryption;
+    let keyring = Keyring::new(&encryption.master_key, &encryption.previous_master_keys);
+    let db = Database::connect(config.postgres_url.as_str(), keyring).await;
     let token_signer = Arc::new(auth::token_signer(&config));
     let mut conn = db.conn().await;
     loop {
@@ -50,14 +62,54 @@ async fn handle_msg(
     token_signer: Arc<TokenSigner>,
     task: Task,
 ) {
-    let repo = db.conn().await.get_repository(&task.repository_id).await;
+    let (repo, agent_config, installation_id, questions) = {
+        let mut conn = db.conn().await;
+        let repo = conn.get_repository(&task.repository_id).await;
+        let agent_config = match task.agent_config_id {
+            Some(agent_config_id) => Some(conn.get_agent_config(&agent_config_id).await),
+            None => None,
+        };
+        let installation_id = conn.task_installation_github_id(&task.id).await;
+        let questions = conn.task_questions(&task.id).await;
+        (repo, agent_config, installation_id, questions)
+    };
+    // Without the installation the task can't be reported on GitHub either
+    let Some(installation_id) = installation_id else {
+        eprintln!("The app is no longer installed for task {}", task.id);
+        db.conn().await.fail_task(&task.id, None, "The app is no longer installed.").await;
+        return;
+    };
+    let agent_image = match agent_config {
+        Some(agent_config) => job::AgentImage::from_agent_config(agent_config),
+        None => job::AgentImage::from_config(&config),
+    };
 
+    let branch_name = task.branch_name();
     let job = job::Job {
+        installation_id,
         issue_id: task.github_issue_id,
         repo_github_id: repo.github_id,
         repo_name: repo.github_full_name,
         task_id: task.id,
+        replay: task.replay_of_task_id.is_some(),
+        base_ref: task.base_ref,
+        model: task.model,
+        agent_image,
+        branch_name,
+        pull_request_id: task.follow_up_of_task_id.and(task.github_pull_request_id),
+        resumed: !questions.is_empty(),
+        status_comment_id: task.github_status_comment_id,
     };
 
-    job::run(&config, &args, db.clone(), &github, &s3, &token_signer, &job).await;
+    if let Err(err) = job::run(&config, &args, db.clone(), &github, &s3, &token_signer, &job).await
+    {
+        eprintln!("GitHub request for task {} failed: {}", job.task_id, err);
+        // Tasks that finished before keep their status
+        let mut conn = db.conn().await;
+        let status = conn.get_task_status(&job.task_id).await;
+        if matches!(status, TaskStatus::Queued | TaskStatus::Running) {
+            let description = format!("GitHub request failed: {err}");
+            conn.fail_task(&job.task_id, None, &description).await;
+        }
+    }
 }
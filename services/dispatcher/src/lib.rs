@@ -0,0 +1,6 @@
+//! The dispatcher watches for new jobs and starts VMs to run them.
+
+pub mod cli;
+pub mod daemon;
+mod tokens;
+mod vm;
@@ -1,10 +1,4 @@
-//! The dispatcher watches for new jobs and starts VMs to run them.
-
-mod cli;
-mod daemon;
-mod tokens;
-mod vm;
-
 fn main() {
-    cli::exec();
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    dispatcher::cli::exec();
 }
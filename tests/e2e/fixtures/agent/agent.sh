@@ -0,0 +1,48 @@
+#!/bin/sh
+# Fetches the task, asks the LLM once, pushes a commit to the task branch and completes the task,
+# reporting its progress along the way. Asks a question first if the task request says so.
+set -eu
+
+api="$MINION_API_BASE_URL"
+auth="Authorization: Bearer $MINION_API_TOKEN"
+
+# Extract a string field from a flat JSON object
+json_field() {
+    sed -n "s/.*\"$1\":\"\([^\"]*\)\".*/\1/p"
+}
+
+task=$(curl -fsS -H "$auth" "${api}agent/task")
+repo_url=$(echo "$task" | json_field git_repo_url)
+branch=$(echo "$task" | json_field git_branch)
+user_name=$(echo "$task" | json_field git_user_name)
+user_email=$(echo "$task" | json_field git_user_email)
+
+# Doesn't exit after asking, so that the dispatcher has to stop it
+if echo "$task" | grep -q "Ask a question first" && ! echo "$task" | grep -q "## Clarifications"; then
+    curl -fsS -H "$auth" -H "Content-Type: application/json" \
+        -d '{"question":"Which directory?"}' "${api}agent/task/ask"
+    sleep 3600
+fi
+
+progress() {
+    curl -fsS -H "$auth" -H "Content-Type: application/json" -d "$1" "${api}agent/task/progress"
+}
+
+progress '{"step":"Asking the LLM","percent":25}'
+curl -fsS -H "$auth" -H "Content-Type: application/json" \
+    -d '{"model":"e2e/fixture","messages":[{"role":"user","content":"Solve the issue"}]}' \
+    "${api}chat/completions" > /tmp/completion.json
+
+progress '{"step":"Pushing the changes","percent":75,"milestone":true}'
+# The git proxy expects the agent token as the basic auth password
+remote=$(echo "$repo_url" | sed "s#://#://agent:${MINION_API_TOKEN}@#")
+git clone --branch "$branch" "$remote" /tmp/repo
+cd /tmp/repo
+cp /tmp/completion.json COMPLETION.json
+git add COMPLETION.json
+git -c user.name="$user_name" -c user.email="$user_email" commit -m "Fixture agent commit"
+git push origin "HEAD:refs/heads/$branch"
+
+curl -fsS -H "$auth" -H "Content-Type: application/json" \
+    -d '{"description":"Fixture agent completed the task"}' \
+    "${api}agent/task/complete"
@@ -0,0 +1,433 @@
+//! A stand-in for the parts of GitHub the app talks to: REST, GraphQL and git smart HTTP.
+//!
+//! It serves a single organization with one member, one repository and one issue. Writes are
+//! recorded so that tests can assert on them, and git requests are served by `git http-backend`
+//! from a bare repository in a temporary directory.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use actix_web::dev::ServerHandle;
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use url::Url;
+
+pub const INSTALLATION_ID: i64 = 7;
+pub const ORG_LOGIN: &str = "minion-e2e";
+pub const ORG_NODE_ID: &str = "O_minion_e2e";
+pub const USER_LOGIN: &str = "octocat";
+pub const USER_NODE_ID: &str = "U_octocat";
+pub const REPO_NAME: &str = "sandbox";
+pub const REPO_FULL_NAME: &str = "minion-e2e/sandbox";
+pub const REPO_NODE_ID: &str = "R_sandbox";
+pub const REPO_DATABASE_ID: i64 = 4242;
+pub const ISSUE_NODE_ID: &str = "I_sandbox_1";
+pub const ISSUE_NUMBER: i64 = 1;
+pub const ISSUE_TITLE: &str = "Sandbox issue";
+pub const ISSUE_BODY: &str = "Add a file to the repository.";
+pub const COMMENT_NODE_ID: &str = "IC_sandbox_1";
+
+/// Writes the app made to GitHub
+#[derive(Default)]
+pub struct Recorded {
+    /// Comments as (subject id, body), the index is part of the comment id
+    pub comments: Vec<(String, String)>,
+    /// Reactions as (subject id, content)
+    pub reactions: Vec<(String, String)>,
+    pub pull_requests: Vec<PullRequest>,
+    /// Check runs in their latest state, the index is the check run id
+    pub check_runs: Vec<CheckRun>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CheckRun {
+    pub head_sha: String,
+    pub external_id: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub summary: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct PullRequest {
+    pub repo_id: String,
+    pub base_ref: String,
+    pub head_ref: String,
+    pub title: String,
+}
+
+#[derive(Clone)]
+struct State {
+    git_root: PathBuf,
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+pub struct FakeGitHub {
+    pub url: Url,
+    pub recorded: Arc<Mutex<Recorded>>,
+    git_root: TempDir,
+    handle: ServerHandle,
+}
+
+impl FakeGitHub {
+    pub async fn start() -> Self {
+        let git_root = TempDir::new().unwrap();
+        init_repository(&git_root.path().join(REPO_FULL_NAME)).await;
+
+        let state = State { git_root: git_root.path().to_owned(), recorded: Default::default() };
+        let recorded = state.recorded.clone();
+
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(state.clone()))
+                .app_data(web::PayloadConfig::new(64 * 1024 * 1024))
+                .route("/login/oauth/access_token", web::post().to(oauth_access_token))
+                .route("/app/hook/config", web::patch().to(HttpResponse::Ok))
+                .route("/app/installations", web::get().to(installations))
+                .route(
+                    "/app/installations/{id}/access_tokens",
+                    web::post().to(installation_access_token),
+                )
+                .route("/installation/repositories", web::get().to(installation_repositories))
+                .route("/orgs/{org}/memberships/{user}", web::get().to(organization_membership))
+                .route("/repos/{owner}/{repo}/git/ref/heads/{branch}", web::get().to(branch_head))
+                .route("/repos/{owner}/{repo}/check-runs", web::post().to(create_check_run))
+                .route("/repos/{owner}/{repo}/check-runs/{id}", web::patch().to(update_check_run))
+                .route("/user/emails", web::get().to(user_emails))
+                .route("/graphql", web::post().to(graphql))
+                // Everything else is a git request for a repository
+                .default_service(web::to(git_http_backend))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+
+        let url = Url::parse(&format!("http://{}/", server.addrs()[0])).unwrap();
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Self { url, recorded, git_root, handle }
+    }
+
+    /// Run git in the bare repository and return its stdout
+    pub async fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(self.git_root.path().join(REPO_FULL_NAME))
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    pub async fn stop(&self) {
+        self.handle.stop(false).await;
+    }
+}
+
+/// Payload of the webhook GitHub sends when the app is installed on the organization
+pub fn installation_created_event() -> Value {
+    json!({
+        "action": "created",
+        "sender": user(),
+        "repositories": [repository()],
+        "installation": {
+            "id": INSTALLATION_ID,
+            "app_id": 1,
+            "target_id": 1,
+            "target_type": "Organization",
+            "events": ["issues", "issue_comment"],
+            "account": {
+                "login": ORG_LOGIN,
+                "id": 1,
+                "node_id": ORG_NODE_ID,
+                "type": "Organization",
+            },
+        },
+    })
+}
+
+/// Payload of the webhook GitHub sends when the member comments on the issue
+pub fn issue_comment_created_event(body: &str) -> Value {
+    json!({
+        "action": "created",
+        "repository": repository(),
+        "issue": {
+            "node_id": ISSUE_NODE_ID,
+            "title": ISSUE_TITLE,
+            "body": ISSUE_BODY,
+            "number": ISSUE_NUMBER,
+            "user": user(),
+        },
+        "comment": { "node_id": COMMENT_NODE_ID, "body": body, "user": user() },
+        "sender": user(),
+        "installation": { "id": INSTALLATION_ID },
+    })
+}
+
+fn user() -> Value {
+    json!({ "login": USER_LOGIN, "node_id": USER_NODE_ID, "name": null })
+}
+
+fn user_info() -> Value {
+    json!({ "id": USER_NODE_ID, "login": USER_LOGIN, "name": null })
+}
+
+fn repository() -> Value {
+    json!({
+        "id": REPO_DATABASE_ID,
+        "full_name": REPO_FULL_NAME,
+        "name": REPO_NAME,
+        "node_id": REPO_NODE_ID,
+        "private": true,
+    })
+}
+
+async fn oauth_access_token() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "access_token": "gho_e2e", "expires_in": 28800 }))
+}
+
+async fn installations() -> HttpResponse {
+    HttpResponse::Ok().json(json!([installation_created_event()["installation"]]))
+}
+
+async fn installation_access_token(path: web::Path<i64>) -> HttpResponse {
+    if path.into_inner() != INSTALLATION_ID {
+        return HttpResponse::NotFound().finish();
+    }
+    let token = json!({ "token": "ghs_e2e", "expires_at": "2099-01-01T00:00:00Z" });
+    HttpResponse::Created().json(token)
+}
+
+async fn installation_repositories() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "total_count": 1, "repositories": [repository()] }))
+}
+
+async fn organization_membership() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "role": "admin", "state": "active", "user": user() }))
+}
+
+async fn branch_head(
+    state: web::Data<State>,
+    path: web::Path<(String, String, String)>,
+) -> HttpResponse {
+    let (owner, repo, branch) = path.into_inner();
+    let output = Command::new("git")
+        .args(["rev-parse", "--verify", &format!("refs/heads/{branch}")])
+        .current_dir(state.git_root.join(owner).join(repo))
+        .output()
+        .await
+        .unwrap();
+    if !output.status.success() {
+        return HttpResponse::NotFound().json(json!({ "message": "Not Found" }));
+    }
+    let sha = String::from_utf8(output.stdout).unwrap().trim().to_owned();
+    HttpResponse::Ok()
+        .json(json!({ "ref": format!("refs/heads/{branch}"), "object": { "sha": sha } }))
+}
+
+async fn create_check_run(state: web::Data<State>, body: web::Json<Value>) -> HttpResponse {
+    let check_run = CheckRun {
+        head_sha: string(&body["head_sha"]),
+        external_id: string(&body["external_id"]),
+        status: string(&body["status"]),
+        conclusion: body["conclusion"].as_str().map(str::to_owned),
+        summary: string(&body["output"]["summary"]),
+    };
+    let mut recorded = state.recorded.lock().unwrap();
+    let id = recorded.check_runs.len();
+    recorded.check_runs.push(check_run);
+    HttpResponse::Created().json(json!({ "id": id }))
+}
+
+async fn update_check_run(
+    state: web::Data<State>,
+    path: web::Path<(String, String, usize)>,
+    body: web::Json<Value>,
+) -> HttpResponse {
+    let (_, _, id) = path.into_inner();
+    let mut recorded = state.recorded.lock().unwrap();
+    let Some(check_run) = recorded.check_runs.get_mut(id) else {
+        return HttpResponse::NotFound().json(json!({ "message": "Not Found" }));
+    };
+    check_run.status = string(&body["status"]);
+    check_run.conclusion = body["conclusion"].as_str().map(str::to_owned);
+    check_run.summary = string(&body["output"]["summary"]);
+    HttpResponse::Ok().json(json!({ "id": id }))
+}
+
+async fn user_emails() -> HttpResponse {
+    HttpResponse::Ok().json(json!([{ "email": "octocat@example.com", "primary": true }]))
+}
+
+/// Answer GraphQL operations by name, as sent by `graphql_client`
+async fn graphql(state: web::Data<State>, body: web::Json<Value>) -> HttpResponse {
+    let vars = &body["variables"];
+    let data = match body["operationName"].as_str().unwrap_or_default() {
+        "ViewerInfo" => json!({ "viewer": user_info() }),
+        "UserInfoView" => json!({ "user": user_info() }),
+        "IssueView" => json!({
+            "node": {
+                "__typename": "Issue",
+                "body": ISSUE_BODY,
+                "author": { "__typename": "User", "login": USER_LOGIN }
+            }
+        }),
+        "IssueContextView" => json!({
+            "node": {
+                "__typename": "Issue",
+                "number": ISSUE_NUMBER,
+                "title": ISSUE_TITLE,
+                "body": ISSUE_BODY,
+                "author": { "__typename": "User", "login": USER_LOGIN },
+                "labels": { "nodes": [] },
+                "firstComment": { "nodes": [] },
+                "comments": {
+                    "totalCount": 0,
+                    "pageInfo": { "hasPreviousPage": false, "startCursor": null },
+                    "nodes": []
+                },
+                "timelineItems": { "nodes": [] }
+            }
+        }),
+        "IssueIdView" => json!({ "repository": { "issue": { "id": ISSUE_NODE_ID } } }),
+        "OrganizationMembers" => json!({
+            "organization": { "membersWithRole": {
+                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                "edges": [{ "role": "ADMIN", "node": user_info() }]
+            } }
+        }),
+        "RepoNumericId" => json!({
+            "node": { "__typename": "Repository", "databaseId": REPO_DATABASE_ID }
+        }),
+        "AddComment" => {
+            let comment = (string(&vars["subject_id"]), string(&vars["body"]));
+            let mut recorded = state.recorded.lock().unwrap();
+            let comment_id = format!("IC_app_{}", recorded.comments.len());
+            recorded.comments.push(comment);
+            json!({ "addComment": { "commentEdge": { "node": { "id": comment_id } } } })
+        }
+        "UpdateComment" => {
+            let comment_id = string(&vars["comment_id"]);
+            let Some(index) =
+                comment_id.strip_prefix("IC_app_").and_then(|i| i.parse::<usize>().ok())
+            else {
+                return HttpResponse::NotFound().body(format!("Unknown comment {comment_id}"));
+            };
+            let mut recorded = state.recorded.lock().unwrap();
+            let Some((_, body)) = recorded.comments.get_mut(index) else {
+                return HttpResponse::NotFound().body(format!("Unknown comment {comment_id}"));
+            };
+            *body = string(&vars["body"]);
+            json!({ "updateIssueComment": { "clientMutationId": null } })
+        }
+        "AddReaction" => {
+            let reaction = (string(&vars["subject_id"]), string(&vars["content"]));
+            state.recorded.lock().unwrap().reactions.push(reaction);
+            json!({ "addReaction": { "clientMutationId": null } })
+        }
+        "CreatePullRequest" => {
+            let pull_request = PullRequest {
+                repo_id: string(&vars["repo_id"]),
+                base_ref: string(&vars["base_ref"]),
+                head_ref: string(&vars["head_ref"]),
+                title: string(&vars["title"]),
+            };
+            state.recorded.lock().unwrap().pull_requests.push(pull_request);
+            json!({ "createPullRequest": { "pullRequest": { "id": "PR_sandbox_1" } } })
+        }
+        operation => {
+            return HttpResponse::BadRequest().body(format!("Unsupported operation {operation}"))
+        }
+    };
+    HttpResponse::Ok().json(json!({ "data": data }))
+}
+
+fn string(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_owned()
+}
+
+/// Serve git smart HTTP by running `git http-backend` as a CGI script
+async fn git_http_backend(
+    state: web::Data<State>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let header = |name: &str| {
+        req.headers().get(name).and_then(|value| value.to_str().ok()).unwrap_or_default()
+    };
+
+    let mut child = Command::new("git")
+        .arg("http-backend")
+        .env("GIT_PROJECT_ROOT", &state.git_root)
+        .env("GIT_HTTP_EXPORT_ALL", "1")
+        // Pushing is only enabled for authenticated users
+        .env("REMOTE_USER", "x-access-token")
+        .env("PATH_INFO", req.path())
+        .env("QUERY_STRING", req.query_string())
+        .env("REQUEST_METHOD", req.method().as_str())
+        .env("CONTENT_TYPE", header("content-type"))
+        .env("CONTENT_LENGTH", body.len().to_string())
+        .env("GIT_PROTOCOL", header("git-protocol"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(&body).await.unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().await.unwrap();
+    let Some(split) = output.stdout.windows(4).position(|window| window == b"\r\n\r\n") else {
+        return HttpResponse::InternalServerError().body("Invalid CGI response");
+    };
+
+    let mut response = HttpResponse::Ok();
+    let head = String::from_utf8_lossy(&output.stdout[..split]);
+    for line in head.lines() {
+        let Some((name, value)) = line.split_once(": ") else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("status") {
+            let code = value.split(' ').next().unwrap().parse().unwrap();
+            response.status(StatusCode::from_u16(code).unwrap());
+        } else {
+            response.insert_header((name.to_owned(), value.to_owned()));
+        }
+    }
+    response.body(output.stdout[split + 4..].to_vec())
+}
+
+/// Create a bare repository with a single commit on `main`
+async fn init_repository(path: &std::path::Path) {
+    let work = TempDir::new().unwrap();
+    let git = |dir: PathBuf, args: Vec<String>| async move {
+        let status = Command::new("git")
+            .args(["-c", "user.name=octocat", "-c", "user.email=octocat@example.com"])
+            .args(&args)
+            .current_dir(dir)
+            .status()
+            .await
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    tokio::fs::create_dir_all(path).await.unwrap();
+    git(path.to_owned(), args(&["init", "--bare", "--initial-branch=main"])).await;
+
+    tokio::fs::write(work.path().join("README.md"), "# Sandbox\n").await.unwrap();
+    git(work.path().to_owned(), args(&["init", "--initial-branch=main"])).await;
+    git(work.path().to_owned(), args(&["add", "README.md"])).await;
+    git(work.path().to_owned(), args(&["commit", "-m", "Initial commit"])).await;
+    let remote = path.to_str().unwrap();
+    git(work.path().to_owned(), args(&["push", remote, "main"])).await;
+}
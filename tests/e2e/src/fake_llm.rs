@@ -0,0 +1,84 @@
+//! A stand-in for the LLM provider that answers every chat completion with the same message.
+
+use std::sync::{Arc, Mutex};
+
+use actix_web::dev::ServerHandle;
+use actix_web::http::header;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use serde_json::{json, Value};
+use url::Url;
+
+pub const COMPLETION_CONTENT: &str = "I added the file.";
+/// Cost of every completion in USD
+pub const COMPLETION_COST: f64 = 0.0001;
+
+pub struct FakeLlm {
+    /// URL of the chat completions endpoint
+    pub url: Url,
+    /// All received requests
+    pub requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    handle: ServerHandle,
+}
+
+pub struct RecordedRequest {
+    pub authorization: Option<String>,
+    pub body: Value,
+}
+
+impl FakeLlm {
+    pub async fn start() -> Self {
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Default::default();
+
+        let server = {
+            let requests = requests.clone();
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(requests.clone()))
+                    .route("/chat/completions", web::post().to(chat_completion))
+            })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap()
+        };
+
+        let url = Url::parse(&format!("http://{}/chat/completions", server.addrs()[0])).unwrap();
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Self { url, requests, handle }
+    }
+
+    pub async fn stop(&self) {
+        self.handle.stop(false).await;
+    }
+}
+
+async fn chat_completion(
+    requests: web::Data<Arc<Mutex<Vec<RecordedRequest>>>>,
+    req: HttpRequest,
+    body: web::Json<Value>,
+) -> HttpResponse {
+    let model = body["model"].clone();
+    let authorization =
+        (req.headers().get(header::AUTHORIZATION)).map(|value| value.to_str().unwrap().to_owned());
+    requests.lock().unwrap().push(RecordedRequest { authorization, body: body.into_inner() });
+
+    HttpResponse::Ok().json(json!({
+        "id": "gen-e2e",
+        "object": "chat.completion",
+        "created": 0,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": COMPLETION_CONTENT },
+            "finish_reason": "stop",
+        }],
+        "usage": {
+            "prompt_tokens": 10,
+            "completion_tokens": 5,
+            "total_tokens": 15,
+            "cost": COMPLETION_COST,
+        },
+    }))
+}
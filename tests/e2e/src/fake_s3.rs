@@ -0,0 +1,77 @@
+//! A stand-in for S3 that keeps the objects in memory.
+//!
+//! Only path-style requests to put and get objects are supported.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use actix_web::dev::ServerHandle;
+use actix_web::web::{self, Bytes};
+use actix_web::{App, HttpResponse, HttpServer};
+use url::Url;
+
+pub struct FakeS3 {
+    pub url: Url,
+    /// Objects by bucket and key, e.g. `bucket/prefix/tasks/<id>/task.log`
+    pub objects: Arc<Mutex<HashMap<String, Bytes>>>,
+    handle: ServerHandle,
+}
+
+impl FakeS3 {
+    pub async fn start() -> Self {
+        let objects: Arc<Mutex<HashMap<String, Bytes>>> = Default::default();
+
+        let server = {
+            let objects = objects.clone();
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(objects.clone()))
+                    // Task logs can be large
+                    .app_data(web::PayloadConfig::new(64 * 1024 * 1024))
+                    .route("/{path:.*}", web::put().to(put_object))
+                    .route("/{path:.*}", web::get().to(get_object))
+            })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap()
+        };
+
+        let url = Url::parse(&format!("http://{}", server.addrs()[0])).unwrap();
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Self { url, objects, handle }
+    }
+
+    /// The object stored under the bucket and key
+    pub fn object(&self, path: &str) -> Option<Bytes> {
+        self.objects.lock().unwrap().get(path).cloned()
+    }
+
+    pub async fn stop(&self) {
+        self.handle.stop(false).await;
+    }
+}
+
+async fn put_object(
+    objects: web::Data<Arc<Mutex<HashMap<String, Bytes>>>>,
+    path: web::Path<String>,
+    body: Bytes,
+) -> HttpResponse {
+    objects.lock().unwrap().insert(path.into_inner(), body);
+    HttpResponse::Ok().insert_header(("ETag", "\"e2e\"")).finish()
+}
+
+async fn get_object(
+    objects: web::Data<Arc<Mutex<HashMap<String, Bytes>>>>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    match objects.lock().unwrap().get(path.as_str()) {
+        Some(object) => HttpResponse::Ok().body(object.clone()),
+        None => HttpResponse::NotFound().content_type("application/xml").body(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+        ),
+    }
+}
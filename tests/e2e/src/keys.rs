@@ -0,0 +1,48 @@
+//! Throwaway keys for the GitHub app, the session tokens and the encryption of credentials.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_compact::KeyPair;
+use rand::RngCore;
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::RsaPrivateKey;
+
+pub struct KeyFiles {
+    pub github_app_private_key: PathBuf,
+    pub jwt_private_key: PathBuf,
+    pub jwt_public_key: PathBuf,
+    pub encryption_master_key: PathBuf,
+}
+
+/// Generate the keys and write them as PEM files to `dir`
+pub fn write_keys(dir: &Path) -> KeyFiles {
+    let files = KeyFiles {
+        github_app_private_key: dir.join("github-app.pem"),
+        jwt_private_key: dir.join("jwt-private.pem"),
+        jwt_public_key: dir.join("jwt-public.pem"),
+        encryption_master_key: dir.join("master.key"),
+    };
+
+    let rsa_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+    fs::write(&files.github_app_private_key, rsa_key.to_pkcs1_pem(LineEnding::LF).unwrap())
+        .unwrap();
+
+    let key_pair = KeyPair::generate();
+    fs::write(&files.jwt_private_key, key_pair.sk.to_pem()).unwrap();
+    fs::write(&files.jwt_public_key, key_pair.pk.to_pem()).unwrap();
+
+    // Harnesses share the master key, the database library uses one keyring per process
+    static MASTER_KEY: OnceLock<[u8; 32]> = OnceLock::new();
+    let master_key = MASTER_KEY.get_or_init(|| {
+        let mut master_key = [0; 32];
+        rand::thread_rng().fill_bytes(&mut master_key);
+        master_key
+    });
+    fs::write(&files.encryption_master_key, BASE64.encode(master_key)).unwrap();
+
+    files
+}
@@ -0,0 +1,334 @@
+//! End-to-end test harness
+//!
+//! Runs the API server and the dispatcher in-process against stand-ins for GitHub, the LLM
+//! provider and S3, so that the pipeline from webhook to pull request can be tested without
+//! network access. Agents run as a fixture container image on the local Docker daemon.
+//!
+//! Requires `TEST_POSTGRES_URL` to point to a Postgres server on which databases can be created
+//! and a Docker daemon with the image `docker.io/library/buildpack-deps:bookworm-scm` available,
+//! the base image of `fixtures/agent/Containerfile`. It is not pulled by the tests.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use actix_web::rt::task::JoinHandle;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use tempfile::TempDir;
+use tokio::process::Command;
+use url::Url;
+use uuid::Uuid;
+
+use config::Config;
+use database::{
+    Database, Keyring, Task, TaskStatus, Update, UpdateInstallationRepository, UpdateUser,
+};
+
+pub mod fake_github;
+pub mod fake_llm;
+pub mod fake_s3;
+mod keys;
+
+use fake_github::FakeGitHub;
+use fake_llm::FakeLlm;
+use fake_s3::FakeS3;
+
+const WEBHOOK_SECRET: &str = "e2e-webhook-secret";
+pub const BOT_HANDLE: &str = "@minion";
+const AGENT_IMAGE: &str = "autominion-e2e-agent:latest";
+/// Registry host the agent image is tagged with, it is never contacted
+const AGENT_REGISTRY_HOST: &str = "localhost";
+/// Name of the LLM provider that forwards to the LLM stand-in with its own key
+pub const LLM_PROVIDER: &str = "e2e-gateway";
+pub const LLM_PROVIDER_API_KEY: &str = "e2e-gateway-key";
+/// The model the fixture agent asks for and the name the provider knows it by
+pub const AGENT_MODEL: &str = "e2e/fixture";
+pub const LLM_PROVIDER_MODEL: &str = "e2e-gateway/fixture";
+
+pub struct Harness {
+    pub config: Config,
+    pub db: Database,
+    pub github: FakeGitHub,
+    pub llm: FakeLlm,
+    pub s3: FakeS3,
+    client: reqwest::Client,
+    api: JoinHandle<std::io::Result<()>>,
+    dispatcher: JoinHandle<()>,
+    _dir: TempDir,
+}
+
+impl Harness {
+    /// Start the stand-ins, the API server and the dispatcher on a fresh database
+    pub async fn start() -> Self {
+        let postgres_url = test_postgres_url();
+        build_agent_image().await;
+
+        let github = FakeGitHub::start().await;
+        let llm = FakeLlm::start().await;
+        let s3 = FakeS3::start().await;
+
+        let dir = TempDir::new().unwrap();
+        let config_path = write_config(dir.path(), &postgres_url, &github, &llm, &s3);
+        let config = Config::load_from(&config_path);
+
+        let api = actix_web::rt::spawn(api::run(config.clone()));
+        let client = reqwest::Client::new();
+        wait_until_ready(&client, &config.web_base_url).await;
+        // The API server creates the database and runs the migrations on startup
+        let encryption = &config.encryption;
+        let keyring = Keyring::new(&encryption.master_key, &encryption.previous_master_keys);
+        let db = Database::connect(config.postgres_url.as_str(), keyring).await;
+
+        // The agent reaches the API on localhost and the test machine doesn't need Sysbox
+        let args = dispatcher::daemon::Args { local: true, host_network: true };
+        let dispatcher = actix_web::rt::spawn(dispatcher::daemon::run(config.clone(), args));
+
+        Self { config, db, github, llm, s3, client, api, dispatcher, _dir: dir }
+    }
+
+    /// Send a signed webhook to the API server like GitHub does
+    pub async fn send_webhook(&self, event: &str, payload: &Value) {
+        let body = serde_json::to_vec(payload).unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(WEBHOOK_SECRET.as_bytes()).unwrap();
+        mac.update(&body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let response = self
+            .client
+            .post(self.config.web_base_url.join("/webhooks/github").unwrap())
+            .header("X-GitHub-Event", event)
+            .header("X-GitHub-Delivery", Uuid::new_v4().to_string())
+            .header("X-Hub-Signature-256", signature)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "Webhook {event} failed: {}", response.status());
+    }
+
+    /// Install the app on the organization and enable the repository and the member
+    pub async fn install_app(&self) {
+        self.send_webhook("installation", &fake_github::installation_created_event()).await;
+        self.wait_for_webhooks(Duration::from_secs(60)).await;
+
+        let mut conn = self.db.conn().await;
+        let user = conn.get_user_by_github_id(fake_github::USER_NODE_ID).await.unwrap();
+        conn.update_user(
+            UpdateUser::default().id(user.id).active(true).openrouter_key(Some("e2e".to_owned())),
+        )
+        .await;
+
+        let installation =
+            conn.get_installation_by_github_id(fake_github::INSTALLATION_ID).await.unwrap();
+        let repo = conn.get_repository_by_full_name(fake_github::REPO_FULL_NAME).await;
+        conn.update_installation_repository(
+            UpdateInstallationRepository {
+                installation_id: installation.id,
+                repository_id: repo.id,
+                ..Default::default()
+            }
+            .active(true),
+        )
+        .await;
+    }
+
+    /// Send the LLM requests of tasks on the repository to the given provider
+    pub async fn set_repository_llm_provider(&self, llm_provider: Option<&str>) {
+        let mut conn = self.db.conn().await;
+        let repo = conn.get_repository_by_full_name(fake_github::REPO_FULL_NAME).await;
+        let inst_repo = conn.installation_repository_by_repo_id(repo.id).await;
+        let update = inst_repo.update().llm_provider(llm_provider.map(ToOwned::to_owned));
+        conn.update_installation_repository(update).await;
+    }
+
+    /// Pay for the tasks of the installation with the given OpenRouter key
+    pub async fn set_installation_openrouter_key(&self, openrouter_key: Option<&str>) {
+        let mut conn = self.db.conn().await;
+        let installation =
+            conn.get_installation_by_github_id(fake_github::INSTALLATION_ID).await.unwrap();
+        let update = installation.update().openrouter_key(openrouter_key.map(ToOwned::to_owned));
+        conn.update_installation(update).await;
+    }
+
+    /// Wait until the inbox handled all webhook deliveries
+    pub async fn wait_for_webhooks(&self, timeout: Duration) {
+        let start = Instant::now();
+        while self.db.conn().await.pending_webhook_deliveries_count().await > 0 {
+            assert!(start.elapsed() < timeout, "Timed out waiting for webhook deliveries");
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    /// Wait until the member has a task that is no longer queued or running
+    pub async fn wait_for_finished_task(&self, timeout: Duration) -> Task {
+        let user_id = {
+            let mut conn = self.db.conn().await;
+            conn.get_user_by_github_id(fake_github::USER_NODE_ID).await.unwrap().id
+        };
+        let start = Instant::now();
+        loop {
+            let mut conn = self.db.conn().await;
+            let finished = conn
+                .get_tasks_for_user(&user_id)
+                .await
+                .into_iter()
+                .map(|(task, _repo)| task)
+                .find(|task| !matches!(task.status, TaskStatus::Queued | TaskStatus::Running));
+            if let Some(task) = finished {
+                return task;
+            }
+            assert!(start.elapsed() < timeout, "Timed out waiting for the task to finish");
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Wait until `condition` holds for the writes recorded by the GitHub stand-in
+    pub async fn wait_for_github(
+        &self,
+        timeout: Duration,
+        condition: impl Fn(&fake_github::Recorded) -> bool,
+    ) {
+        let start = Instant::now();
+        while !condition(&self.github.recorded.lock().unwrap()) {
+            assert!(start.elapsed() < timeout, "Timed out waiting for GitHub writes");
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    pub async fn stop(self) {
+        self.dispatcher.abort();
+        self.api.abort();
+        self.github.stop().await;
+        self.llm.stop().await;
+        self.s3.stop().await;
+    }
+}
+
+fn test_postgres_url() -> Url {
+    match dotenvy::dotenv() {
+        Ok(_) => {}
+        Err(dotenvy::Error::Io(err)) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                panic!("Failed to load .env file: {}", err);
+            }
+        }
+        Err(err) => panic!("Failed to load .env file: {}", err),
+    }
+
+    let mut url: Url = env::var("TEST_POSTGRES_URL")
+        .expect("TEST_POSTGRES_URL must be set")
+        .parse()
+        .expect("Failed to parse TEST_POSTGRES_URL");
+    // Each run gets its own database
+    url.set_path(&format!("/e2e_{}", Uuid::new_v4().simple()));
+    url
+}
+
+/// Build the fixture agent and tag it as if it was pulled from the registry
+async fn build_agent_image() {
+    let context = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/agent");
+    let status = Command::new("docker")
+        .args(["build", "--pull=false", "-f", "Containerfile", "-t"])
+        .arg(format!("{AGENT_REGISTRY_HOST}/{AGENT_IMAGE}"))
+        .arg(".")
+        .current_dir(context)
+        .status()
+        .await
+        .expect("Failed to run docker");
+    assert!(status.success(), "Failed to build the fixture agent image");
+}
+
+fn write_config(
+    dir: &Path,
+    postgres_url: &Url,
+    github: &FakeGitHub,
+    llm: &FakeLlm,
+    s3: &FakeS3,
+) -> PathBuf {
+    let keys = keys::write_keys(dir);
+    let port = free_port();
+
+    let config = format!(
+        r#"
+service_name = "autominion e2e"
+host = "127.0.0.1"
+port = {port}
+aws_access_key_id = "unused"
+aws_secret_access_key = "unused"
+aws_region = "unused"
+aws_image_id = "unused"
+github_git_name = "autominion e2e"
+github_git_email = "e2e@example.com"
+github_app_id = "1"
+github_app_client_id = "e2e"
+github_app_client_secret = "e2e"
+github_app_webhook_secret = "{WEBHOOK_SECRET}"
+github_app_private_key = "{github_app_private_key}"
+github_bot_handle = "{BOT_HANDLE}"
+github_api_user_agent = "autominion-e2e"
+github_web_url = "{github_url}"
+github_api_url = "{github_url}"
+llm_chat_completions_url = "{llm_url}"
+postgres_url = "{postgres_url}"
+web_base_url = "http://127.0.0.1:{port}"
+default_agent_container_registry_host = "{AGENT_REGISTRY_HOST}"
+default_agent_container_registry_username = ""
+default_agent_container_registry_password = ""
+default_agent_container_image = "{AGENT_IMAGE}"
+jwt_private_key = "{jwt_private_key}"
+jwt_public_key = "{jwt_public_key}"
+access_control = "Waitlist"
+s3_endpoint = "{s3_url}"
+s3_region = "us-east-1"
+s3_bucket = "e2e"
+s3_access_key = "e2e"
+s3_secret_key = "e2e"
+s3_prefix = "e2e"
+s3_force_path_style = true
+static_dir = "{static_dir}"
+
+[encryption]
+master_key_file = "{encryption_master_key}"
+
+[[llm_providers]]
+name = "{LLM_PROVIDER}"
+chat_completions_url = "{llm_url}"
+auth = "Bearer"
+api_key = "{LLM_PROVIDER_API_KEY}"
+installations = [{installation_id}]
+
+[llm_providers.models]
+"{AGENT_MODEL}" = "{LLM_PROVIDER_MODEL}"
+"#,
+        github_app_private_key = keys.github_app_private_key.display(),
+        github_url = github.url,
+        llm_url = llm.url,
+        s3_url = s3.url,
+        installation_id = fake_github::INSTALLATION_ID,
+        jwt_private_key = keys.jwt_private_key.display(),
+        jwt_public_key = keys.jwt_public_key.display(),
+        encryption_master_key = keys.encryption_master_key.display(),
+        static_dir = dir.display(),
+    );
+
+    let path = dir.join("config.toml");
+    std::fs::write(&path, config).unwrap();
+    path
+}
+
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+async fn wait_until_ready(client: &reqwest::Client, base_url: &Url) {
+    let url = base_url.join("/ready").unwrap();
+    let start = Instant::now();
+    while client.get(url.clone()).send().await.is_err() {
+        assert!(start.elapsed() < Duration::from_secs(60), "API server did not start");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
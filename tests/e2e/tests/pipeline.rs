@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use database::{LlmKeySource, TaskStatus};
+use e2e::fake_github::{self, COMMENT_NODE_ID, ISSUE_NODE_ID, REPO_NODE_ID};
+use e2e::{fake_llm, Harness, BOT_HANDLE};
+use e2e::{AGENT_MODEL, LLM_PROVIDER, LLM_PROVIDER_API_KEY, LLM_PROVIDER_MODEL};
+
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[actix_web::test]
+async fn test_solve_comment_opens_pull_request() {
+    let harness = Harness::start().await;
+    harness.install_app().await;
+
+    let comment = fake_github::issue_comment_created_event(&format!("{BOT_HANDLE} solve"));
+    harness.send_webhook("issue_comment", &comment).await;
+
+    let task = harness.wait_for_finished_task(TIMEOUT).await;
+    assert!(matches!(task.status, TaskStatus::Completed), "Task {:?}", task.status);
+
+    let branch_ref = format!("refs/heads/{}", task.id);
+    // The status comment is updated after the pull request is opened
+    let status_completed = |(_, body): &(String, String)| body.ends_with("completed.");
+    harness
+        .wait_for_github(TIMEOUT, |recorded| recorded.comments.iter().any(status_completed))
+        .await;
+
+    {
+        let recorded = harness.github.recorded.lock().unwrap();
+        let pull_request = &recorded.pull_requests[0];
+        assert_eq!(pull_request.repo_id, REPO_NODE_ID);
+        assert_eq!(pull_request.base_ref, "main");
+        assert_eq!(pull_request.head_ref, branch_ref);
+
+        // The command is acknowledged with a reaction
+        assert_eq!(recorded.reactions, [(COMMENT_NODE_ID.to_owned(), "EYES".to_owned())]);
+
+        // One status comment that is updated when the work is done
+        assert_eq!(recorded.comments.len(), 1);
+        let (subject_id, body) = &recorded.comments[0];
+        assert_eq!(subject_id, ISSUE_NODE_ID);
+        assert!(body.ends_with("completed."), "Status comment {body:?}");
+
+        // The check run started on the base commit and the final state is on the agent commit
+        let conclusions: Vec<_> =
+            recorded.check_runs.iter().map(|check_run| check_run.conclusion.as_deref()).collect();
+        assert_eq!(conclusions, [Some("success"), Some("success")]);
+        assert!(recorded
+            .check_runs
+            .iter()
+            .all(|check_run| check_run.external_id == task.id.to_string()));
+    }
+
+    // The agent pushed its commit to the task branch through the git proxy
+    let subject = harness.github.git(&["log", "-1", "--format=%s", &branch_ref]).await;
+    assert_eq!(subject.trim(), "Fixture agent commit");
+
+    // The log of the agent was stored
+    let log = harness.s3.object(&format!("e2e/e2e/tasks/{}/task.log", task.id));
+    assert!(log.is_some_and(|log| !log.is_empty()), "No log stored");
+
+    // The completion was forwarded to the LLM provider and recorded
+    {
+        let requests = harness.llm.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].authorization.as_deref(), Some("Bearer e2e"));
+        assert_eq!(requests[0].body["model"], AGENT_MODEL);
+    }
+    let interactions = harness.db.conn().await.llm_interactions(&task.id).await;
+    assert_eq!(interactions.len(), 1);
+    assert_eq!(interactions[0].key_source, Some(LlmKeySource::User));
+    let response = interactions[0].response.as_ref().unwrap();
+    assert_eq!(
+        response["choices"][0]["message"]["content"].as_str(),
+        Some(fake_llm::COMPLETION_CONTENT)
+    );
+    // Its usage counts towards the budgets
+    let usage = harness.db.conn().await.task_llm_usage(&task.id).await;
+    assert_eq!(
+        (usage.prompt_tokens, usage.completion_tokens, usage.cost),
+        (10, 5, fake_llm::COMPLETION_COST)
+    );
+
+    harness.stop().await;
+}
+
+#[actix_web::test]
+async fn test_question_is_answered_and_task_continues() {
+    let harness = Harness::start().await;
+    harness.install_app().await;
+
+    let body = format!("{BOT_HANDLE} solve\n\nAsk a question first.");
+    harness.send_webhook("issue_comment", &fake_github::issue_comment_created_event(&body)).await;
+
+    let task = harness.wait_for_finished_task(TIMEOUT).await;
+    assert!(matches!(task.status, TaskStatus::WaitingForInput), "Task {:?}", task.status);
+    let asked = |(_, body): &(String, String)| body.contains("Which directory?");
+    harness.wait_for_github(TIMEOUT, |recorded| recorded.comments.iter().any(asked)).await;
+
+    // The agent doesn't exit after asking, the dispatcher stops it
+    let action_required = |check_run: &fake_github::CheckRun| {
+        check_run.conclusion.as_deref() == Some("action_required")
+    };
+    harness
+        .wait_for_github(TIMEOUT, |recorded| recorded.check_runs.iter().any(action_required))
+        .await;
+    let container = Command::new("docker")
+        .args(["ps", "--all", "--quiet", "--filter", &format!("name=minion-{}", task.id)])
+        .output()
+        .await
+        .unwrap();
+    assert!(container.stdout.is_empty(), "The agent container was not removed");
+
+    // The reply of the issue author answers the question and queues the task again
+    let answer = fake_github::issue_comment_created_event("Put it in `docs/`.");
+    harness.send_webhook("issue_comment", &answer).await;
+    harness.wait_for_webhooks(TIMEOUT).await;
+
+    let task = harness.wait_for_finished_task(TIMEOUT).await;
+    assert!(matches!(task.status, TaskStatus::Completed), "Task {:?}", task.status);
+    let questions = harness.db.conn().await.task_questions(&task.id).await;
+    assert_eq!(questions.len(), 1);
+    assert_eq!(questions[0].answer.as_deref(), Some("Put it in `docs/`."));
+
+    let status_completed = |(_, body): &(String, String)| body.ends_with("completed.");
+    harness
+        .wait_for_github(TIMEOUT, |recorded| recorded.comments.iter().any(status_completed))
+        .await;
+    assert_eq!(harness.github.recorded.lock().unwrap().pull_requests.len(), 1);
+
+    harness.stop().await;
+}
+
+#[actix_web::test]
+async fn test_repository_llm_provider() {
+    let harness = Harness::start().await;
+    harness.install_app().await;
+    harness.set_repository_llm_provider(Some(LLM_PROVIDER)).await;
+
+    let comment = fake_github::issue_comment_created_event(&format!("{BOT_HANDLE} solve"));
+    harness.send_webhook("issue_comment", &comment).await;
+
+    let task = harness.wait_for_finished_task(TIMEOUT).await;
+    assert!(matches!(task.status, TaskStatus::Completed), "Task {:?}", task.status);
+
+    // The provider got the request with its key and its name of the model
+    {
+        let requests = harness.llm.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let authorization = format!("Bearer {LLM_PROVIDER_API_KEY}");
+        assert_eq!(requests[0].authorization.as_deref(), Some(authorization.as_str()));
+        assert_eq!(requests[0].body["model"], LLM_PROVIDER_MODEL);
+    }
+
+    harness.stop().await;
+}
+
+#[actix_web::test]
+async fn test_installation_openrouter_key() {
+    let harness = Harness::start().await;
+    harness.install_app().await;
+    harness.set_installation_openrouter_key(Some("e2e-installation")).await;
+
+    let comment = fake_github::issue_comment_created_event(&format!("{BOT_HANDLE} solve"));
+    harness.send_webhook("issue_comment", &comment).await;
+
+    let task = harness.wait_for_finished_task(TIMEOUT).await;
+    assert!(matches!(task.status, TaskStatus::Completed), "Task {:?}", task.status);
+
+    // The key of the installation takes precedence over the key of the user by default
+    {
+        let requests = harness.llm.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].authorization.as_deref(), Some("Bearer e2e-installation"));
+    }
+    let interactions = harness.db.conn().await.llm_interactions(&task.id).await;
+    assert_eq!(interactions[0].key_source, Some(LlmKeySource::Installation));
+
+    harness.stop().await;
+}
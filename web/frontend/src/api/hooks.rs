@@ -7,7 +7,10 @@ use serde::Serialize;
 use leptos::prelude::*;
 use leptos_router::hooks::use_navigate;
 
-use user_api::{OpenRouterStatus, Repo, RepoUserInfo, TaskDetails, TaskInfo, UserInfo};
+use user_api::{
+    InstallationInfo, OpenRouterStatus, Repo, RepoUserInfo, TaskArtifact, TaskDetails, TaskInfo,
+    UserInfo, WebhookDelivery,
+};
 
 use crate::api::http;
 use crate::api::http::ApiError;
@@ -75,6 +78,15 @@ pub fn use_task_logs(id: impl ToString) -> LocalResource<Result<Option<String>,
     })
 }
 
+/// Fetches the artifacts the agent uploaded for a task.
+pub fn use_task_artifacts(id: impl ToString) -> LocalResource<Result<Vec<TaskArtifact>, ApiError>> {
+    let id = id.to_string();
+    use_api(move || {
+        let id = id.clone();
+        async move { http::task_artifacts(&id).await }
+    })
+}
+
 /// Fetches the list of tasks.
 pub fn use_tasks() -> LocalResource<Result<Vec<TaskInfo>, ApiError>> {
     use_api(|| async { http::tasks().await })
@@ -89,6 +101,16 @@ pub fn use_repo(id: impl ToString) -> LocalResource<Result<Repo, ApiError>> {
     })
 }
 
+/// Fetches the names of the LLM providers repositories can choose from.
+pub fn use_llm_providers() -> LocalResource<Result<Vec<String>, ApiError>> {
+    use_api(|| async { http::llm_providers().await })
+}
+
+/// Fetches the installations the user administers.
+pub fn use_installations() -> LocalResource<Result<Vec<InstallationInfo>, ApiError>> {
+    use_api(|| async { http::installations().await })
+}
+
 /// Fetches the repository’s users.
 pub fn use_repo_users(
     repo_id: impl ToString,
@@ -99,3 +121,8 @@ pub fn use_repo_users(
         async move { http::repo_users(&repo_id).await }
     })
 }
+
+/// Fetches the recent webhook deliveries.
+pub fn use_webhook_deliveries() -> LocalResource<Result<Vec<WebhookDelivery>, ApiError>> {
+    use_api(|| async { http::webhook_deliveries().await })
+}
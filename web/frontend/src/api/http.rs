@@ -54,6 +54,46 @@ pub async fn remove_repo(id: &str) -> Result<(), ApiError> {
     delete(&format!("repos/{}/active", id)).await
 }
 
+pub async fn set_trigger_label(id: &str, trigger_label: Option<String>) -> Result<(), ApiError> {
+    put_json(&format!("repos/{}/trigger-label", id), &SetTriggerLabelRequest { trigger_label })
+        .await
+}
+
+pub async fn set_repo_llm_provider(id: &str, llm_provider: Option<String>) -> Result<(), ApiError> {
+    put_json(&format!("repos/{}/llm-provider", id), &SetLlmProviderRequest { llm_provider }).await
+}
+
+pub async fn llm_providers() -> Result<Vec<String>, ApiError> {
+    get_json("llm-providers").await
+}
+
+pub async fn installations() -> Result<Vec<InstallationInfo>, ApiError> {
+    get_json("installations").await
+}
+
+pub async fn set_installation_openrouter_key(
+    id: &str,
+    openrouter_key: String,
+) -> Result<(), ApiError> {
+    put_json(
+        &format!("installations/{}/openrouter-key", id),
+        &SetOpenRouterKeyRequest { openrouter_key },
+    )
+    .await
+}
+
+pub async fn delete_installation_openrouter_key(id: &str) -> Result<(), ApiError> {
+    delete(&format!("installations/{}/openrouter-key", id)).await
+}
+
+pub async fn set_installation_llm_key_precedence(
+    id: &str,
+    llm_key_precedence: LlmKeyPrecedence,
+) -> Result<(), ApiError> {
+    let request = SetLlmKeyPrecedenceRequest { llm_key_precedence };
+    put_json(&format!("installations/{}/llm-key-precedence", id), &request).await
+}
+
 pub async fn repo_users(id: &str) -> Result<Vec<RepoUserInfo>, ApiError> {
     get_json(&format!("repos/{}/users", id)).await
 }
@@ -86,10 +126,22 @@ pub async fn task(id: &str) -> Result<TaskDetails, ApiError> {
     get_json(&format!("tasks/{}", id)).await
 }
 
+pub async fn replay_task(id: &str) -> Result<TaskInfo, ApiError> {
+    post_for_json(&format!("tasks/{}/replay", id)).await
+}
+
 pub async fn task_logs(id: &str) -> Result<String, ApiError> {
     get_raw_text(&format!("tasks/{}/logs", id)).await
 }
 
+pub async fn task_artifacts(id: &str) -> Result<Vec<TaskArtifact>, ApiError> {
+    get_json(&format!("tasks/{}/artifacts", id)).await
+}
+
+pub async fn webhook_deliveries() -> Result<Vec<WebhookDelivery>, ApiError> {
+    get_json("webhook-deliveries").await
+}
+
 /// Perform an HTTP GET and parses the response as JSON.
 pub async fn get_json<T: DeserializeOwned>(path: &str) -> Result<T, ApiError> {
     send_request(reqwest::Method::GET, path, |b| b, |r| r.json::<T>()).await
@@ -105,6 +157,11 @@ pub async fn put(path: &str) -> Result<(), ApiError> {
     send_request(reqwest::Method::PUT, path, |b| b, |_| async { Ok(()) }).await
 }
 
+/// Perform an HTTP PUT with a JSON body. Returns an empty result on success.
+pub async fn put_json<T: Serialize>(path: &str, body: T) -> Result<(), ApiError> {
+    send_request(reqwest::Method::PUT, path, |b| b.json(&body), |_| async { Ok(()) }).await
+}
+
 /// Perform an HTTP DELETE. Returns an empty result on success.
 pub async fn delete(path: &str) -> Result<(), ApiError> {
     send_request(reqwest::Method::DELETE, path, |b| b, |_| async { Ok(()) }).await
@@ -115,6 +172,11 @@ pub async fn post_json<T: Serialize>(path: &str, body: T) -> Result<(), ApiError
     send_request(reqwest::Method::POST, path, |b| b.json(&body), |_| async { Ok(()) }).await
 }
 
+/// Perform an HTTP POST without any request body and parses the response as JSON.
+pub async fn post_for_json<T: DeserializeOwned>(path: &str) -> Result<T, ApiError> {
+    send_request(reqwest::Method::POST, path, |b| b, |r| r.json::<T>()).await
+}
+
 /// Send a request with the given HTTP method and path.
 /// The `configure` closure allows further modification of the request (for example,
 /// adding a JSON body), and the `extractor` closure is used to parse the response.
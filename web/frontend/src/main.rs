@@ -90,6 +90,10 @@ pub fn App() -> impl IntoView {
                             }
                         }
                     />
+                    <Route
+                        path=path!("webhook-deliveries")
+                        view=|| view! { <WithUser><WebhookDeliveriesPage/></WithUser> }
+                    />
                     <Route
                         path=path!("privacy")
                         view=|| view! { <PrivacyPage/> }
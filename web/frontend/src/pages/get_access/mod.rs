@@ -49,6 +49,7 @@ pub fn Waitlist() -> impl IntoView {
                 let navigate = navigate.clone();
                 let error_store = error_store;
                 let user_info = user_info;
+                let emails_url = format!("{}settings/emails", info.github_url);
 
                 view! {
                         <>
@@ -56,7 +57,7 @@ pub fn Waitlist() -> impl IntoView {
                                 <b>"You are on the waitlist! "</b>
                                 "We will notify you as soon as we can grant you access. "
                                 "The notification will be sent to your primary "
-                                <a target="_blank" href="https://github.com/settings/emails">
+                                <a target="_blank" href=emails_url>
                                     "GitHub email address"
                                 </a>
                                 " at "
@@ -9,6 +9,7 @@ mod repos;
 mod settings;
 mod task;
 mod tasks;
+mod webhook_deliveries;
 
 pub use get_access::WaitlistPage;
 pub use landing::LandingPage;
@@ -21,3 +22,4 @@ pub use repos::ReposPage;
 pub use settings::SettingsPage;
 pub use task::TaskPage;
 pub use tasks::TasksPage;
+pub use webhook_deliveries::WebhookDeliveriesPage;
@@ -10,7 +10,7 @@ use user_api::RepoUserInfo;
 use crate::components::*;
 use crate::errors::handle_api_result;
 use crate::{
-    api::{http, use_repo, use_repo_users},
+    api::{http, use_llm_providers, use_repo, use_repo_users},
     routes::paths,
 };
 
@@ -35,6 +35,7 @@ pub fn RepoPage(id: String) -> impl IntoView {
 pub fn RepoPageContent(id: String) -> impl IntoView {
     let repo_resource = use_repo(id.clone());
     let repo_users_resource = use_repo_users(id.clone());
+    let llm_providers_resource = use_llm_providers();
     let navigate = Arc::new(use_navigate());
     let error_store = expect_context::<RwSignal<crate::errors::ErrorStore>>();
 
@@ -43,7 +44,55 @@ pub fn RepoPageContent(id: String) -> impl IntoView {
     let removing_user = RwSignal::new(None::<RepoUserInfo>);
     let removing_repo = RwSignal::new(false);
 
+    let trigger_label = RwSignal::new(String::new());
+    let llm_provider = RwSignal::new(String::new());
+
     let on_change = Callback::new(move |value: String| new_user_login.set(value));
+    let on_trigger_label_change = Callback::new(move |value: String| trigger_label.set(value));
+
+    // Start editing from the saved label once the repository is loaded
+    Effect::new(move |_| {
+        if let Some(Ok(repo)) = repo_resource.get().map(|sw| sw.take()) {
+            trigger_label.set(repo.trigger_label.unwrap_or_default());
+            llm_provider.set(repo.llm_provider.unwrap_or_default());
+        }
+    });
+
+    let on_trigger_label_submit = {
+        let id = id.clone();
+        let navigate = navigate.clone();
+        move |ev: SubmitEvent| {
+            ev.prevent_default();
+            let id = id.clone();
+            let navigate = navigate.clone();
+            let error_store = error_store;
+            let label = trigger_label.get().trim().to_string();
+            let label = (!label.is_empty()).then_some(label);
+            spawn_local(async move {
+                let result = http::set_trigger_label(&id, label).await;
+                let _ = handle_api_result(result, navigate, &error_store);
+                repo_resource.refetch();
+            });
+        }
+    };
+
+    let on_llm_provider_submit = {
+        let id = id.clone();
+        let navigate = navigate.clone();
+        move |ev: SubmitEvent| {
+            ev.prevent_default();
+            let id = id.clone();
+            let navigate = navigate.clone();
+            let error_store = error_store;
+            let provider = llm_provider.get();
+            let provider = (!provider.is_empty()).then_some(provider);
+            spawn_local(async move {
+                let result = http::set_repo_llm_provider(&id, provider).await;
+                let _ = handle_api_result(result, navigate, &error_store);
+                repo_resource.refetch();
+            });
+        }
+    };
 
     let on_add_user = {
         let id = id.clone();
@@ -124,7 +173,12 @@ pub fn RepoPageContent(id: String) -> impl IntoView {
     move || {
         let repo_option = repo_resource.get().map(|sw| sw.take());
         let users_option = repo_users_resource.get().map(|sw| sw.take());
+        let llm_providers = (llm_providers_resource.get().map(|sw| sw.take()))
+            .and_then(Result::ok)
+            .unwrap_or_default();
         let on_add_user_submit = on_add_user_submit.clone();
+        let on_trigger_label_submit = on_trigger_label_submit.clone();
+        let on_llm_provider_submit = on_llm_provider_submit.clone();
         let on_confirm_remove_user = on_confirm_remove_user.clone();
         let on_remove_action = on_remove_action.clone();
         if let (Some(Ok(repo)), Some(Ok(users))) = (repo_option, users_option) {
@@ -134,7 +188,7 @@ pub fn RepoPageContent(id: String) -> impl IntoView {
                         <p>
                             { format!("Configure {} for ", crate::whitelabel::SERVICE_NAME) }
                             <a
-                                href=format!("https://github.com/{}", repo.name)
+                                href=repo.url.clone()
                                 target="_blank"
                             >
                                 {repo.name.clone()}
@@ -192,6 +246,61 @@ pub fn RepoPageContent(id: String) -> impl IntoView {
                             }.into_any()
                         }}
 
+                        <div style="margin-top: 2em"></div>
+                        <h2>{ "Trigger Label" }</h2>
+                        <p>
+                            { "Adding this label to an issue or assigning " }
+                            <b>{ crate::whitelabel::GITHUB_BOT_HANDLE }</b>
+                            { " to it starts a task, just like commenting " }
+                            <b>{ format!("{} solve", crate::whitelabel::GITHUB_BOT_HANDLE) }</b>
+                            { ". Leave the label empty to only react to assignments." }
+                        </p>
+
+                        <form on:submit=on_trigger_label_submit>
+                            <div class="input-row">
+                                <TextInput
+                                    value=trigger_label
+                                    on_change=on_trigger_label_change
+                                    placeholder="Label".to_owned()
+                                />
+                                <button type="submit" class="primary">
+                                    { "Save" }
+                                </button>
+                            </div>
+                        </form>
+
+                        {(!llm_providers.is_empty()).then(|| view! {
+                            <div style="margin-top: 2em"></div>
+                            <h2>{ "LLM Provider" }</h2>
+                            <p>
+                                { "The agent sends its LLM requests to this provider. " }
+                                { "By default it uses the provider of the installation, or OpenRouter " }
+                                { "with the key of the user who started the task." }
+                            </p>
+
+                            <form on:submit=on_llm_provider_submit>
+                                <div class="input-row">
+                                    <select on:change=move |ev| llm_provider.set(event_target_value(&ev))>
+                                        <option value="" selected=move || llm_provider.get().is_empty()>
+                                            { "Default" }
+                                        </option>
+                                        {llm_providers.into_iter().map(|name| {
+                                            let selected = {
+                                                let name = name.clone();
+                                                move || llm_provider.get() == name
+                                            };
+                                            view! {
+                                                <option value=name.clone() selected=selected>{name}</option>
+                                            }
+                                        }).collect_view()}
+                                    </select>
+                                    <button type="submit" class="primary">
+                                        { "Save" }
+                                    </button>
+                                </div>
+                            </form>
+                        })}
+
                         <div style="margin-top: 2em"></div>
                         <h2>{ "Danger Zone" }</h2>
                         <p>
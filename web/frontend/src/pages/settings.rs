@@ -1,13 +1,16 @@
 use std::sync::Arc;
 
+use leptos::ev::SubmitEvent;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos_router::hooks::use_navigate;
+use user_api::{InstallationInfo, LlmKeyPrecedence};
 use web_sys::window;
 
 use crate::api;
 use crate::components::*;
 use crate::errors::handle_api_result;
+use crate::routes::paths;
 
 #[component]
 pub fn SettingsPage() -> impl IntoView {
@@ -104,6 +107,13 @@ pub fn SettingsPageContent() -> impl IntoView {
                         <b>"not"</b>
                         " apply any extra charges for using OpenRouter."
                     </p>
+                    <InstallationSettings />
+                    <h2>"Webhook deliveries"</h2>
+                    <p>
+                        "Administrators of an installation can inspect the "
+                        <a href=paths::WEBHOOK_DELIVERIES>"recent webhook deliveries"</a>
+                        " from GitHub and whether they were processed successfully."
+                    </p>
                     <Modal
                         title="Disconnect OpenRouter".to_owned()
                         visible=confirm_modal
@@ -129,3 +139,131 @@ pub fn SettingsPageContent() -> impl IntoView {
         }
     }
 }
+
+/// OpenRouter keys of the installations the user administers
+#[component]
+fn InstallationSettings() -> impl IntoView {
+    let installations_resource = api::use_installations();
+
+    move || {
+        let installations = (installations_resource.get().map(|sw| sw.take()))
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        let on_saved = Callback::new(move |_: ()| installations_resource.refetch());
+
+        (!installations.is_empty()).then(|| {
+            view! {
+                <h2>"Installations"</h2>
+                <p>
+                    "An installation can pay for the tasks of all its users with an OpenRouter key of the organization. "
+                    "Choose whether the key of the installation or the key of the user who started a task is used first. "
+                    "The other key is the fallback."
+                </p>
+                {installations.into_iter().map(|installation| view! {
+                    <InstallationKeySettings installation=installation on_saved=on_saved />
+                }).collect_view()}
+            }
+        })
+    }
+}
+
+#[component]
+fn InstallationKeySettings(
+    installation: InstallationInfo,
+    on_saved: Callback<()>,
+) -> impl IntoView {
+    let navigate = Arc::new(use_navigate());
+    let error_store = expect_context::<RwSignal<crate::errors::ErrorStore>>();
+    let openrouter_key = RwSignal::new(String::new());
+    let id = installation.id.clone();
+
+    let on_key_submit = {
+        let id = id.clone();
+        let navigate = navigate.clone();
+        move |ev: SubmitEvent| {
+            ev.prevent_default();
+            let key = openrouter_key.get().trim().to_string();
+            if key.is_empty() {
+                return;
+            }
+            let id = id.clone();
+            let navigate = navigate.clone();
+            spawn_local(async move {
+                let result = api::http::set_installation_openrouter_key(&id, key).await;
+                if handle_api_result(result, navigate, &error_store).is_ok() {
+                    openrouter_key.set(String::new());
+                }
+                on_saved.run(());
+            });
+        }
+    };
+
+    let on_key_remove = {
+        let id = id.clone();
+        let navigate = navigate.clone();
+        move |_| {
+            let id = id.clone();
+            let navigate = navigate.clone();
+            spawn_local(async move {
+                let result = api::http::delete_installation_openrouter_key(&id).await;
+                let _ = handle_api_result(result, navigate, &error_store);
+                on_saved.run(());
+            });
+        }
+    };
+
+    let on_precedence_change = move |ev| {
+        let precedence = match event_target_value(&ev).as_str() {
+            "UserFirst" => LlmKeyPrecedence::UserFirst,
+            _ => LlmKeyPrecedence::InstallationFirst,
+        };
+        let id = id.clone();
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            let result = api::http::set_installation_llm_key_precedence(&id, precedence).await;
+            let _ = handle_api_result(result, navigate, &error_store);
+            on_saved.run(());
+        });
+    };
+
+    let user_first = installation.llm_key_precedence == LlmKeyPrecedence::UserFirst;
+
+    view! {
+        <h3>{installation.name.clone()}</h3>
+        <p>
+            { if installation.openrouter_key_set {
+                "An OpenRouter key is set for this installation."
+            } else {
+                "No OpenRouter key is set for this installation."
+            }}
+        </p>
+        <form on:submit=on_key_submit>
+            <div class="input-row">
+                <input
+                    type="password"
+                    bind:value=openrouter_key
+                    placeholder="OpenRouter API key"
+                    autocomplete="off"
+                />
+                <button type="submit" class="primary">
+                    { if installation.openrouter_key_set { "Replace Key" } else { "Save Key" } }
+                </button>
+                {installation.openrouter_key_set.then(|| view! {
+                    <button type="button" class="danger" on:click=on_key_remove>
+                        "Remove Key"
+                    </button>
+                })}
+            </div>
+        </form>
+        <div class="input-row">
+            <select on:change=on_precedence_change>
+                <option value="InstallationFirst" selected=!user_first>
+                    "Use the key of the installation first"
+                </option>
+                <option value="UserFirst" selected=user_first>
+                    "Use the key of the user first"
+                </option>
+            </select>
+        </div>
+    }
+}
@@ -0,0 +1,74 @@
+use leptos::prelude::*;
+
+use user_api::TaskArtifact;
+
+use crate::api::use_task_artifacts;
+
+#[component]
+pub fn Artifacts(task_id: String) -> impl IntoView {
+    let artifacts = use_task_artifacts(&task_id);
+
+    move || {
+        let Some(Ok(artifacts)) = artifacts.get().map(|sw| sw.take()) else {
+            return {
+                view! { <></> };
+                ().into_any()
+            };
+        };
+
+        if artifacts.is_empty() {
+            return view! {
+                <>
+                    <p>"The agent has not uploaded any artifacts."</p>
+                </>
+            }
+            .into_any();
+        }
+
+        let task_id = task_id.clone();
+        view! {
+            <>
+                <p>"Files the agent uploaded during this run."</p>
+                <ul class="listbox">
+                    <For
+                        each=move || artifacts.clone()
+                        key=|artifact| artifact.name.clone()
+                        children=move |artifact| view! {
+                            <ArtifactItem task_id=task_id.clone() artifact=artifact />
+                        }
+                    />
+                </ul>
+            </>
+        }
+        .into_any()
+    }
+}
+
+#[component]
+fn ArtifactItem(task_id: String, artifact: TaskArtifact) -> impl IntoView {
+    let href = format!("/api/tasks/{}/artifacts/{}", task_id, artifact.name);
+
+    view! {
+        <li class="listitem" title=artifact.uploaded_at>
+            <span class="small-space"></span>
+            <a href=href download=artifact.name.clone()>
+                <i class="fa-solid fa-download"></i>
+                <span class="small-space"></span>
+                {artifact.name.clone()}
+            </a>
+            <span class="medium-space"></span>
+            {artifact.content_type}
+            <div class="stretch"></div>
+            {format_size(artifact.size)}
+            <span class="small-space"></span>
+        </li>
+    }
+}
+
+fn format_size(size: i64) -> String {
+    match size {
+        size if size < 1024 => format!("{size} B"),
+        size if size < 1024 * 1024 => format!("{:.1} KiB", size as f64 / 1024.0),
+        size => format!("{:.1} MiB", size as f64 / (1024.0 * 1024.0)),
+    }
+}
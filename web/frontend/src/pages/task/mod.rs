@@ -1,22 +1,30 @@
 use std::sync::Arc;
 
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
 use user_api::TaskStatus;
 use web_sys::{window, ScrollBehavior, ScrollToOptions};
 
-use crate::api::use_task;
+use crate::api::{http, use_task};
 use crate::components::*;
+use crate::errors::handle_api_result;
+use crate::routes::paths;
 
+mod artifacts;
 mod interaction_item;
 mod llm_interactions;
 mod logs;
 mod message_item;
 mod parse;
+mod progress;
 mod request;
 mod response;
 
+use artifacts::Artifacts;
 use llm_interactions::LlmInteractions;
 use logs::Logs;
+use progress::Progress;
 
 #[component]
 pub fn TaskPage(id: String) -> impl IntoView {
@@ -31,18 +39,37 @@ pub fn TaskPage(id: String) -> impl IntoView {
 #[component]
 pub fn TaskContent(id: String) -> impl IntoView {
     let task_resource = use_task(id.clone());
+    let navigate = Arc::new(use_navigate());
+    let error_store = expect_context::<RwSignal<crate::errors::ErrorStore>>();
+
+    let on_replay = move |_| {
+        let id = id.clone();
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            let result = http::replay_task(&id).await;
+            if let Ok(replay) = handle_api_result(result, navigate.clone(), &error_store) {
+                navigate(&format!("{}/{}", paths::TASKS, replay.id), Default::default());
+            }
+        });
+    };
 
     move || match task_resource.get().map(|sw| sw.take()) {
         Some(Ok(task)) => {
-            let github_url =
-                format!("https://github.com/{}/issues/{}", task.repo_name, task.issue_number);
+            let github_url = task.issue_url.clone();
             let interactions = task.interactions.clone();
+            let events = task.events.clone();
 
             let initial_interaction_id =
                 interactions.last().map(|interaction| interaction.id.clone());
             let active_interaction_id = RwSignal::new(initial_interaction_id);
             let active_tab = RwSignal::new(0);
-            let tab_labels = vec!["Prompts".to_string(), "Logs".to_string()];
+            let tab_labels = vec![
+                "Prompts".to_string(),
+                "Progress".to_string(),
+                "Logs".to_string(),
+                "Artifacts".to_string(),
+            ];
+            let finished = matches!(task.status, TaskStatus::Completed | TaskStatus::Failed);
 
             let onclick_fab = {
                 let last_interaction_id =
@@ -80,6 +107,31 @@ pub fn TaskContent(id: String) -> impl IntoView {
                         {format!("{}#{}", task.repo_name, task.issue_number)}
                     </h3>
 
+                    {task.replay_of.clone().map(|replay_of| view! {
+                        <p>
+                            "This task replays the LLM interactions of "
+                            <a href=format!("{}/{}", paths::TASKS, replay_of)>"an earlier task"</a>
+                            "."
+                        </p>
+                    })}
+
+                    <p>
+                        {format!(
+                            "LLM usage: {} prompt and {} completion tokens, ${:.2}",
+                            task.llm_usage.prompt_tokens,
+                            task.llm_usage.completion_tokens,
+                            task.llm_usage.cost,
+                        )}
+                    </p>
+
+                    {finished.then(|| view! {
+                        <button on:click=on_replay.clone()>
+                            <i class="fa-solid fa-rotate-right"></i>
+                            <span class="small-space"></span>
+                            "Replay"
+                        </button>
+                    })}
+
                     <TabBar
                         tabs=tab_labels
                         active_tab
@@ -88,8 +140,8 @@ pub fn TaskContent(id: String) -> impl IntoView {
 
                     {
                         move || {
-                            if active_tab.get() == 0 {
-                                view! {
+                            match active_tab.get() {
+                                0 => view! {
                                     <>
                                         <LlmInteractions
                                             interactions=interactions.clone()
@@ -100,15 +152,16 @@ pub fn TaskContent(id: String) -> impl IntoView {
                                         </button>
                                     </>
                                 }
-                                .into_any()
-                            } else {
-                                view! {
+                                .into_any(),
+                                1 => view! { <Progress events=events.clone() /> }.into_any(),
+                                2 => view! {
                                     <Logs
                                         task_id=task.id.clone()
                                         running=task.status == TaskStatus::Running
                                     />
                                 }
-                                .into_any()
+                                .into_any(),
+                                _ => view! { <Artifacts task_id=task.id.clone() /> }.into_any(),
                             }
                         }
                     }
@@ -0,0 +1,47 @@
+use leptos::prelude::*;
+
+use user_api::TaskEvent;
+
+#[component]
+pub fn Progress(events: Vec<TaskEvent>) -> impl IntoView {
+    if events.is_empty() {
+        return view! {
+            <>
+                <p>"The agent has not reported any progress yet."</p>
+            </>
+        }
+        .into_any();
+    }
+
+    view! {
+        <>
+            <p>"Progress the agent reported during this run."</p>
+            <ul class="listbox">
+                <For
+                    each=move || events.clone()
+                    key=|event| event.id.clone()
+                    children=move |event| view! { <EventItem event=event /> }
+                />
+            </ul>
+        </>
+    }
+    .into_any()
+}
+
+#[component]
+fn EventItem(event: TaskEvent) -> impl IntoView {
+    view! {
+        <li class="listitem task-event" title=event.created_at>
+            <span class="small-space"></span>
+            {event.milestone.then(|| view! {
+                <i class="fa-solid fa-flag"></i>
+                <span class="small-space"></span>
+            })}
+            <span class="task-event-step">{event.step}</span>
+            {event.message.map(|message| view! { <span class="medium-space"></span>{message} })}
+            <div class="stretch"></div>
+            {event.percent.map(|percent| format!("{percent}%"))}
+            <span class="small-space"></span>
+        </li>
+    }
+}
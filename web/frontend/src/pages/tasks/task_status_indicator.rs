@@ -8,6 +8,8 @@ pub fn TaskStatusIndicator(status: TaskStatus) -> impl IntoView {
         TaskStatus::Running => "running",
         TaskStatus::Completed => "completed",
         TaskStatus::Failed => "failed",
+        TaskStatus::Cancelled => "cancelled",
+        TaskStatus::WaitingForInput => "waiting-for-input",
     };
 
     let fa_icon = match status {
@@ -15,6 +17,8 @@ pub fn TaskStatusIndicator(status: TaskStatus) -> impl IntoView {
         TaskStatus::Running => "fa-spinner fa-spin",
         TaskStatus::Completed => "fa-check",
         TaskStatus::Failed => "fa-times",
+        TaskStatus::Cancelled => "fa-ban",
+        TaskStatus::WaitingForInput => "fa-circle-question",
     };
 
     let tooltip = match status {
@@ -22,6 +26,8 @@ pub fn TaskStatusIndicator(status: TaskStatus) -> impl IntoView {
         TaskStatus::Running => "Task is running",
         TaskStatus::Completed => "Task is completed",
         TaskStatus::Failed => "Task has failed",
+        TaskStatus::Cancelled => "Task was cancelled",
+        TaskStatus::WaitingForInput => "Task is waiting for an answer on the issue",
     };
 
     let class = format!("task-status {} fa-solid {}", status_class, fa_icon);
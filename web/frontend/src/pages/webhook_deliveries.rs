@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use leptos::prelude::*;
+
+use user_api::{WebhookDelivery, WebhookDeliveryStatus};
+
+use crate::api::use_webhook_deliveries;
+use crate::components::*;
+
+#[component]
+pub fn WebhookDeliveriesPage() -> impl IntoView {
+    let deliveries_content: ChildrenFn =
+        Arc::new(move || view! { <><WebhookDeliveriesPageContent /></> }.into_any());
+    view! {
+        <StandardPage children=deliveries_content />
+    }
+}
+
+#[component]
+pub fn WebhookDeliveriesPageContent() -> impl IntoView {
+    let deliveries = use_webhook_deliveries();
+
+    move || match deliveries.get().map(|sw| sw.take()) {
+        Some(Ok(deliveries)) => view! {
+            <>
+                <h1>"Webhook deliveries"</h1>
+                <p>"Recent webhooks that GitHub sent for the installations you administer."</p>
+                <ul class="listbox">
+                    <For
+                        each=move || deliveries.clone()
+                        key=|delivery| delivery.id.clone()
+                        children=move |delivery| view! { <DeliveryItem delivery=delivery /> }
+                    />
+                </ul>
+            </>
+        }
+        .into_any(),
+        Some(Err(_)) | None => {
+            view! { <></> };
+            ().into_any()
+        }
+    }
+}
+
+#[component]
+fn DeliveryItem(delivery: WebhookDelivery) -> impl IntoView {
+    let status = match delivery.status {
+        WebhookDeliveryStatus::Queued => "Queued".to_owned(),
+        WebhookDeliveryStatus::Processing => "Processing".to_owned(),
+        WebhookDeliveryStatus::Processed => "Processed".to_owned(),
+        WebhookDeliveryStatus::Failed => format!("Failed ({} attempts)", delivery.attempts),
+        WebhookDeliveryStatus::Dead => format!("Gave up ({} attempts)", delivery.attempts),
+    };
+    let event = match delivery.action {
+        Some(action) => format!("{}.{}", delivery.event, action),
+        None => delivery.event,
+    };
+
+    view! {
+        <li class="listitem" title=delivery.delivery_id>
+            <span class="small-space"></span>
+            {delivery.received_at}
+            <span class="medium-space"></span>
+            {event}
+            <div class="stretch"></div>
+            {status}
+            {delivery.error.map(|error| view! { <span class="medium-space"></span>{error} })}
+            <span class="small-space"></span>
+        </li>
+    }
+}
@@ -6,6 +6,7 @@ pub mod paths {
     pub const REPOS: &str = "/repos";
     pub const SETTINGS: &str = "/settings";
     pub const TASKS: &str = "/tasks";
+    pub const WEBHOOK_DELIVERIES: &str = "/webhook-deliveries";
     pub const PRIVACY: &str = "/privacy";
     pub const LEGAL_NOTICE: &str = "/legal-notice";
 }